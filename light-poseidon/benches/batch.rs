@@ -0,0 +1,52 @@
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use light_poseidon::{fixed::FixedPoseidon, Poseidon, PoseidonHasher};
+
+/// Compares hashing pairs one by one with
+/// [`Poseidon::hash_batch`](light_poseidon::Poseidon::hash_batch), which is
+/// parallel (the benchmark requires the `parallel` feature).
+pub fn bench_poseidon_bn254_x5_batch(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let mut group = c.benchmark_group("poseidon_bn254_x5_batch");
+
+    for batch_size in [16, 256, 4096] {
+        let inputs: Vec<[Fr; 2]> = (0..batch_size)
+            .map(|_| [Fr::rand(&mut rng), Fr::rand(&mut rng)])
+            .collect();
+        group.throughput(Throughput::Elements(batch_size as u64));
+
+        let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("sequential", batch_size),
+            &inputs,
+            |b, inputs| {
+                b.iter(|| {
+                    inputs
+                        .iter()
+                        .map(|input| hasher.hash(input).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+
+        let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("batch", batch_size),
+            &inputs,
+            |b, inputs| b.iter(|| hasher.hash_batch(inputs).unwrap()),
+        );
+
+        let hasher = FixedPoseidon::<Fr, 3>::new_circom().unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("fixed_batch", batch_size),
+            &inputs,
+            |b, inputs| b.iter(|| hasher.hash_batch(inputs)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_poseidon_bn254_x5_batch);
+criterion_main!(benches);
@@ -0,0 +1,116 @@
+//! Hashing of many independent inputs at once, e.g. the nodes of a Merkle
+//! tree level.
+//!
+//! With the `parallel` feature, the inputs are split over the
+//! [rayon](https://github.com/rayon-rs/rayon) thread pool. Hashing takes
+//! `&self`, so all threads share one hasher. Without the feature, the inputs
+//! are hashed sequentially. The results are the same in both cases and keep
+//! the order of the inputs.
+//!
+//! ```rust
+//! use light_poseidon::{Poseidon, PoseidonHasher};
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+//! let pairs: Vec<[Fr; 2]> = (0..64u64)
+//!     .map(|i| [Fr::from(2 * i), Fr::from(2 * i + 1)])
+//!     .collect();
+//!
+//! let hashes = poseidon.hash_batch(&pairs).unwrap();
+//!
+//! assert_eq!(hashes[1], poseidon.hash(&pairs[1]).unwrap());
+//! ```
+use alloc::vec::Vec;
+
+use ark_ff::PrimeField;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    fixed::FixedPoseidon, Poseidon, PoseidonBytesHasher, PoseidonError, PoseidonHasher, HASH_LEN,
+};
+
+/// Maps the inputs with `f` on the rayon thread pool.
+#[cfg(feature = "parallel")]
+fn map_inputs<I, R, C>(inputs: &[I], f: impl Fn(&I) -> R + Sync + Send) -> C
+where
+    I: Sync,
+    R: Send,
+    C: FromParallelIterator<R>,
+{
+    inputs.par_iter().map(f).collect()
+}
+
+/// Maps the inputs with `f`.
+#[cfg(not(feature = "parallel"))]
+fn map_inputs<I, R, C>(inputs: &[I], f: impl Fn(&I) -> R) -> C
+where
+    C: FromIterator<R>,
+{
+    inputs.iter().map(f).collect()
+}
+
+/// Returns all results or the error with the lowest index. Collecting into
+/// `Result` directly would return any of the errors with rayon, depending on
+/// which thread finds one first.
+fn first_error<R>(results: Vec<Result<R, PoseidonError>>) -> Result<Vec<R>, PoseidonError> {
+    results.into_iter().collect()
+}
+
+impl<F: PrimeField> Poseidon<F> {
+    /// Calculates the hashes of all inputs, like
+    /// [`PoseidonHasher::hash`](crate::PoseidonHasher::hash) called for each
+    /// of them. `N` has to be `width - 1`.
+    pub fn hash_batch<const N: usize>(&self, inputs: &[[F; N]]) -> Result<Vec<F>, PoseidonError> {
+        self.check_batch_inputs(N)?;
+
+        map_inputs(inputs, |input| self.hash(input))
+    }
+
+    /// Calculates the hashes of all big-endian byte inputs, like
+    /// [`PoseidonBytesHasher::hash_bytes_be`](crate::PoseidonBytesHasher::hash_bytes_be)
+    /// called for each of them. If any inputs are invalid, returns the error
+    /// of the first one (with the lowest index), also with the `parallel`
+    /// feature.
+    pub fn hash_bytes_be_batch<const N: usize>(
+        &self,
+        inputs: &[[&[u8]; N]],
+    ) -> Result<Vec<[u8; HASH_LEN]>, PoseidonError> {
+        self.check_batch_inputs(N)?;
+
+        first_error(map_inputs(inputs, |input| self.hash_bytes_be(input)))
+    }
+
+    /// Calculates the hashes of all little-endian byte inputs, like
+    /// [`PoseidonBytesHasher::hash_bytes_le`](crate::PoseidonBytesHasher::hash_bytes_le)
+    /// called for each of them. If any inputs are invalid, returns the error
+    /// of the first one (with the lowest index), also with the `parallel`
+    /// feature.
+    pub fn hash_bytes_le_batch<const N: usize>(
+        &self,
+        inputs: &[[&[u8]; N]],
+    ) -> Result<Vec<[u8; HASH_LEN]>, PoseidonError> {
+        self.check_batch_inputs(N)?;
+
+        first_error(map_inputs(inputs, |input| self.hash_bytes_le(input)))
+    }
+
+    fn check_batch_inputs(&self, nr_inputs: usize) -> Result<(), PoseidonError> {
+        if nr_inputs != self.params.width - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: nr_inputs,
+                max_limit: self.params.width - 1,
+                width: self.params.width,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField, const T: usize> FixedPoseidon<'_, F, T> {
+    /// Calculates the hashes of all inputs, like
+    /// [`hash`](crate::fixed::FixedPoseidon::hash) called for each of them.
+    pub fn hash_batch<const N: usize>(&self, inputs: &[[F; N]]) -> Vec<F> {
+        map_inputs(inputs, |input| self.hash(input))
+    }
+}
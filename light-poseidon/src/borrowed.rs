@@ -0,0 +1,290 @@
+//! Poseidon hasher borrowing its parameters, e.g. from `static` arrays.
+//!
+//! [`Poseidon::new_circom`](crate::Poseidon::new_circom) converts hundreds of
+//! round constants into Montgomery form and allocates them on every call.
+//! The [`bn254_x5`](crate::parameters::bn254_x5) module also provides the
+//! constants as `static` arrays, which are already in Montgomery form, and
+//! [`get_borrowed_poseidon_parameters`](crate::parameters::bn254_x5::get_borrowed_poseidon_parameters)
+//! returns [`BorrowedPoseidonParameters`](crate::borrowed::BorrowedPoseidonParameters)
+//! pointing to them. Constructing a
+//! [`BorrowedPoseidon`](crate::borrowed::BorrowedPoseidon) with them doesn't
+//! compute or allocate anything.
+//!
+//! Hashing keeps the state on the stack for widths up to 16 and allocates it
+//! for larger widths, which are supported only with the `alloc` feature.
+//! Without it, larger widths result in an error.
+//!
+//! ```rust
+//! use light_poseidon::{borrowed::BorrowedPoseidon, Poseidon, PoseidonHasher};
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = BorrowedPoseidon::new_circom(2).unwrap();
+//! let hash = poseidon.hash(&[Fr::from(1u64), Fr::from(2u64)]).unwrap();
+//!
+//! let owned = Poseidon::<Fr>::new_circom(2).unwrap();
+//! assert_eq!(hash, owned.hash(&[Fr::from(1u64), Fr::from(2u64)]).unwrap());
+//! ```
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, Zero};
+
+#[cfg(feature = "alloc")]
+use crate::PoseidonParameters;
+use crate::{
+    check_stack_width,
+    parameters::bn254_x5,
+    sbox::{self, PowerMap, Sbox},
+    with_stack_state, PoseidonError, PoseidonHasher, MAX_X5_LEN,
+};
+
+/// Parameters for the Poseidon hash algorithm, borrowed instead of owned like
+/// in [`PoseidonParameters`](crate::PoseidonParameters).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BorrowedPoseidonParameters<'a, F: PrimeField> {
+    /// Round constants.
+    pub ark: &'a [F],
+    /// MDS matrix, row by row (`width * width` elements).
+    pub mds: &'a [F],
+    /// Number of full rounds (where S-box is applied to all elements of the
+    /// state).
+    pub full_rounds: usize,
+    /// Number of partial rounds (where S-box is applied only to the first
+    /// element of the state).
+    pub partial_rounds: usize,
+    /// Number of prime fields in the state.
+    pub width: usize,
+    /// S-box applied to elements of the state.
+    pub sbox: Sbox,
+}
+
+impl<'a, F: PrimeField> BorrowedPoseidonParameters<'a, F> {
+    /// Returns parameters borrowing the given round constants and MDS matrix
+    /// (row by row). Returns an error if there isn't exactly `width` round
+    /// constants per round or `width * width` elements of the MDS matrix.
+    pub const fn new(
+        ark: &'a [F],
+        mds: &'a [F],
+        full_rounds: usize,
+        partial_rounds: usize,
+        width: usize,
+        sbox: Sbox,
+    ) -> Result<Self, PoseidonError> {
+        let expected = (full_rounds + partial_rounds) * width;
+        if ark.len() != expected {
+            return Err(PoseidonError::InvalidArkLength {
+                len: ark.len(),
+                expected,
+            });
+        }
+        if width < 2 || mds.len() != width * width {
+            return Err(PoseidonError::InvalidMdsSize { width });
+        }
+
+        Ok(Self {
+            ark,
+            mds,
+            full_rounds,
+            partial_rounds,
+            width,
+            sbox,
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F: PrimeField> From<BorrowedPoseidonParameters<'_, F>> for PoseidonParameters<F> {
+    fn from(params: BorrowedPoseidonParameters<'_, F>) -> Self {
+        PoseidonParameters::new(
+            params.ark.to_vec(),
+            params
+                .mds
+                .chunks(params.width)
+                .map(|row| row.to_vec())
+                .collect(),
+            params.full_rounds,
+            params.partial_rounds,
+            params.width,
+            params.sbox,
+        )
+    }
+}
+
+/// Applies the Poseidon permutation defined by borrowed `params` to the given
+/// state, like [`permute`](crate::permute).
+pub fn permute<F: PrimeField>(
+    params: &BorrowedPoseidonParameters<'_, F>,
+    state: &mut [F],
+) -> Result<(), PoseidonError> {
+    if state.len() != params.width {
+        return Err(PoseidonError::InvalidStateLength {
+            len: state.len(),
+            width: params.width,
+        });
+    }
+    check_stack_width(params.width)?;
+
+    let rounds: RoundsFn<F> = sbox::select_rounds!(apply_rounds_with, params.sbox);
+    rounds(params, state);
+    Ok(())
+}
+
+#[inline(always)]
+fn apply_ark<F: PrimeField>(
+    params: &BorrowedPoseidonParameters<'_, F>,
+    state: &mut [F],
+    round: usize,
+) {
+    state
+        .iter_mut()
+        .zip(&params.ark[round * params.width..(round + 1) * params.width])
+        .for_each(|(a, c)| *a += c);
+}
+
+#[inline(always)]
+fn apply_mds<F: PrimeField>(params: &BorrowedPoseidonParameters<'_, F>, state: &mut [F]) {
+    with_stack_state(params.width, |result| {
+        for (r, row) in result.iter_mut().zip(params.mds.chunks(params.width)) {
+            *r = row
+                .iter()
+                .zip(state.iter())
+                .fold(F::zero(), |acc, (m, a)| acc + *m * a);
+        }
+        state.copy_from_slice(result);
+    })
+}
+
+/// Applies all rounds of the permutation with the S-box map `P`.
+fn apply_rounds_with<F: PrimeField, P: PowerMap>(
+    params: &BorrowedPoseidonParameters<'_, F>,
+    state: &mut [F],
+) {
+    let all_rounds = params.full_rounds + params.partial_rounds;
+    let half_rounds = params.full_rounds / 2;
+
+    for round in 0..all_rounds {
+        apply_ark(params, state, round);
+        if round < half_rounds || round >= half_rounds + params.partial_rounds {
+            P::apply_all(state, params.sbox);
+        } else {
+            state[0] = P::apply(state[0], params.sbox);
+        }
+        apply_mds(params, state);
+    }
+}
+
+/// Function applying all rounds of the permutation, specialized for the
+/// exponent of the S-box. Generic over the lifetime of the parameters.
+type RoundsFn<F> = fn(&BorrowedPoseidonParameters<'_, F>, &mut [F]);
+
+/// A Poseidon hasher using [`BorrowedPoseidonParameters`](crate::borrowed::BorrowedPoseidonParameters).
+/// Produces the same hashes as [`Poseidon`](crate::Poseidon) with the same
+/// parameters.
+pub struct BorrowedPoseidon<'a, F: PrimeField> {
+    params: BorrowedPoseidonParameters<'a, F>,
+    domain_tag: F,
+    rounds: RoundsFn<F>,
+}
+
+impl<'a, F: PrimeField> BorrowedPoseidon<'a, F> {
+    /// Returns a hasher based on the given borrowed parameters, with zero as
+    /// the domain tag.
+    pub fn new(params: BorrowedPoseidonParameters<'a, F>) -> Self {
+        Self::with_domain_tag(params, F::zero())
+    }
+
+    /// Returns a hasher which puts the given domain tag in the first element
+    /// of the state, like [`Poseidon::with_domain_tag_circom`](crate::Poseidon::with_domain_tag_circom).
+    pub fn with_domain_tag(params: BorrowedPoseidonParameters<'a, F>, domain_tag: F) -> Self {
+        Self {
+            domain_tag,
+            rounds: sbox::select_rounds!(apply_rounds_with, params.sbox),
+            params,
+        }
+    }
+
+    /// The borrowed parameters of this hasher.
+    pub fn params(&self) -> &BorrowedPoseidonParameters<'a, F> {
+        &self.params
+    }
+
+    /// Applies the Poseidon permutation to the given state, using the
+    /// parameters of this hasher.
+    pub fn permute(&self, state: &mut [F]) -> Result<(), PoseidonError> {
+        if state.len() != self.params.width {
+            return Err(PoseidonError::InvalidStateLength {
+                len: state.len(),
+                width: self.params.width,
+            });
+        }
+        check_stack_width(self.params.width)?;
+
+        (self.rounds)(&self.params, state);
+        Ok(())
+    }
+
+    /// Calls `f` with a buffer for `len` inputs, kept on the stack like the
+    /// state. Returns an error if `len` isn't the number of inputs of this
+    /// hasher.
+    pub(crate) fn with_inputs_buffer(
+        &self,
+        len: usize,
+        f: impl FnOnce(&mut [F]) -> Result<F, PoseidonError>,
+    ) -> Result<F, PoseidonError> {
+        if len != self.params.width - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: len,
+                max_limit: self.params.width - 1,
+                width: self.params.width,
+            });
+        }
+        check_stack_width(self.params.width)?;
+
+        with_stack_state(len, f)
+    }
+}
+
+impl<F: PrimeField> PoseidonHasher<F> for BorrowedPoseidon<'_, F> {
+    fn hash(&self, inputs: &[F]) -> Result<F, PoseidonError> {
+        if inputs.len() != self.params.width - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: inputs.len(),
+                max_limit: self.params.width - 1,
+                width: self.params.width,
+            });
+        }
+        check_stack_width(self.params.width)?;
+
+        Ok(with_stack_state(self.params.width, |state| {
+            state[0] = self.domain_tag;
+            state[1..].copy_from_slice(inputs);
+            (self.rounds)(&self.params, state);
+            state[0]
+        }))
+    }
+}
+
+impl BorrowedPoseidon<'static, Fr> {
+    /// Returns a hasher for the given number of inputs, borrowing the
+    /// circom-compatible BN254 parameters from the `static` arrays of
+    /// [`bn254_x5`](crate::parameters::bn254_x5).
+    pub fn new_circom(nr_inputs: usize) -> Result<Self, PoseidonError> {
+        Self::with_domain_tag_circom(nr_inputs, Fr::zero())
+    }
+
+    /// Returns a hasher for the given number of inputs like
+    /// [`new_circom`](crate::borrowed::BorrowedPoseidon::new_circom), which
+    /// puts the given domain tag in the first element of the state.
+    pub fn with_domain_tag_circom(nr_inputs: usize, domain_tag: Fr) -> Result<Self, PoseidonError> {
+        let width = nr_inputs + 1;
+        if width > MAX_X5_LEN {
+            return Err(PoseidonError::InvalidWidthCircom {
+                width,
+                max_limit: MAX_X5_LEN,
+            });
+        }
+
+        let params = bn254_x5::get_borrowed_poseidon_parameters(
+            width.try_into().map_err(|_| PoseidonError::U64Tou8)?,
+        )?;
+        Ok(Self::with_domain_tag(params, domain_tag))
+    }
+}
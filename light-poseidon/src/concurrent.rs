@@ -0,0 +1,272 @@
+//! Concurrent Merkle tree with a changelog, like the on-chain Merkle tree
+//! accounts of Light Protocol (and SPL account compression).
+//!
+//! Leaves are hashed into nodes with `Poseidon::<Fr>::new_circom(2)`, in the
+//! same layout as [`MerkleTree`](crate::merkle::MerkleTree). In addition to
+//! appending, leaves can be replaced with proofs created against one of the
+//! recent roots: every modification of the tree is recorded in a changelog
+//! of the last `CHANGELOG` entries, which is used to fast-forward a stale
+//! proof to the current root before verifying it. This way, multiple
+//! modifications based on the same root (e.g. transactions submitted
+//! concurrently) can all be applied.
+//!
+//! The tree also keeps the history of the last `ROOTS` roots, which can be
+//! checked with [`is_known_root`](crate::concurrent::ConcurrentMerkleTree::is_known_root).
+//!
+//! ```rust
+//! use light_poseidon::{concurrent::ConcurrentMerkleTree, merkle::MerkleTree};
+//! use ark_bn254::Fr;
+//!
+//! let mut tree = ConcurrentMerkleTree::<4, 16, 16>::new().unwrap();
+//! // Proofs are usually provided by an indexer keeping all the leaves.
+//! let mut indexer = MerkleTree::new(4).unwrap();
+//! for leaf in [Fr::from(1u64), Fr::from(2u64)] {
+//!     tree.append(leaf).unwrap();
+//!     indexer.append(leaf).unwrap();
+//! }
+//!
+//! // Proofs of both leaves are created against the same root...
+//! let root = tree.root();
+//! let proof_0: [Fr; 4] = indexer.proof(0).unwrap().path_elements.try_into().unwrap();
+//! let proof_1: [Fr; 4] = indexer.proof(1).unwrap().path_elements.try_into().unwrap();
+//!
+//! // ...and both replacements succeed.
+//! tree.replace_leaf(root, Fr::from(1u64), Fr::from(3u64), 0, &proof_0)
+//!     .unwrap();
+//! tree.replace_leaf(root, Fr::from(2u64), Fr::from(4u64), 1, &proof_1)
+//!     .unwrap();
+//! ```
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+
+use crate::{merkle, Poseidon, PoseidonError, PoseidonHasher};
+
+/// Maximum height of the tree.
+pub const MAX_HEIGHT: usize = merkle::MAX_HEIGHT;
+
+/// Record of a single modification of the tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangelogEntry<const HEIGHT: usize> {
+    /// Root after the modification.
+    pub root: Fr,
+    /// New nodes on the path of the modified leaf, starting with the leaf.
+    pub path: [Fr; HEIGHT],
+    /// Index of the modified leaf.
+    pub index: usize,
+}
+
+/// Merkle tree of the height `HEIGHT`, which keeps the last `CHANGELOG`
+/// changelog entries and the last `ROOTS` roots.
+pub struct ConcurrentMerkleTree<const HEIGHT: usize, const CHANGELOG: usize, const ROOTS: usize> {
+    poseidon: Poseidon<Fr>,
+    zero_hashes: Vec<Fr>,
+    /// Left nodes of every level on the path of the rightmost leaf, used for
+    /// appending.
+    filled_subtrees: [Fr; HEIGHT],
+    next_index: usize,
+    sequence_number: usize,
+    changelog: VecDeque<ChangelogEntry<HEIGHT>>,
+    roots: VecDeque<Fr>,
+}
+
+impl<const HEIGHT: usize, const CHANGELOG: usize, const ROOTS: usize>
+    ConcurrentMerkleTree<HEIGHT, CHANGELOG, ROOTS>
+{
+    /// Returns an empty tree with zero as the zero leaf. `HEIGHT` has to be
+    /// between 1 and 31, `CHANGELOG` and `ROOTS` greater than 0.
+    pub fn new() -> Result<Self, PoseidonError> {
+        if HEIGHT == 0 || HEIGHT > MAX_HEIGHT {
+            return Err(PoseidonError::InvalidMerkleTreeHeight {
+                height: HEIGHT,
+                max_limit: MAX_HEIGHT,
+            });
+        }
+        if CHANGELOG == 0 || ROOTS == 0 {
+            return Err(PoseidonError::InvalidMerkleTreeBufferSize {
+                changelog: CHANGELOG,
+                roots: ROOTS,
+            });
+        }
+
+        let zero_hashes = merkle::zero_hashes(HEIGHT, Fr::zero())?;
+        let mut filled_subtrees = [Fr::zero(); HEIGHT];
+        filled_subtrees.copy_from_slice(&zero_hashes[..HEIGHT]);
+        let root = zero_hashes[HEIGHT];
+
+        let mut tree = Self {
+            poseidon: Poseidon::<Fr>::new_circom(2)?,
+            zero_hashes,
+            filled_subtrees,
+            next_index: 0,
+            sequence_number: 0,
+            changelog: VecDeque::with_capacity(CHANGELOG),
+            roots: VecDeque::with_capacity(ROOTS),
+        };
+        // The empty tree is the first entry, so proofs against it can be
+        // fast-forwarded too.
+        tree.push_changelog_entry(ChangelogEntry {
+            root,
+            path: filled_subtrees,
+            index: 0,
+        });
+        Ok(tree)
+    }
+
+    /// Maximum number of leaves.
+    pub fn capacity(&self) -> usize {
+        1 << HEIGHT
+    }
+
+    /// Index of the next appended leaf.
+    pub fn next_index(&self) -> usize {
+        self.next_index
+    }
+
+    /// Number of modifications of the tree.
+    pub fn sequence_number(&self) -> usize {
+        self.sequence_number
+    }
+
+    /// Current root of the tree.
+    pub fn root(&self) -> Fr {
+        // There is always at least the root of the empty tree.
+        self.roots[self.roots.len() - 1]
+    }
+
+    /// Recent roots, from the oldest to the current one.
+    pub fn roots(&self) -> impl Iterator<Item = &Fr> {
+        self.roots.iter()
+    }
+
+    /// Checks whether the root is one of the last `ROOTS` roots.
+    pub fn is_known_root(&self, root: &Fr) -> bool {
+        self.roots.contains(root)
+    }
+
+    /// Recent changelog entries, from the oldest to the last one.
+    pub fn changelog(&self) -> impl Iterator<Item = &ChangelogEntry<HEIGHT>> {
+        self.changelog.iter()
+    }
+
+    /// Appends the leaf and returns its index.
+    pub fn append(&mut self, leaf: Fr) -> Result<usize, PoseidonError> {
+        let index = self.next_index;
+        if index == self.capacity() {
+            return Err(PoseidonError::MerkleTreeFull {
+                capacity: self.capacity(),
+            });
+        }
+
+        let mut path = [Fr::zero(); HEIGHT];
+        let mut node = leaf;
+        for (level, path_node) in path.iter_mut().enumerate() {
+            *path_node = node;
+            node = if (index >> level) & 1 == 0 {
+                self.filled_subtrees[level] = node;
+                self.poseidon.hash(&[node, self.zero_hashes[level]])?
+            } else {
+                self.poseidon.hash(&[self.filled_subtrees[level], node])?
+            };
+        }
+
+        self.next_index += 1;
+        self.sequence_number += 1;
+        self.push_changelog_entry(ChangelogEntry {
+            root: node,
+            path,
+            index,
+        });
+        Ok(index)
+    }
+
+    /// Replaces `old_leaf` with `new_leaf` at the given index.
+    ///
+    /// The `proof` (sibling nodes from the leaf level up) can be created
+    /// against any `root` still present in the changelog. It is
+    /// fast-forwarded through the changelog entries recorded after `root` and
+    /// then verified against the current root. Fails if the leaf was modified
+    /// after `root`.
+    pub fn replace_leaf(
+        &mut self,
+        root: Fr,
+        old_leaf: Fr,
+        new_leaf: Fr,
+        index: usize,
+        proof: &[Fr; HEIGHT],
+    ) -> Result<(), PoseidonError> {
+        if index >= self.next_index {
+            return Err(PoseidonError::InvalidLeafIndex {
+                index,
+                len: self.next_index,
+            });
+        }
+
+        let position = self
+            .changelog
+            .iter()
+            .rposition(|entry| entry.root == root)
+            .ok_or(PoseidonError::MerkleRootNotFound)?;
+        let mut proof = *proof;
+        for entry in self.changelog.iter().skip(position + 1) {
+            if entry.index == index {
+                return Err(PoseidonError::MerkleLeafModified { index });
+            }
+            // The paths meet at the level of the most significant differing
+            // bit, where the node of the modified path is the sibling.
+            let level = (usize::BITS - 1 - (entry.index ^ index).leading_zeros()) as usize;
+            proof[level] = entry.path[level];
+        }
+
+        let mut old_node = old_leaf;
+        let mut node = new_leaf;
+        let mut path = [Fr::zero(); HEIGHT];
+        for (level, sibling) in proof.iter().enumerate() {
+            path[level] = node;
+            (old_node, node) = if (index >> level) & 1 == 0 {
+                (
+                    self.poseidon.hash(&[old_node, *sibling])?,
+                    self.poseidon.hash(&[node, *sibling])?,
+                )
+            } else {
+                (
+                    self.poseidon.hash(&[*sibling, old_node])?,
+                    self.poseidon.hash(&[*sibling, node])?,
+                )
+            };
+        }
+        if old_node != self.root() {
+            return Err(PoseidonError::InvalidMerkleProof);
+        }
+
+        // Update the left nodes on the path of the rightmost leaf, if they
+        // are on the modified path.
+        let rightmost = self.next_index - 1;
+        for (level, path_node) in path.iter().enumerate() {
+            if index >> level == (rightmost >> level) & !1 {
+                self.filled_subtrees[level] = *path_node;
+            }
+        }
+
+        self.sequence_number += 1;
+        self.push_changelog_entry(ChangelogEntry {
+            root: node,
+            path,
+            index,
+        });
+        Ok(())
+    }
+
+    fn push_changelog_entry(&mut self, entry: ChangelogEntry<HEIGHT>) {
+        if self.changelog.len() == CHANGELOG {
+            self.changelog.pop_front();
+        }
+        if self.roots.len() == ROOTS {
+            self.roots.pop_front();
+        }
+        self.roots.push_back(entry.root);
+        self.changelog.push_back(entry);
+    }
+}
@@ -0,0 +1,214 @@
+//! Poseidon hasher with the width `T` known at compile time.
+//!
+//! [`FixedPoseidon`](crate::fixed::FixedPoseidon) keeps the state in `[F; T]`
+//! and the MDS matrix in `[[F; T]; T]`, so hashing doesn't allocate and the
+//! compiler can unroll the loops over the state. The round constants are
+//! borrowed from [`BorrowedPoseidonParameters`](crate::borrowed::BorrowedPoseidonParameters),
+//! e.g. from the `static` arrays of [`bn254_x5`](crate::parameters::bn254_x5).
+//!
+//! The number of inputs of [`hash`](crate::fixed::FixedPoseidon::hash) is
+//! checked at compile time:
+//!
+//! ```rust
+//! use light_poseidon::{fixed::FixedPoseidon, Poseidon, PoseidonHasher};
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = FixedPoseidon::<Fr, 3>::new_circom().unwrap();
+//! let hash = poseidon.hash(&[Fr::from(1u64), Fr::from(2u64)]);
+//!
+//! let dynamic = Poseidon::<Fr>::new_circom(2).unwrap();
+//! assert_eq!(hash, dynamic.hash(&[Fr::from(1u64), Fr::from(2u64)]).unwrap());
+//! ```
+//!
+//! ```compile_fail
+//! use light_poseidon::fixed::FixedPoseidon;
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = FixedPoseidon::<Fr, 3>::new_circom().unwrap();
+//! // Three inputs don't fit in the width 3.
+//! let hash = poseidon.hash(&[Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)]);
+//! ```
+//!
+//! [`Poseidon`](crate::Poseidon) is still the choice for widths picked at
+//! runtime.
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, Zero};
+
+use crate::{
+    borrowed::BorrowedPoseidonParameters,
+    parameters::bn254_x5,
+    sbox::{self, PowerMap, Sbox},
+    PoseidonError, PoseidonHasher,
+};
+
+/// Function applying all rounds of the permutation, specialized for the
+/// exponent of the S-box and the width.
+type RoundsFn<F, const T: usize> = fn(&FixedPoseidon<'_, F, T>, &mut [F; T]);
+
+/// A Poseidon hasher of the width `T`. Produces the same hashes as
+/// [`Poseidon`](crate::Poseidon) with the same parameters.
+pub struct FixedPoseidon<'a, F: PrimeField, const T: usize> {
+    /// Round constants.
+    ark: &'a [F],
+    mds: [[F; T]; T],
+    full_rounds: usize,
+    partial_rounds: usize,
+    sbox: Sbox,
+    domain_tag: F,
+    rounds: RoundsFn<F, T>,
+}
+
+impl<'a, F: PrimeField, const T: usize> FixedPoseidon<'a, F, T> {
+    /// Returns a hasher based on the given parameters, which have to be of
+    /// the width `T`.
+    pub fn new(params: BorrowedPoseidonParameters<'a, F>) -> Result<Self, PoseidonError> {
+        Self::with_domain_tag(params, F::zero())
+    }
+
+    /// Returns a hasher which puts the given domain tag in the first element
+    /// of the state, like [`Poseidon::with_domain_tag_circom`](crate::Poseidon::with_domain_tag_circom).
+    pub fn with_domain_tag(
+        params: BorrowedPoseidonParameters<'a, F>,
+        domain_tag: F,
+    ) -> Result<Self, PoseidonError> {
+        if params.width != T {
+            return Err(PoseidonError::UnexpectedWidth {
+                width: params.width,
+                expected: T,
+            });
+        }
+        let expected = (params.full_rounds + params.partial_rounds) * T;
+        if params.ark.len() != expected {
+            return Err(PoseidonError::InvalidArkLength {
+                len: params.ark.len(),
+                expected,
+            });
+        }
+        if params.mds.len() != T * T {
+            return Err(PoseidonError::InvalidMdsSize { width: T });
+        }
+
+        let mut mds = [[F::zero(); T]; T];
+        for (row, params_row) in mds.iter_mut().zip(params.mds.chunks(T)) {
+            row.copy_from_slice(params_row);
+        }
+
+        Ok(Self {
+            ark: params.ark,
+            mds,
+            full_rounds: params.full_rounds,
+            partial_rounds: params.partial_rounds,
+            sbox: params.sbox,
+            domain_tag,
+            rounds: sbox::select_rounds!(apply_rounds_with, params.sbox, T),
+        })
+    }
+
+    /// Applies the Poseidon permutation to the given state.
+    pub fn permute(&self, state: &mut [F; T]) {
+        (self.rounds)(self, state);
+    }
+
+    /// Calculates the hash of `T - 1` inputs. Passing a different number of
+    /// inputs fails to compile.
+    pub fn hash<const N: usize>(&self, inputs: &[F; N]) -> F {
+        const { assert!(N + 1 == T, "the number of inputs has to be T - 1") };
+
+        let mut state = [self.domain_tag; T];
+        state[1..].copy_from_slice(inputs);
+        self.permute(&mut state);
+        state[0]
+    }
+}
+
+impl<F: PrimeField, const T: usize> FixedPoseidon<'_, F, T> {
+    /// Calls `f` with a buffer for `len` inputs on the stack. Returns an error
+    /// if `len` isn't `T - 1`.
+    pub(crate) fn with_inputs_buffer(
+        &self,
+        len: usize,
+        f: impl FnOnce(&mut [F]) -> Result<F, PoseidonError>,
+    ) -> Result<F, PoseidonError> {
+        if len != T - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: len,
+                max_limit: T - 1,
+                width: T,
+            });
+        }
+
+        let mut buffer = [F::zero(); T];
+        f(&mut buffer[..len])
+    }
+}
+
+impl<F: PrimeField, const T: usize> PoseidonHasher<F> for FixedPoseidon<'_, F, T> {
+    /// Calculates the hash of a slice of `T - 1` inputs, checked at runtime.
+    fn hash(&self, inputs: &[F]) -> Result<F, PoseidonError> {
+        if inputs.len() != T - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: inputs.len(),
+                max_limit: T - 1,
+                width: T,
+            });
+        }
+
+        let mut state = [self.domain_tag; T];
+        state[1..].copy_from_slice(inputs);
+        self.permute(&mut state);
+        Ok(state[0])
+    }
+}
+
+impl<const T: usize> FixedPoseidon<'static, Fr, T> {
+    /// Returns a hasher of `T - 1` inputs (`2 ≤ T ≤ 13`), borrowing the
+    /// circom-compatible BN254 parameters from the `static` arrays of
+    /// [`bn254_x5`](crate::parameters::bn254_x5).
+    pub fn new_circom() -> Result<Self, PoseidonError> {
+        Self::with_domain_tag_circom(Fr::zero())
+    }
+
+    pub fn with_domain_tag_circom(domain_tag: Fr) -> Result<Self, PoseidonError> {
+        let width = T.try_into().map_err(|_| PoseidonError::U64Tou8)?;
+        let params = bn254_x5::get_borrowed_poseidon_parameters(width)?;
+        Self::with_domain_tag(params, domain_tag)
+    }
+}
+
+#[inline(always)]
+fn apply_ark<F: PrimeField, const T: usize>(ark: &[F], state: &mut [F; T], round: usize) {
+    for (a, c) in state.iter_mut().zip(&ark[round * T..(round + 1) * T]) {
+        *a += c;
+    }
+}
+
+#[inline(always)]
+fn apply_mds<F: PrimeField, const T: usize>(mds: &[[F; T]; T], state: &mut [F; T]) {
+    let mut result = [F::zero(); T];
+    for (r, row) in result.iter_mut().zip(mds) {
+        *r = row
+            .iter()
+            .zip(state.iter())
+            .fold(F::zero(), |acc, (m, a)| acc + *m * a);
+    }
+    *state = result;
+}
+
+/// Applies all rounds of the permutation with the S-box map `P`.
+fn apply_rounds_with<F: PrimeField, P: PowerMap, const T: usize>(
+    poseidon: &FixedPoseidon<'_, F, T>,
+    state: &mut [F; T],
+) {
+    let all_rounds = poseidon.full_rounds + poseidon.partial_rounds;
+    let half_rounds = poseidon.full_rounds / 2;
+
+    for round in 0..all_rounds {
+        apply_ark(poseidon.ark, state, round);
+        if round < half_rounds || round >= half_rounds + poseidon.partial_rounds {
+            P::apply_all(state, poseidon.sbox);
+        } else {
+            state[0] = P::apply(state[0], poseidon.sbox);
+        }
+        apply_mds(&poseidon.mds, state);
+    }
+}
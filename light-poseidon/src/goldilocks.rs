@@ -0,0 +1,14 @@
+//! The 64-bit Goldilocks prime field (*p = 2^64 - 2^32 + 1*), used by
+//! [Plonky2](https://github.com/0xPolygonZero/plonky2) and the
+//! [`goldilocks_x7`](crate::parameters::goldilocks_x7) parameters.
+// The `MontConfig` derive emits code gated on the `asm` feature of ark-ff.
+#![allow(unexpected_cfgs)]
+
+use ark_ff::fields::{Fp64, MontBackend, MontConfig};
+
+#[derive(MontConfig)]
+#[modulus = "18446744069414584321"]
+#[generator = "7"]
+pub struct GoldilocksConfig;
+/// The Goldilocks field.
+pub type Goldilocks = Fp64<MontBackend<GoldilocksConfig, 1>>;
@@ -0,0 +1,214 @@
+//! Generation of Poseidon parameters with the Grain LFSR.
+//!
+//! This module is a Rust port of the parameter generation from the
+//! [generate_parameters_grain.sage](https://extgit.iaik.tugraz.at/krypto/hadeshash/-/blob/master/code/generate_parameters_grain.sage)
+//! script from the Poseidon paper and of its
+//! [Poseidon2 variant](https://github.com/HorizenLabs/poseidon2/blob/main/poseidon2_rust_params.sage).
+//! It produces exactly the same round constants and MDS matrices, without
+//! the need of SageMath.
+//!
+//! ```rust
+//! use light_poseidon::{grain, parameters::bn254_x5, Sbox};
+//! use ark_bn254::Fr;
+//!
+//! let params = grain::generate_parameters::<Fr>(3, 8, 57, Sbox::Power(5));
+//! let expected = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+//!
+//! assert_eq!(params.ark, expected.ark);
+//! assert_eq!(params.mds, expected.mds);
+//! ```
+use alloc::vec::Vec;
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::{matrix::has_duplicates, mds, PoseidonParameters, Sbox};
+
+const STATE_LEN: usize = 80;
+
+/// Grain LFSR used as a pseudo-random generator of Poseidon parameters,
+/// seeded with the description of the instance.
+pub struct PoseidonGrainLfsr {
+    state: [bool; STATE_LEN],
+    head: usize,
+    field_size: u64,
+}
+
+impl PoseidonGrainLfsr {
+    /// Returns a new LFSR for a prime field of `field_size` bits and the
+    /// given instance. `sbox` is `0` for *x^alpha* S-boxes and `1` for
+    /// *x^-1* S-boxes.
+    pub fn new(
+        sbox: u64,
+        field_size: u64,
+        width: u64,
+        full_rounds: u64,
+        partial_rounds: u64,
+    ) -> Self {
+        let mut state = [true; STATE_LEN];
+        let mut i = 0;
+        // The first 2 bits describe the field (1 = GF(p)), the last 30 bits
+        // are ones.
+        for (value, len) in [
+            (1, 2),
+            (sbox, 4),
+            (field_size, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+        ] {
+            for bit in (0..len).rev() {
+                state[i] = (value >> bit) & 1 == 1;
+                i += 1;
+            }
+        }
+
+        let mut lfsr = Self {
+            state,
+            head: 0,
+            field_size,
+        };
+        for _ in 0..160 {
+            lfsr.update();
+        }
+        lfsr
+    }
+
+    /// Shifts the register and returns the new bit.
+    #[inline(always)]
+    fn update(&mut self) -> bool {
+        let bit = |i: usize| self.state[(self.head + i) % STATE_LEN];
+        let new_bit = bit(62) ^ bit(51) ^ bit(38) ^ bit(23) ^ bit(13) ^ bit(0);
+        self.state[self.head] = new_bit;
+        self.head = (self.head + 1) % STATE_LEN;
+        new_bit
+    }
+
+    /// Returns the next output bit. The output is filtered - a bit is
+    /// returned only if the preceding bit is one.
+    fn next_bit(&mut self) -> bool {
+        while !self.update() {
+            self.update();
+        }
+        self.update()
+    }
+
+    /// Returns the next `n` output bits, the most significant bit first.
+    pub fn get_bits(&mut self, n: usize) -> Vec<bool> {
+        (0..n).map(|_| self.next_bit()).collect()
+    }
+
+    /// Returns `n` prime field elements, sampling `field_size` bits for each
+    /// of them and rejecting values which are not smaller than the modulus.
+    pub fn get_field_elements_rejection_sampling<F: PrimeField>(&mut self, n: usize) -> Vec<F> {
+        (0..n)
+            .map(|_| loop {
+                let bits = self.get_bits(self.field_size as usize);
+                if let Some(element) = F::from_bigint(F::BigInt::from_bits_be(&bits)) {
+                    break element;
+                }
+            })
+            .collect()
+    }
+
+    /// Returns `n` prime field elements, sampling `field_size` bits for each
+    /// of them and reducing them modulo the modulus.
+    pub fn get_field_elements_mod_p<F: PrimeField>(&mut self, n: usize) -> Vec<F> {
+        (0..n)
+            .map(|_| {
+                let bits = self.get_bits(self.field_size as usize);
+                F::from_be_bytes_mod_order(&F::BigInt::from_bits_be(&bits).to_bytes_be())
+            })
+            .collect()
+    }
+}
+
+/// Generates `(full_rounds + partial_rounds) * width` round constants.
+pub fn generate_round_constants<F: PrimeField>(
+    lfsr: &mut PoseidonGrainLfsr,
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+) -> Vec<F> {
+    lfsr.get_field_elements_rejection_sampling((full_rounds + partial_rounds) * width)
+}
+
+/// Generates a `width x width` Cauchy matrix `M[i][j] = 1 / (x_i + y_j)`,
+/// with distinct `x_i` and `y_j` elements sampled from the LFSR. Matrices
+/// which don't pass [`mds::check_mds_security`](crate::mds::check_mds_security)
+/// are rejected.
+pub fn generate_cauchy_matrix<F: PrimeField>(
+    lfsr: &mut PoseidonGrainLfsr,
+    width: usize,
+) -> Vec<Vec<F>> {
+    loop {
+        let mut elements = lfsr.get_field_elements_mod_p::<F>(2 * width);
+        while has_duplicates(&elements) {
+            elements = lfsr.get_field_elements_mod_p::<F>(2 * width);
+        }
+        let (xs, ys) = elements.split_at(width);
+
+        let mds: Option<Vec<Vec<F>>> = xs
+            .iter()
+            .map(|x| ys.iter().map(|y| (*x + y).inverse()).collect())
+            .collect();
+        if let Some(mds) = mds {
+            if mds::check_mds_security(&mds, width).is_ok() {
+                return mds;
+            }
+        }
+    }
+}
+
+/// Generates Poseidon parameters with the given S-box for the prime field
+/// `F`, the same way as `generate_parameters_grain.sage`.
+pub fn generate_parameters<F: PrimeField>(
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    sbox: Sbox,
+) -> PoseidonParameters<F> {
+    generate_parameters_with_sbox_flag(sbox.grain_flag(), width, full_rounds, partial_rounds, sbox)
+}
+
+/// Generates Poseidon parameters with the given S-box for the prime field
+/// `F`, seeding the LFSR with the given S-box type.
+///
+/// The S-box type should be `0` for *x^alpha* S-boxes, but some
+/// implementations (e.g. neptune for BLS12-381) generated their
+/// parameters with `1`, which is meant for *x^-1* S-boxes.
+pub fn generate_parameters_with_sbox_flag<F: PrimeField>(
+    sbox_flag: u64,
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    sbox: Sbox,
+) -> PoseidonParameters<F> {
+    let mut lfsr = PoseidonGrainLfsr::new(
+        sbox_flag,
+        F::MODULUS_BIT_SIZE.into(),
+        width as u64,
+        full_rounds as u64,
+        partial_rounds as u64,
+    );
+    let ark = generate_round_constants(&mut lfsr, width, full_rounds, partial_rounds);
+    let mds = generate_cauchy_matrix(&mut lfsr, width);
+
+    PoseidonParameters::new(ark, mds, full_rounds, partial_rounds, width, sbox)
+}
+
+/// Generates Poseidon2 round constants with *x^alpha* S-boxes for the prime
+/// field `F` - `width` constants for each full round and one constant for each
+/// partial round, the same way as `poseidon2_rust_params.sage`.
+pub fn generate_poseidon2_round_constants<F: PrimeField>(
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+) -> Vec<F> {
+    let mut lfsr = PoseidonGrainLfsr::new(
+        0,
+        F::MODULUS_BIT_SIZE.into(),
+        width as u64,
+        full_rounds as u64,
+        partial_rounds as u64,
+    );
+    lfsr.get_field_elements_rejection_sampling(full_rounds * width + partial_rounds)
+}
@@ -0,0 +1,133 @@
+//! Poseidon hash compatible with
+//! [halo2_gadgets](https://github.com/zcash/halo2/tree/main/halo2_gadgets/src/poseidon).
+//!
+//! [`Halo2Poseidon`](crate::halo2::Halo2Poseidon) computes the same output as
+//! `poseidon::Hash<_, _, ConstantLength<L>, WIDTH, RATE>` from halo2_gadgets,
+//! which differs from [`Poseidon`](crate::Poseidon) in the layout of the
+//! state:
+//!
+//! * the rate part comes first (the first `width - 1` elements) and the
+//!   capacity element is the last one,
+//! * the capacity element is initialized with `L * 2^64`, where `L` is the
+//!   number of inputs,
+//! * inputs are added to the rate part in chunks, padded with zeros to a
+//!   multiple of the rate, and the state is permuted after each chunk,
+//! * the hash is the first element of the state.
+//!
+//! With the [`pallas_x5`](crate::parameters::pallas_x5) and
+//! [`vesta_x5`](crate::parameters::vesta_x5) parameters for *t = 3*, it's
+//! equivalent to `poseidon::Hash<_, P128Pow5T3, ConstantLength<L>, 3, 2>`
+//! used in Orchard:
+//!
+//! ```rust
+//! use light_poseidon::{halo2::Halo2Poseidon, pasta::Fp, PoseidonHasher};
+//!
+//! let poseidon = Halo2Poseidon::<Fp>::new_pallas(2).unwrap();
+//!
+//! let hash = poseidon.hash(&[Fp::from(1u64), Fp::from(2u64)]).unwrap();
+//!
+//! // Do something with `hash`.
+//! ```
+use ark_ff::PrimeField;
+
+use crate::{
+    parameters, pasta, with_stack_state, Poseidon, PoseidonError, PoseidonHasher,
+    PoseidonParameters,
+};
+
+/// Width of the `P128Pow5T3` parameters.
+const P128_POW5_T3_WIDTH: u8 = 3;
+
+/// A Poseidon hasher over a constant number of inputs, compatible with
+/// halo2_gadgets.
+pub struct Halo2Poseidon<F: PrimeField> {
+    poseidon: Poseidon<F>,
+    nr_inputs: usize,
+}
+
+impl<F: PrimeField> Halo2Poseidon<F> {
+    /// Returns a new hasher based on the given parameters, hashing exactly
+    /// `nr_inputs` inputs. The last element of the state is used as capacity
+    /// and the remaining `width - 1` elements as rate.
+    pub fn new(params: PoseidonParameters<F>, nr_inputs: usize) -> Result<Self, PoseidonError> {
+        let width = params.width;
+        if width < 2 {
+            return Err(PoseidonError::InvalidCapacity {
+                capacity: 1,
+                max_limit: width.saturating_sub(1),
+                width,
+            });
+        }
+        if nr_inputs == 0 {
+            return Err(PoseidonError::EmptyInput);
+        }
+
+        Ok(Self {
+            nr_inputs,
+            poseidon: Poseidon::new(params),
+        })
+    }
+
+    /// Number of inputs the hasher was created for.
+    pub fn nr_inputs(&self) -> usize {
+        self.nr_inputs
+    }
+
+    /// Number of inputs absorbed per permutation.
+    pub fn rate(&self) -> usize {
+        self.poseidon.params.width - 1
+    }
+
+    /// Initial value of the capacity element, `nr_inputs * 2^64`, which
+    /// separates domains of different input lengths.
+    fn initial_capacity_element(&self) -> F {
+        F::from(self.nr_inputs as u64) * F::from(1u128 << 64)
+    }
+}
+
+impl<F: PrimeField> PoseidonHasher<F> for Halo2Poseidon<F> {
+    fn hash(&self, inputs: &[F]) -> Result<F, PoseidonError> {
+        if inputs.len() != self.nr_inputs {
+            return Err(PoseidonError::UnexpectedNumberOfInputs {
+                inputs: inputs.len(),
+                expected: self.nr_inputs,
+            });
+        }
+
+        let rate = self.rate();
+        Ok(with_stack_state(self.poseidon.params.width, |state| {
+            state[rate] = self.initial_capacity_element();
+
+            // Padding the last chunk with zeros doesn't change the state, so
+            // it's enough to permute after each chunk.
+            for chunk in inputs.chunks(rate) {
+                for (a, input) in state.iter_mut().zip(chunk) {
+                    *a += input;
+                }
+                self.poseidon.permute_unchecked(state);
+            }
+
+            state[0]
+        }))
+    }
+}
+
+impl<F: PrimeField> Halo2Poseidon<F> {
+    /// Returns a new hasher over the base field of Pallas with the
+    /// `P128Pow5T3` parameters, equivalent to
+    /// `poseidon::Hash<pallas::Base, P128Pow5T3, ConstantLength<nr_inputs>, 3, 2>`.
+    pub fn new_pallas(nr_inputs: usize) -> Result<Halo2Poseidon<pasta::Fp>, PoseidonError> {
+        let params =
+            parameters::pallas_x5::get_poseidon_parameters::<pasta::Fp>(P128_POW5_T3_WIDTH)?;
+        Halo2Poseidon::<pasta::Fp>::new(params, nr_inputs)
+    }
+
+    /// Returns a new hasher over the base field of Vesta with the
+    /// `P128Pow5T3` parameters, equivalent to
+    /// `poseidon::Hash<vesta::Base, P128Pow5T3, ConstantLength<nr_inputs>, 3, 2>`.
+    pub fn new_vesta(nr_inputs: usize) -> Result<Halo2Poseidon<pasta::Fq>, PoseidonError> {
+        let params =
+            parameters::vesta_x5::get_poseidon_parameters::<pasta::Fq>(P128_POW5_T3_WIDTH)?;
+        Halo2Poseidon::<pasta::Fq>::new(params, nr_inputs)
+    }
+}
@@ -14,12 +14,60 @@
 //! * inputs - *1 ≤ n ≤ 12*
 //! * 8 full rounds and partial rounds depending on *t*: *[56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65]*
 //!
+//! Parameters over the BLS12-381 scalar field with the same widths, with the
+//! round constants of [neptune](https://github.com/argumentcomputer/neptune)
+//! (checked for *t = 9*), are provided in
+//! the [`bls12_381_x5`](crate::parameters::bls12_381_x5) module and used by
+//! [`Poseidon::new_bls12_381`](crate::Poseidon::new_bls12_381).
+//!
+//! Parameters over the base fields of the Pasta curves (see the
+//! [`pasta`](crate::pasta) module), compatible with the `P128Pow5T3`
+//! parameters of halo2 for *t = 3*, are provided in the
+//! [`pallas_x5`](crate::parameters::pallas_x5) and
+//! [`vesta_x5`](crate::parameters::vesta_x5) modules and used by
+//! [`Halo2Poseidon`](crate::halo2::Halo2Poseidon), which follows the
+//! `ConstantLength` hashing of halo2_gadgets.
+//!
+//! Parameters of [Plonky2](https://github.com/0xPolygonZero/plonky2) over
+//! the 64-bit Goldilocks field (see the [`goldilocks`](crate::goldilocks)
+//! module) with *x^7* S-boxes and *t = 12* are provided in the
+//! [`goldilocks_x7`](crate::parameters::goldilocks_x7) module and used by
+//! [`Plonky2Poseidon`](crate::plonky2::Plonky2Poseidon), which computes the
+//! same hashes as `PoseidonHash::hash_no_pad` of Plonky2.
+//!
+//! Parameters over the BN254 scalar field with *x^-1* S-boxes (see
+//! [`Sbox::Inverse`](crate::Sbox::Inverse)), the variant from the Poseidon
+//! paper which is a permutation of every prime field, are provided in the
+//! [`bn254_inverse`](crate::parameters::bn254_inverse) module.
+//!
 //! The parameters can be generated with:
 //!
 //! ```bash
 //! cargo xtask generate-poseidon-parameters
 //! ````
 //!
+//! The generation doesn't require SageMath - the Grain LFSR and the Cauchy
+//! MDS matrix construction from the Poseidon paper are implemented in the
+//! [`grain`](crate::grain) module, which can be also used at runtime to
+//! generate parameters for other prime fields.
+//!
+//! The numbers of rounds are calculated by the [`rounds`](crate::rounds)
+//! module, which implements the security bounds from the Poseidon paper and
+//! can be used to pick the numbers of rounds for other fields and widths.
+//!
+//! Custom parameters (e.g. loaded from a config) should be created with
+//! [`PoseidonParameters::try_new`](crate::PoseidonParameters::try_new) or
+//! checked with [`PoseidonParameters::validate`](crate::PoseidonParameters::validate),
+//! which verify the number of round constants, the number of full rounds,
+//! the S-box exponent and whether the MDS matrix is secure (see the
+//! [`mds`](crate::mds) module).
+//!
+//! S-boxes with the exponents *3*, *5*, *7* and *17* use unrolled
+//! square-and-multiply chains (see the [`sbox`](crate::sbox) module), other
+//! exponents fall back to a generic exponentiation. *x^-1* S-boxes invert the
+//! whole state of full rounds with a batch inversion. The implementation is
+//! picked once, when a hasher is created.
+//!
 //! # Output type
 //!
 //! [`Poseidon`](crate::Poseidon) type implements two traits which serve the purpose
@@ -44,7 +92,7 @@
 //! use ark_bn254::Fr;
 //! use ark_ff::{BigInteger, PrimeField};
 //!
-//! let mut poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+//! let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
 //!
 //! let hash = poseidon.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
 //!
@@ -64,7 +112,7 @@
 //! use ark_bn254::Fr;
 //! use ark_ff::{BigInteger, PrimeField};
 //!
-//! let mut poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+//! let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
 //!
 //! let input1 = Fr::from_be_bytes_mod_order(&[1u8; 32]);
 //! let input2 = Fr::from_be_bytes_mod_order(&[2u8; 32]);
@@ -74,6 +122,197 @@
 //! // Do something with `hash`.
 //! ```
 //!
+//! # Permutation
+//!
+//! The underlying permutation is exposed by the [`permute`](crate::permute)
+//! function, which applies all rounds to a state of `width` prime field
+//! elements provided by the caller and returns the whole output state. It's
+//! useful for building custom constructions on top of Poseidon.
+//!
+//! # Optimized permutation
+//!
+//! The [`optimized`](crate::optimized) module provides
+//! [`OptimizedPoseidonParameters`](crate::optimized::OptimizedPoseidonParameters),
+//! derived from regular parameters, with constants of partial rounds folded
+//! and sparse matrices used in partial rounds, as described in the appendix B
+//! of the Poseidon paper.
+//! [`OptimizedPoseidon`](crate::optimized::OptimizedPoseidon) uses them to
+//! compute exactly the same hashes as [`Poseidon`](crate::Poseidon), but
+//! faster:
+//!
+//! ```rust
+//! use light_poseidon::{optimized::OptimizedPoseidon, PoseidonBytesHasher};
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = OptimizedPoseidon::<Fr>::new_circom(2).unwrap();
+//!
+//! let hash = poseidon.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+//!
+//! assert_eq!(
+//!     hash,
+//!     [
+//!         13, 84, 225, 147, 143, 138, 140, 28, 125, 235, 94, 3, 85, 242, 99, 25, 32, 123, 132,
+//!         254, 156, 162, 206, 27, 38, 231, 53, 200, 41, 130, 25, 144
+//!     ]
+//! );
+//! ```
+//!
+//! # Static parameters
+//!
+//! [`Poseidon::new_circom`](crate::Poseidon::new_circom) converts the
+//! parameters into Montgomery form and allocates them on every call. The
+//! [`bn254_x5`](crate::parameters::bn254_x5) module also contains them as
+//! `static` arrays, already in Montgomery form.
+//! [`BorrowedPoseidon`](crate::borrowed::BorrowedPoseidon) borrows them, so
+//! its construction is free, which matters in hot loops and on-chain
+//! programs:
+//!
+//! ```rust
+//! use light_poseidon::{borrowed::BorrowedPoseidon, PoseidonHasher};
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = BorrowedPoseidon::new_circom(2).unwrap();
+//!
+//! let hash = poseidon.hash(&[Fr::from(1u64), Fr::from(2u64)]).unwrap();
+//! ```
+//!
+//! # Fixed width
+//!
+//! [`FixedPoseidon`](crate::fixed::FixedPoseidon) takes the width as a const
+//! generic parameter. Its state lives on the stack, hashing doesn't allocate
+//! and the number of inputs is checked at compile time:
+//!
+//! ```rust
+//! use light_poseidon::fixed::FixedPoseidon;
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = FixedPoseidon::<Fr, 3>::new_circom().unwrap();
+//!
+//! let hash = poseidon.hash(&[Fr::from(1u64), Fr::from(2u64)]);
+//! ```
+//!
+//! # Batch hashing
+//!
+//! [`Poseidon::hash_batch`](crate::Poseidon::hash_batch) and its bytes
+//! variants hash many inputs of the same length at once (see the
+//! [`batch`](crate::batch) module). With the `parallel` feature, the work is
+//! split over the [rayon](https://github.com/rayon-rs/rayon) thread pool:
+//!
+//! ```toml
+//! light-poseidon = { version = "0.4", features = ["parallel"] }
+//! ```
+//!
+//! ```rust
+//! use light_poseidon::Poseidon;
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+//!
+//! let hashes = poseidon
+//!     .hash_batch(&[
+//!         [Fr::from(1u64), Fr::from(2u64)],
+//!         [Fr::from(3u64), Fr::from(4u64)],
+//!     ])
+//!     .unwrap();
+//! ```
+//!
+//! # Sharing between threads
+//!
+//! Hashing takes `&self` and keeps the state on the stack, so the hashers are
+//! `Send + Sync` and one instance can be shared across a thread pool or
+//! stored in a `static`, e.g. with `std::sync::OnceLock` (see
+//! [`Poseidon`](crate::Poseidon)). [`BorrowedPoseidon`](crate::borrowed::BorrowedPoseidon)
+//! borrowing the `static` BN254 parameters doesn't need any initialization.
+//!
+//! # Poseidon2
+//!
+//! [`Poseidon2`](crate::poseidon2::Poseidon2) implements
+//! [Poseidon2](https://eprint.iacr.org/2023/323), which uses cheaper linear
+//! layers than Poseidon. It implements the same traits, so it can be used as
+//! a drop-in alternative. The library provides BN254 parameters for
+//! *2 ≤ t ≤ 4*, compatible with the reference implementation and
+//! Barretenberg:
+//!
+//! ```rust
+//! use light_poseidon::{poseidon2::Poseidon2, PoseidonHasher};
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = Poseidon2::<Fr>::new_bn254(2).unwrap();
+//!
+//! let hash = poseidon.hash(&[Fr::from(1u64), Fr::from(2u64)]).unwrap();
+//!
+//! // Do something with `hash`.
+//! ```
+//!
+//! # Sponge
+//!
+//! Apart from the fixed-length hashers, [`PoseidonSponge`](crate::sponge::PoseidonSponge)
+//! provides a duplex sponge with configurable rate and capacity, which
+//! absorbs and squeezes an arbitrary number of prime field elements:
+//!
+//! ```rust
+//! use light_poseidon::sponge::PoseidonSponge;
+//! use ark_bn254::Fr;
+//!
+//! let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+//!
+//! sponge.absorb(&[Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)]);
+//! let output = sponge.squeeze(2);
+//!
+//! // Do something with `output`.
+//! ```
+//!
+//! Byte strings of any length can be hashed with
+//! [`PoseidonSponge::hash_bytes_var`](crate::sponge::PoseidonSponge::hash_bytes_var),
+//! which splits them into 31-byte limbs with an unambiguous padding, so there
+//! is no need for custom packing of bytes into prime field elements:
+//!
+//! ```rust
+//! use light_poseidon::sponge::PoseidonSponge;
+//! use ark_bn254::Fr;
+//!
+//! let sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+//!
+//! let hash = sponge.hash_bytes_var(b"any number of bytes").unwrap();
+//!
+//! // Do something with `hash`.
+//! ```
+//!
+//! For domain-separated usage following the SAFE API, where the sequence of
+//! absorb and squeeze calls is declared up front and enforced, see the
+//! [`safe`](crate::safe) module.
+//!
+//! # Merkle trees
+//!
+//! The [`merkle`](crate::merkle) module provides an append-only incremental
+//! Merkle tree hashing nodes with `Poseidon::<Fr>::new_circom(2)`, compatible
+//! with the on-chain Merkle trees of Tornado Cash and Light Protocol and with
+//! circomlib-based inclusion proof circuits.
+//!
+//! The [`concurrent`](crate::concurrent) module provides a Merkle tree with
+//! a changelog, which accepts leaf replacements with proofs against recent
+//! roots, like the on-chain concurrent Merkle trees of Light Protocol.
+//!
+//! The [`smt`](crate::smt) module provides a sparse Merkle tree keyed by
+//! prime field elements, with inclusion and non-inclusion proofs, using the
+//! leaf and node hashing of iden3's circomlib `SMTVerifier`.
+//!
+//! # Debugging
+//!
+//! [`Poseidon::hash_with_trace`](crate::Poseidon::hash_with_trace) and
+//! [`Poseidon::permute_with_trace`](crate::Poseidon::permute_with_trace)
+//! return the state after every step of every round, which can be dumped to
+//! JSON and diffed against witnesses of circuits (see the
+//! [`trace`](crate::trace) module).
+//!
+//! # R1CS gadget
+//!
+//! With the `r1cs` feature enabled, `r1cs::PoseidonGadget` enforces the hash in circuits built with
+//! [ark-r1cs-std](https://github.com/arkworks-rs/r1cs-std), consuming the
+//! same [`PoseidonParameters`](crate::PoseidonParameters) as the native
+//! hasher. It needs 3 constraints per *x^5* S-box, matching the constraint
+//! count of circomlib's `Poseidon` template.
+//!
 //! # Implementation
 //!
 //! The implementation is compatible with the
@@ -86,7 +325,9 @@
 //! # Performance
 //!
 //! This repository contains a benchmark measuring the performance of this
-//! Poseidon implementation for given 1 - 12 random 32 bytes inputs.
+//! Poseidon implementation, both [`Poseidon`](crate::Poseidon) and
+//! [`OptimizedPoseidon`](crate::optimized::OptimizedPoseidon), for given
+//! 1 - 12 random 32 bytes inputs.
 //!
 //! To run them, simply use:
 //!
@@ -94,79 +335,426 @@
 //! cargo bench
 //! ```
 //!
-//! This is the result from a host with the following hardware:
-//!
-//! * AMD Ryzen™ 9 7945HX with Radeon™ Graphics × 32
+//! These are the median times from a virtualized Intel® Xeon® host with a
+//! single vCPU:
 //!
 //! ```norust
-//! poseidon_bn254_x5_1     time:   [12.710 µs 12.735 µs 12.754 µs]
-//!
-//! poseidon_bn254_x5_2     time:   [18.948 µs 18.963 µs 18.990 µs]
-//!
-//! poseidon_bn254_x5_3     time:   [26.607 µs 26.611 µs 26.615 µs]
-//!
-//! poseidon_bn254_x5_4     time:   [38.507 µs 38.513 µs 38.519 µs]
-//!
-//! poseidon_bn254_x5_5     time:   [51.024 µs 51.031 µs 51.039 µs]
-//!
-//! poseidon_bn254_x5_6     time:   [68.368 µs 68.375 µs 68.385 µs]
-//!
-//! poseidon_bn254_x5_7     time:   [86.819 µs 86.886 µs 86.968 µs]
-//!
-//! poseidon_bn254_x5_8     time:   [105.38 µs 105.49 µs 105.61 µs]
-//!
-//! poseidon_bn254_x5_9     time:   [121.99 µs 122.00 µs 122.01 µs]
+//! inputs   Poseidon     OptimizedPoseidon
+//! 1        18.364 µs    18.697 µs
+//! 2        37.586 µs    29.301 µs
+//! 3        51.115 µs    36.546 µs
+//! 4        79.821 µs    43.833 µs
+//! 5        126.66 µs    58.498 µs
+//! 6        155.38 µs    75.128 µs
+//! 7        218.43 µs    83.930 µs
+//! 8        362.98 µs    85.162 µs
+//! 9        390.17 µs    102.54 µs
+//! 10       478.21 µs    128.44 µs
+//! 11       524.19 µs    152.18 µs
+//! 12       637.64 µs    187.07 µs
+//! ```
 //!
-//! poseidon_bn254_x5_10    time:   [157.00 µs 157.02 µs 157.05 µs]
+//! # `no_std`
 //!
-//! poseidon_bn254_x5_11    time:   [170.01 µs 170.04 µs 170.07 µs]
+//! The library supports `no_std` targets with the default `std` feature
+//! disabled. Targets with an allocator (e.g. Solana programs) should enable
+//! the `alloc` feature, which provides everything except the
+//! [`rounds`](crate::rounds) module (it uses floating point functions, which
+//! require `std`):
 //!
-//! poseidon_bn254_x5_12    time:   [210.78 µs 210.81 µs 210.84 µs]
+//! ```toml
+//! light-poseidon = { version = "0.4", default-features = false, features = ["alloc"] }
 //! ```
 //!
+//! Without `alloc`, the library provides only the APIs which never allocate:
+//! [`FixedPoseidon`](crate::fixed::FixedPoseidon) and
+//! [`BorrowedPoseidon`](crate::borrowed::BorrowedPoseidon) (with widths up to
+//! 16) with the `static` BN254 parameters of
+//! [`bn254_x5`](crate::parameters::bn254_x5), including hashing bytes with
+//! [`PoseidonBytesHasher`](crate::PoseidonBytesHasher), and the byte
+//! conversion functions. Note that arkworks still links the `alloc` crate on
+//! `no_std` targets.
+//!
 //! # Security
 //!
 //! This library has been audited by [Veridise](https://veridise.com/). You can
 //! read the audit report [here](https://github.com/Lightprotocol/light-poseidon/blob/main/assets/audit.pdf).
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
 use ark_bn254::Fr;
-use ark_ff::{BigInteger, PrimeField, Zero};
-use thiserror::Error;
+#[cfg(feature = "alloc")]
+use ark_ff::Zero;
+use ark_ff::{BigInteger, PrimeField};
+
+#[cfg(feature = "alloc")]
+use sbox::PowerMap;
+pub use sbox::Sbox;
+#[cfg(feature = "alloc")]
+use trace::{trace_rounds_with, PermutationTrace};
 
+#[cfg(feature = "alloc")]
+pub mod batch;
+pub mod borrowed;
+#[cfg(feature = "alloc")]
+pub mod concurrent;
+pub mod fixed;
+pub mod goldilocks;
+#[cfg(feature = "alloc")]
+pub mod grain;
+#[cfg(feature = "alloc")]
+pub mod halo2;
+#[cfg(feature = "alloc")]
+mod matrix;
+#[cfg(feature = "alloc")]
+pub mod mds;
+#[cfg(feature = "alloc")]
+pub mod merkle;
+#[cfg(feature = "alloc")]
+pub mod optimized;
 pub mod parameters;
+pub mod pasta;
+#[cfg(feature = "alloc")]
+pub mod plonky2;
+#[cfg(feature = "alloc")]
+pub mod poseidon2;
+#[cfg(feature = "r1cs")]
+pub mod r1cs;
+// Uses floating point functions which are not available in `core`.
+#[cfg(feature = "std")]
+pub mod rounds;
+#[cfg(feature = "alloc")]
+pub mod safe;
+pub mod sbox;
+#[cfg(feature = "alloc")]
+pub mod smt;
+#[cfg(feature = "alloc")]
+pub mod sponge;
+#[cfg(feature = "alloc")]
+pub mod trace;
 
 pub const HASH_LEN: usize = 32;
 pub const MAX_X5_LEN: usize = 13;
 
-#[derive(Error, Debug, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub enum PoseidonError {
-    #[error("Invalid number of inputs: {inputs}. Maximum allowed is {max_limit} ({width} - 1).")]
     InvalidNumberOfInputs {
         inputs: usize,
         max_limit: usize,
         width: usize,
     },
-    #[error("Input is an empty slice.")]
     EmptyInput,
-    #[error("Invalid length of the input: {len}. The length matching the modulus of the prime field is: {modulus_bytes_len}.")]
     InvalidInputLength {
         len: usize,
         modulus_bytes_len: usize,
     },
-    #[error("Failed to convert bytes {bytes:?} into a prime field element")]
-    BytesToPrimeFieldElement { bytes: Vec<u8> },
-    #[error("Input is larger than the modulus of the prime field.")]
+    #[cfg(feature = "alloc")]
+    BytesToPrimeFieldElement {
+        bytes: Vec<u8>,
+    },
     InputLargerThanModulus,
-    #[error("Failed to convert a vector of bytes into an array.")]
     VecToArray,
-    #[error("Failed to convert the number of inputs from u64 to u8.")]
     U64Tou8,
-    #[error("Failed to convert bytes to BigInt")]
     BytesToBigInt,
-    #[error("Invalid width: {width}. Choose a width between 2 and 16 for 1 to 15 inputs.")]
-    InvalidWidthCircom { width: usize, max_limit: usize },
+    InvalidWidthCircom {
+        width: usize,
+        max_limit: usize,
+    },
+    InvalidCapacity {
+        capacity: usize,
+        max_limit: usize,
+        width: usize,
+    },
+    InvalidStateLength {
+        len: usize,
+        width: usize,
+    },
+    InvalidFullRounds {
+        full_rounds: usize,
+    },
+    MdsNotInvertible,
+    InvalidWidth {
+        width: usize,
+        max_limit: usize,
+    },
+    InvalidWidthPoseidon2 {
+        width: usize,
+    },
+    InvalidWidthGoldilocks {
+        width: usize,
+    },
+    InvalidMdsSize {
+        width: usize,
+    },
+    NotMds,
+    MdsTooLarge {
+        width: usize,
+        max_limit: usize,
+    },
+    InvariantSubspaceTrail {
+        algorithm: u8,
+        rounds: usize,
+    },
+    InvalidArkLength {
+        len: usize,
+        expected: usize,
+    },
+    InvalidMatInternalDiagLength {
+        len: usize,
+        width: usize,
+    },
+    InvalidAlpha {
+        alpha: u64,
+    },
+    UnexpectedNumberOfInputs {
+        inputs: usize,
+        expected: usize,
+    },
+    UnexpectedWidth {
+        width: usize,
+        expected: usize,
+    },
+    #[cfg(feature = "alloc")]
+    InvalidIoPattern {
+        index: usize,
+        op: String,
+    },
+    InvalidIoPatternAggregate {
+        index: usize,
+        max_limit: u32,
+    },
+    #[cfg(feature = "alloc")]
+    IoPatternViolation {
+        index: usize,
+        expected: String,
+        op: String,
+    },
+    IoPatternNotFinished {
+        performed: usize,
+        expected: usize,
+    },
+    InvalidMerkleTreeHeight {
+        height: usize,
+        max_limit: usize,
+    },
+    MerkleTreeFull {
+        capacity: usize,
+    },
+    InvalidLeafIndex {
+        index: usize,
+        len: usize,
+    },
+    LeafIndexOutOfRange {
+        index: usize,
+        height: usize,
+    },
+    InvalidMerkleTreeBufferSize {
+        changelog: usize,
+        roots: usize,
+    },
+    MerkleRootNotFound,
+    MerkleLeafModified {
+        index: usize,
+    },
+    InvalidMerkleProof,
+    SparseMerkleKeyExists,
+    SparseMerkleKeyNotFound,
+    SparseMerkleMaxDepth {
+        depth: usize,
+    },
+    SparseMerkleNodeNotFound,
+    InvalidSparseMerkleProof,
+    UnsupportedPrimeField {
+        modulus_bit_size: u32,
+    },
+    #[cfg(feature = "r1cs")]
+    Synthesis(ark_relations::r1cs::SynthesisError),
+}
+
+impl fmt::Display for PoseidonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumberOfInputs { inputs, max_limit, width } => write!(
+                f,
+                "Invalid number of inputs: {inputs}. Maximum allowed is {max_limit} ({width} - 1)."
+            ),
+            Self::EmptyInput => write!(f, "Input is an empty slice."),
+            Self::InvalidInputLength { len, modulus_bytes_len } => write!(
+                f,
+                "Invalid length of the input: {len}. The length matching the modulus of the prime field is: {modulus_bytes_len}."
+            ),
+            #[cfg(feature = "alloc")]
+            Self::BytesToPrimeFieldElement { bytes } => write!(
+                f,
+                "Failed to convert bytes {bytes:?} into a prime field element"
+            ),
+            Self::InputLargerThanModulus => write!(
+                f,
+                "Input is larger than the modulus of the prime field."
+            ),
+            Self::VecToArray => write!(f, "Failed to convert a vector of bytes into an array."),
+            Self::U64Tou8 => write!(f, "Failed to convert the number of inputs from u64 to u8."),
+            Self::BytesToBigInt => write!(f, "Failed to convert bytes to BigInt"),
+            Self::InvalidWidthCircom { width, .. } => write!(
+                f,
+                "Invalid width: {width}. Choose a width between 2 and 16 for 1 to 15 inputs."
+            ),
+            Self::InvalidCapacity { capacity, max_limit, width } => write!(
+                f,
+                "Invalid capacity: {capacity}. Capacity has to be between 1 and {max_limit} ({width} - 1)."
+            ),
+            Self::InvalidStateLength { len, width } => write!(
+                f,
+                "Invalid length of the state: {len}. The length has to be equal to the width: {width}."
+            ),
+            Self::InvalidFullRounds { full_rounds } => write!(
+                f,
+                "Invalid number of full rounds: {full_rounds}. It has to be an even number greater than 0."
+            ),
+            Self::MdsNotInvertible => write!(
+                f,
+                "The MDS matrix (or its submatrix) is not invertible."
+            ),
+            Self::InvalidWidth { width, max_limit } => write!(
+                f,
+                "Invalid width: {width}. Choose a width between 2 and {max_limit}."
+            ),
+            Self::InvalidWidthPoseidon2 { width } => write!(
+                f,
+                "Invalid width: {width}. Poseidon2 supports widths 2, 3, 4, 8, 12, 16, 20 and 24."
+            ),
+            Self::InvalidWidthGoldilocks { width } => write!(
+                f,
+                "Invalid width: {width}. Goldilocks parameters are provided only for width 12."
+            ),
+            Self::InvalidMdsSize { width } => write!(
+                f,
+                "Invalid size of the MDS matrix. It has to be a square matrix of size {width}."
+            ),
+            Self::NotMds => write!(
+                f,
+                "The MDS matrix is not MDS - one of its square submatrices is not invertible."
+            ),
+            Self::MdsTooLarge { width, max_limit } => write!(
+                f,
+                "The MDS matrix of size {width} is not a Cauchy matrix. Checking whether such matrices are MDS is supported up to the size {max_limit}."
+            ),
+            Self::InvariantSubspaceTrail { algorithm, rounds } => write!(
+                f,
+                "The MDS matrix admits an infinitely long invariant subspace trail over {rounds} rounds (found by the algorithm {algorithm} of Grassi et al.)."
+            ),
+            Self::InvalidArkLength { len, expected } => write!(
+                f,
+                "Invalid number of round constants: {len}. Expected {expected}."
+            ),
+            Self::InvalidMatInternalDiagLength { len, width } => write!(
+                f,
+                "Invalid length of the diagonal of the internal matrix: {len}. It has to be equal to the width {width}."
+            ),
+            Self::InvalidAlpha { alpha } => write!(
+                f,
+                "Invalid alpha: {alpha}. It has to be greater than 1 and coprime with p - 1."
+            ),
+            Self::UnexpectedNumberOfInputs { inputs, expected } => write!(
+                f,
+                "Invalid number of inputs: {inputs}. The hasher expects exactly {expected} inputs."
+            ),
+            Self::UnexpectedWidth { width, expected } => write!(
+                f,
+                "Invalid width of the parameters: {width}. The hasher expects width {expected}."
+            ),
+            #[cfg(feature = "alloc")]
+            Self::InvalidIoPattern { index, op } => write!(
+                f,
+                "Invalid IO pattern: operation {index} ({op}) has to absorb or squeeze between 1 and 2^31 - 1 elements."
+            ),
+            Self::InvalidIoPatternAggregate { index, max_limit } => write!(
+                f,
+                "Invalid IO pattern: consecutive operations of the same kind up to operation {index} add up to more than {max_limit} elements."
+            ),
+            #[cfg(feature = "alloc")]
+            Self::IoPatternViolation { index, expected, op } => write!(
+                f,
+                "IO pattern violation: operation {index} is {op}, but the pattern declares {expected}."
+            ),
+            Self::IoPatternNotFinished { performed, expected } => write!(
+                f,
+                "IO pattern not finished: {performed} out of {expected} operations were performed."
+            ),
+            Self::InvalidMerkleTreeHeight { height, max_limit } => write!(
+                f,
+                "Invalid height of the Merkle tree: {height}. Choose a height between 1 and {max_limit}."
+            ),
+            Self::MerkleTreeFull { capacity } => write!(
+                f,
+                "The Merkle tree is full. It can hold at most {capacity} leaves."
+            ),
+            Self::InvalidLeafIndex { index, len } => write!(
+                f,
+                "Invalid leaf index: {index}. The Merkle tree has {len} leaves."
+            ),
+            Self::LeafIndexOutOfRange { index, height } => write!(
+                f,
+                "Invalid leaf index: {index}. A Merkle proof of height {height} covers indices lower than 2^{height}."
+            ),
+            Self::InvalidMerkleTreeBufferSize { changelog, roots } => write!(
+                f,
+                "Invalid sizes of the Merkle tree buffers: changelog {changelog}, roots {roots}. Both have to be greater than 0."
+            ),
+            Self::MerkleRootNotFound => write!(
+                f,
+                "The root of the Merkle proof is not present in the changelog."
+            ),
+            Self::MerkleLeafModified { index } => write!(
+                f,
+                "The leaf {index} was modified after the root of the Merkle proof."
+            ),
+            Self::InvalidMerkleProof => write!(f, "Invalid Merkle proof."),
+            Self::SparseMerkleKeyExists => write!(
+                f,
+                "The key is already present in the sparse Merkle tree."
+            ),
+            Self::SparseMerkleKeyNotFound => write!(
+                f,
+                "The key is not present in the sparse Merkle tree."
+            ),
+            Self::SparseMerkleMaxDepth { depth } => write!(
+                f,
+                "The key shares the path of {depth} bits (the depth of the sparse Merkle tree) with another key."
+            ),
+            Self::SparseMerkleNodeNotFound => write!(
+                f,
+                "A node on the path of the key is missing from the sparse Merkle tree."
+            ),
+            Self::InvalidSparseMerkleProof => write!(f, "Invalid serialized sparse Merkle proof."),
+            Self::UnsupportedPrimeField { modulus_bit_size } => write!(
+                f,
+                "Prime fields with a {modulus_bit_size}-bit modulus are not supported by this operation."
+            ),
+            #[cfg(feature = "r1cs")]
+            Self::Synthesis(error) => write!(f, "Constraint synthesis failed: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for PoseidonError {}
+
+#[cfg(feature = "r1cs")]
+impl From<ark_relations::r1cs::SynthesisError> for PoseidonError {
+    fn from(error: ark_relations::r1cs::SynthesisError) -> Self {
+        Self::Synthesis(error)
+    }
 }
 
 /// Parameters for the Poseidon hash algorithm.
+#[cfg(feature = "alloc")]
 pub struct PoseidonParameters<F: PrimeField> {
     /// Round constants.
     pub ark: Vec<F>,
@@ -180,10 +768,11 @@ pub struct PoseidonParameters<F: PrimeField> {
     pub partial_rounds: usize,
     /// Number of prime fields in the state.
     pub width: usize,
-    /// Exponential used in S-box to power elements of the state.
-    pub alpha: u64,
+    /// S-box applied to elements of the state.
+    pub sbox: Sbox,
 }
 
+#[cfg(feature = "alloc")]
 impl<F: PrimeField> PoseidonParameters<F> {
     pub fn new(
         ark: Vec<F>,
@@ -191,7 +780,7 @@ impl<F: PrimeField> PoseidonParameters<F> {
         full_rounds: usize,
         partial_rounds: usize,
         width: usize,
-        alpha: u64,
+        sbox: Sbox,
     ) -> Self {
         Self {
             ark,
@@ -199,9 +788,89 @@ impl<F: PrimeField> PoseidonParameters<F> {
             full_rounds,
             partial_rounds,
             width,
-            alpha,
+            sbox,
+        }
+    }
+
+    /// Returns new Poseidon parameters, checking whether they are valid (see
+    /// [`validate`](crate::PoseidonParameters::validate)).
+    pub fn try_new(
+        ark: Vec<F>,
+        mds: Vec<Vec<F>>,
+        full_rounds: usize,
+        partial_rounds: usize,
+        width: usize,
+        sbox: Sbox,
+    ) -> Result<Self, PoseidonError> {
+        let params = Self::new(ark, mds, full_rounds, partial_rounds, width, sbox);
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Checks whether the MDS matrix is secure - whether it's an invertible
+    /// MDS matrix of size `width` which doesn't admit infinitely long
+    /// invariant subspace trails. See
+    /// [`mds::check_mds_security`](crate::mds::check_mds_security).
+    pub fn check_mds_security(&self) -> Result<(), PoseidonError> {
+        mds::check_mds_security(&self.mds, self.width)
+    }
+
+    /// Validates the parameters. Should be used for parameters which don't
+    /// come from the library, e.g. provided by users. Checks whether:
+    ///
+    /// * the number of round constants is `(full_rounds + partial_rounds) * width`,
+    /// * the number of full rounds is even and greater than 0,
+    /// * for [`Sbox::Power`](crate::Sbox::Power), `alpha` is greater than 1
+    ///   and coprime with `p - 1` (so the S-box is a permutation),
+    /// * the MDS matrix is secure (see
+    ///   [`check_mds_security`](crate::PoseidonParameters::check_mds_security)).
+    pub fn validate(&self) -> Result<(), PoseidonError> {
+        let expected = (self.full_rounds + self.partial_rounds) * self.width;
+        if self.ark.len() != expected {
+            return Err(PoseidonError::InvalidArkLength {
+                len: self.ark.len(),
+                expected,
+            });
+        }
+        if self.full_rounds == 0 || !self.full_rounds.is_multiple_of(2) {
+            return Err(PoseidonError::InvalidFullRounds {
+                full_rounds: self.full_rounds,
+            });
+        }
+        validate_sbox::<F>(self.sbox)?;
+
+        self.check_mds_security()
+    }
+}
+
+/// Checks whether the given S-box is a permutation of the prime field `F` -
+/// for [`Sbox::Power`](crate::Sbox::Power), whether `alpha` is greater than 1
+/// and coprime with `p - 1`.
+#[cfg(feature = "alloc")]
+pub(crate) fn validate_sbox<F: PrimeField>(sbox: Sbox) -> Result<(), PoseidonError> {
+    if let Sbox::Power(alpha) = sbox {
+        if alpha < 2 || gcd(alpha, modulus_minus_one_rem::<F>(alpha)) != 1 {
+            return Err(PoseidonError::InvalidAlpha { alpha });
         }
     }
+    Ok(())
+}
+
+/// Returns `(p - 1) mod n`, where `p` is the modulus of the prime field `F`.
+#[cfg(feature = "alloc")]
+fn modulus_minus_one_rem<F: PrimeField>(n: u64) -> u64 {
+    let rem = F::MODULUS.as_ref().iter().rev().fold(0u128, |rem, limb| {
+        ((rem << 64) | u128::from(*limb)) % u128::from(n)
+    });
+    ((rem + u128::from(n) - 1) % u128::from(n)) as u64
+}
+
+#[cfg(feature = "alloc")]
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
 }
 
 pub trait PoseidonHasher<F: PrimeField> {
@@ -218,7 +887,7 @@ pub trait PoseidonHasher<F: PrimeField> {
     /// use ark_bn254::Fr;
     /// use ark_ff::{BigInteger, PrimeField};
     ///
-    /// let mut poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    /// let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
     ///
     /// let input1 = Fr::from_be_bytes_mod_order(&[1u8; 32]);
     /// let input2 = Fr::from_be_bytes_mod_order(&[2u8; 32]);
@@ -226,7 +895,7 @@ pub trait PoseidonHasher<F: PrimeField> {
     /// let hash = poseidon.hash(&[input1, input2]).unwrap();
     ///
     /// // Do something with `hash`.
-    fn hash(&mut self, inputs: &[F]) -> Result<F, PoseidonError>;
+    fn hash(&self, inputs: &[F]) -> Result<F, PoseidonError>;
 }
 
 pub trait PoseidonBytesHasher {
@@ -243,7 +912,7 @@ pub trait PoseidonBytesHasher {
     /// use ark_bn254::Fr;
     /// use ark_ff::{BigInteger, PrimeField};
     ///
-    /// let mut poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    /// let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
     ///
     /// let hash = poseidon.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
     ///
@@ -267,7 +936,7 @@ pub trait PoseidonBytesHasher {
     /// that might occur when using `from_be_bytes_mod_order` or `from_random_bytes`,
     /// which simply take a subslice of the input if it's too large, potentially
     /// leading to collisions.
-    fn hash_bytes_be(&mut self, inputs: &[&[u8]]) -> Result<[u8; HASH_LEN], PoseidonError>;
+    fn hash_bytes_be(&self, inputs: &[&[u8]]) -> Result<[u8; HASH_LEN], PoseidonError>;
     /// Calculates a Poseidon hash for the given input of little-endian byte
     /// slices and returns the result as a byte array.
     ///
@@ -281,7 +950,7 @@ pub trait PoseidonBytesHasher {
     /// use ark_bn254::Fr;
     /// use ark_ff::{BigInteger, PrimeField};
     ///
-    /// let mut poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    /// let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
     ///
     /// let hash = poseidon.hash_bytes_le(&[&[1u8; 32], &[2u8; 32]]).unwrap();
     ///
@@ -305,16 +974,78 @@ pub trait PoseidonBytesHasher {
     /// that might occur when using `from_be_bytes_mod_order` or `from_random_bytes`,
     /// which simply take a subslice of the input if it's too large, potentially
     /// leading to collisions.
-    fn hash_bytes_le(&mut self, inputs: &[&[u8]]) -> Result<[u8; HASH_LEN], PoseidonError>;
+    fn hash_bytes_le(&self, inputs: &[&[u8]]) -> Result<[u8; HASH_LEN], PoseidonError>;
 }
 
-/// A stateful sponge performing Poseidon hash computation.
+/// Function applying all rounds of the permutation, specialized for the
+/// exponent of the S-box.
+#[cfg(feature = "alloc")]
+type RoundsFn<F> = fn(&PoseidonParameters<F>, &mut [F]);
+
+/// Maximum width of states kept on the stack while hashing. Wider states are
+/// allocated, so they require the `alloc` feature.
+const MAX_STACK_WIDTH: usize = 16;
+
+/// Checks whether a state of the given width can be passed to
+/// [`with_stack_state`]. Without the `alloc` feature, the width can't exceed
+/// [`MAX_STACK_WIDTH`].
+fn check_stack_width(width: usize) -> Result<(), PoseidonError> {
+    if cfg!(not(feature = "alloc")) && width > MAX_STACK_WIDTH {
+        return Err(PoseidonError::InvalidWidth {
+            width,
+            max_limit: MAX_STACK_WIDTH,
+        });
+    }
+    Ok(())
+}
+
+/// Calls `f` with a state of zeros of the given width, kept on the stack if
+/// the width doesn't exceed [`MAX_STACK_WIDTH`]. Without the `alloc` feature,
+/// the width has to be checked with [`check_stack_width`] first.
+fn with_stack_state<F: PrimeField, R>(width: usize, f: impl FnOnce(&mut [F]) -> R) -> R {
+    if width <= MAX_STACK_WIDTH {
+        let mut state = [F::zero(); MAX_STACK_WIDTH];
+        f(&mut state[..width])
+    } else {
+        #[cfg(feature = "alloc")]
+        return f(&mut alloc::vec![F::zero(); width]);
+        #[cfg(not(feature = "alloc"))]
+        unreachable!("states wider than {MAX_STACK_WIDTH} require the `alloc` feature");
+    }
+}
+
+/// A Poseidon hasher.
+///
+/// Hashing takes `&self` and keeps the state on the stack, so one hasher can
+/// be shared between threads, e.g. in a `static`:
+///
+/// ```rust
+/// use std::sync::OnceLock;
+///
+/// use light_poseidon::{Poseidon, PoseidonHasher};
+/// use ark_bn254::Fr;
+///
+/// static POSEIDON: OnceLock<Poseidon<Fr>> = OnceLock::new();
+///
+/// fn poseidon() -> &'static Poseidon<Fr> {
+///     POSEIDON.get_or_init(|| Poseidon::<Fr>::new_circom(2).unwrap())
+/// }
+///
+/// let handles: Vec<_> = (0..4u64)
+///     .map(|i| std::thread::spawn(move || poseidon().hash(&[Fr::from(i), Fr::from(i)])))
+///     .collect();
+/// for handle in handles {
+///     handle.join().unwrap().unwrap();
+/// }
+/// ```
+#[cfg(feature = "alloc")]
 pub struct Poseidon<F: PrimeField> {
     params: PoseidonParameters<F>,
     domain_tag: F,
-    state: Vec<F>,
+    rounds: RoundsFn<F>,
 }
 
+#[cfg(feature = "alloc")]
 impl<F: PrimeField> Poseidon<F> {
     /// Returns a new Poseidon hasher based on the given parameters.
     ///
@@ -324,53 +1055,61 @@ impl<F: PrimeField> Poseidon<F> {
         Self::with_domain_tag(params, F::zero())
     }
 
+    /// Returns a new Poseidon hasher based on the given parameters, checking
+    /// whether they are valid (see
+    /// [`PoseidonParameters::validate`](crate::PoseidonParameters::validate)).
+    pub fn try_new(params: PoseidonParameters<F>) -> Result<Self, PoseidonError> {
+        params.validate()?;
+        Ok(Self::new(params))
+    }
+
     fn with_domain_tag(params: PoseidonParameters<F>, domain_tag: F) -> Self {
-        let width = params.width;
         Self {
             domain_tag,
+            rounds: sbox::select_rounds!(apply_rounds_with, params.sbox),
             params,
-            state: Vec::with_capacity(width),
         }
     }
 
-    #[inline(always)]
-    fn apply_ark(&mut self, round: usize) {
-        self.state.iter_mut().enumerate().for_each(|(i, a)| {
-            let c = self.params.ark[round * self.params.width + i];
-            *a += c;
-        });
-    }
+    /// Applies the Poseidon permutation to the given state, using the
+    /// parameters of this hasher.
+    ///
+    /// See [`permute`](crate::permute) for details.
+    pub fn permute(&self, state: &mut [F]) -> Result<(), PoseidonError> {
+        if state.len() != self.params.width {
+            return Err(PoseidonError::InvalidStateLength {
+                len: state.len(),
+                width: self.params.width,
+            });
+        }
 
-    #[inline(always)]
-    fn apply_sbox_full(&mut self) {
-        self.state.iter_mut().for_each(|a| {
-            *a = a.pow([self.params.alpha]);
-        });
+        self.permute_unchecked(state);
+        Ok(())
     }
 
-    #[inline(always)]
-    fn apply_sbox_partial(&mut self) {
-        self.state[0] = self.state[0].pow([self.params.alpha]);
-    }
+    /// Applies the Poseidon permutation to the given state like
+    /// [`permute`](crate::Poseidon::permute), returning the state after every
+    /// step of every round (see the [`trace`](crate::trace) module).
+    pub fn permute_with_trace(
+        &self,
+        state: &mut [F],
+    ) -> Result<PermutationTrace<F>, PoseidonError> {
+        if state.len() != self.params.width {
+            return Err(PoseidonError::InvalidStateLength {
+                len: state.len(),
+                width: self.params.width,
+            });
+        }
 
-    #[inline(always)]
-    fn apply_mds(&mut self) {
-        self.state = self
-            .state
-            .iter()
-            .enumerate()
-            .map(|(i, _)| {
-                self.state
-                    .iter()
-                    .enumerate()
-                    .fold(F::zero(), |acc, (j, a)| acc + *a * self.params.mds[i][j])
-            })
-            .collect();
+        Ok(sbox::select_rounds!(trace_rounds_with, self.params.sbox)(
+            &self.params,
+            state,
+        ))
     }
-}
 
-impl<F: PrimeField> PoseidonHasher<F> for Poseidon<F> {
-    fn hash(&mut self, inputs: &[F]) -> Result<F, PoseidonError> {
+    /// Computes the hash like [`PoseidonHasher::hash`](crate::PoseidonHasher::hash),
+    /// returning it together with the trace of the permutation.
+    pub fn hash_with_trace(&self, inputs: &[F]) -> Result<(F, PermutationTrace<F>), PoseidonError> {
         if inputs.len() != self.params.width - 1 {
             return Err(PoseidonError::InvalidNumberOfInputs {
                 inputs: inputs.len(),
@@ -379,43 +1118,151 @@ impl<F: PrimeField> PoseidonHasher<F> for Poseidon<F> {
             });
         }
 
-        self.state.push(self.domain_tag);
+        let mut state = Vec::with_capacity(self.params.width);
+        state.push(self.domain_tag);
+        state.extend_from_slice(inputs);
+        let trace = self.permute_with_trace(&mut state)?;
 
-        for input in inputs {
-            self.state.push(*input);
-        }
+        Ok((state[0], trace))
+    }
 
-        let all_rounds = self.params.full_rounds + self.params.partial_rounds;
-        let half_rounds = self.params.full_rounds / 2;
+    /// Applies all rounds of the permutation to a state of the correct
+    /// width.
+    fn permute_unchecked(&self, state: &mut [F]) {
+        (self.rounds)(&self.params, state);
+    }
+}
 
-        // full rounds + partial rounds
-        for round in 0..half_rounds {
-            self.apply_ark(round);
-            self.apply_sbox_full();
-            self.apply_mds();
-        }
+/// Applies the Poseidon permutation defined by `params` to the given state
+/// and leaves the whole output state in `state`.
+///
+/// Unlike [`PoseidonHasher::hash`](crate::PoseidonHasher::hash), it doesn't
+/// put a domain tag in the state and doesn't truncate the output, so it can
+/// be used to build custom constructions (PRFs, compression functions,
+/// sponges) on top of the permutation. The length of `state` has to be equal
+/// to the width of the parameters.
+///
+/// # Examples
+///
+/// ```rust
+/// use light_poseidon::{permute, parameters::bn254_x5};
+/// use ark_bn254::Fr;
+///
+/// let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+///
+/// let mut state = [Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)];
+/// permute(&params, &mut state).unwrap();
+///
+/// // Do something with `state`.
+/// ```
+#[cfg(feature = "alloc")]
+pub fn permute<F: PrimeField>(
+    params: &PoseidonParameters<F>,
+    state: &mut [F],
+) -> Result<(), PoseidonError> {
+    if state.len() != params.width {
+        return Err(PoseidonError::InvalidStateLength {
+            len: state.len(),
+            width: params.width,
+        });
+    }
+
+    apply_rounds(params, state);
+    Ok(())
+}
 
-        for round in half_rounds..half_rounds + self.params.partial_rounds {
-            self.apply_ark(round);
-            self.apply_sbox_partial();
-            self.apply_mds();
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn apply_ark<F: PrimeField>(params: &PoseidonParameters<F>, state: &mut [F], round: usize) {
+    state.iter_mut().enumerate().for_each(|(i, a)| {
+        let c = params.ark[round * params.width + i];
+        *a += c;
+    });
+}
+
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn apply_sbox_full<F: PrimeField, P: PowerMap>(params: &PoseidonParameters<F>, state: &mut [F]) {
+    P::apply_all(state, params.sbox);
+}
+
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn apply_sbox_partial<F: PrimeField, P: PowerMap>(params: &PoseidonParameters<F>, state: &mut [F]) {
+    state[0] = P::apply(state[0], params.sbox);
+}
+
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn apply_mds<F: PrimeField>(params: &PoseidonParameters<F>, state: &mut [F]) {
+    with_stack_state(params.width, |result| {
+        for (r, row) in result.iter_mut().zip(params.mds.iter()) {
+            *r = row
+                .iter()
+                .zip(state.iter())
+                .fold(F::zero(), |acc, (m, a)| acc + *m * a);
         }
+        state.copy_from_slice(result);
+    })
+}
+
+/// Applies all full and partial rounds of the permutation to the given state,
+/// which is expected to have the length of `params.width`.
+#[cfg(feature = "alloc")]
+fn apply_rounds<F: PrimeField>(params: &PoseidonParameters<F>, state: &mut [F]) {
+    sbox::select_rounds!(apply_rounds_with, params.sbox)(params, state)
+}
+
+/// [`apply_rounds`] with the S-box map `P`.
+#[cfg(feature = "alloc")]
+fn apply_rounds_with<F: PrimeField, P: PowerMap>(params: &PoseidonParameters<F>, state: &mut [F]) {
+    let all_rounds = params.full_rounds + params.partial_rounds;
+    let half_rounds = params.full_rounds / 2;
+
+    // full rounds + partial rounds
+    for round in 0..half_rounds {
+        apply_ark(params, state, round);
+        apply_sbox_full::<F, P>(params, state);
+        apply_mds(params, state);
+    }
+
+    for round in half_rounds..half_rounds + params.partial_rounds {
+        apply_ark(params, state, round);
+        apply_sbox_partial::<F, P>(params, state);
+        apply_mds(params, state);
+    }
 
-        for round in half_rounds + self.params.partial_rounds..all_rounds {
-            self.apply_ark(round);
-            self.apply_sbox_full();
-            self.apply_mds();
+    for round in half_rounds + params.partial_rounds..all_rounds {
+        apply_ark(params, state, round);
+        apply_sbox_full::<F, P>(params, state);
+        apply_mds(params, state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F: PrimeField> PoseidonHasher<F> for Poseidon<F> {
+    fn hash(&self, inputs: &[F]) -> Result<F, PoseidonError> {
+        if inputs.len() != self.params.width - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: inputs.len(),
+                max_limit: self.params.width - 1,
+                width: self.params.width,
+            });
         }
 
-        let result = self.state[0];
-        self.state.clear();
-        Ok(result)
+        Ok(with_stack_state(self.params.width, |state| {
+            state[0] = self.domain_tag;
+            state[1..].copy_from_slice(inputs);
+            self.permute_unchecked(state);
+            state[0]
+        }))
     }
 }
 
+#[cfg(feature = "alloc")]
 macro_rules! impl_hash_bytes {
     ($fn_name:ident, $bytes_to_prime_field_element_fn:ident, $to_bytes_fn:ident) => {
-        fn $fn_name(&mut self, inputs: &[&[u8]]) -> Result<[u8; HASH_LEN], PoseidonError> {
+        fn $fn_name(&self, inputs: &[&[u8]]) -> Result<[u8; HASH_LEN], PoseidonError> {
             let inputs: Result<Vec<_>, _> = inputs
                 .iter()
                 .map(|input| validate_bytes_length::<F>(input))
@@ -426,19 +1273,111 @@ macro_rules! impl_hash_bytes {
                 .map(|input| $bytes_to_prime_field_element_fn(input))
                 .collect();
             let inputs = inputs?;
-            let hash = self.hash(&inputs)?;
+            let hash = PoseidonHasher::hash(self, &inputs)?;
 
-            hash.into_bigint()
-                .$to_bytes_fn()
-                .try_into()
-                .map_err(|_| PoseidonError::VecToArray)
+            $to_bytes_fn(hash.into_bigint())
         }
     };
 }
 
+/// Like [`impl_hash_bytes`], but converts the inputs into a buffer provided by
+/// the `with_inputs_buffer` method of the hasher instead of a `Vec`.
+macro_rules! impl_hash_bytes_with_buffer {
+    ($fn_name:ident, $bytes_to_prime_field_element_fn:ident, $to_bytes_fn:ident) => {
+        fn $fn_name(&self, inputs: &[&[u8]]) -> Result<[u8; HASH_LEN], PoseidonError> {
+            let hash = self.with_inputs_buffer(inputs.len(), |elements| {
+                for (element, input) in elements.iter_mut().zip(inputs) {
+                    *element =
+                        $bytes_to_prime_field_element_fn(validate_bytes_length::<F>(input)?)?;
+                }
+                PoseidonHasher::hash(self, elements)
+            })?;
+
+            $to_bytes_fn(hash.into_bigint())
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
 impl<F: PrimeField> PoseidonBytesHasher for Poseidon<F> {
-    impl_hash_bytes!(hash_bytes_le, bytes_to_prime_field_element_le, to_bytes_le);
-    impl_hash_bytes!(hash_bytes_be, bytes_to_prime_field_element_be, to_bytes_be);
+    impl_hash_bytes!(
+        hash_bytes_le,
+        bytes_to_prime_field_element_le,
+        bigint_to_bytes_le
+    );
+    impl_hash_bytes!(
+        hash_bytes_be,
+        bytes_to_prime_field_element_be,
+        bigint_to_bytes_be
+    );
+}
+
+impl<F: PrimeField> PoseidonBytesHasher for borrowed::BorrowedPoseidon<'_, F> {
+    impl_hash_bytes_with_buffer!(
+        hash_bytes_le,
+        bytes_to_prime_field_element_le,
+        bigint_to_bytes_le
+    );
+    impl_hash_bytes_with_buffer!(
+        hash_bytes_be,
+        bytes_to_prime_field_element_be,
+        bigint_to_bytes_be
+    );
+}
+
+impl<F: PrimeField, const T: usize> PoseidonBytesHasher for fixed::FixedPoseidon<'_, F, T> {
+    impl_hash_bytes_with_buffer!(
+        hash_bytes_le,
+        bytes_to_prime_field_element_le,
+        bigint_to_bytes_le
+    );
+    impl_hash_bytes_with_buffer!(
+        hash_bytes_be,
+        bytes_to_prime_field_element_be,
+        bigint_to_bytes_be
+    );
+}
+
+#[cfg(feature = "alloc")]
+impl<F: PrimeField> PoseidonBytesHasher for optimized::OptimizedPoseidon<F> {
+    impl_hash_bytes!(
+        hash_bytes_le,
+        bytes_to_prime_field_element_le,
+        bigint_to_bytes_le
+    );
+    impl_hash_bytes!(
+        hash_bytes_be,
+        bytes_to_prime_field_element_be,
+        bigint_to_bytes_be
+    );
+}
+
+#[cfg(feature = "alloc")]
+impl<F: PrimeField> PoseidonBytesHasher for poseidon2::Poseidon2<F> {
+    impl_hash_bytes!(
+        hash_bytes_le,
+        bytes_to_prime_field_element_le,
+        bigint_to_bytes_le
+    );
+    impl_hash_bytes!(
+        hash_bytes_be,
+        bytes_to_prime_field_element_be,
+        bigint_to_bytes_be
+    );
+}
+
+#[cfg(feature = "alloc")]
+impl<F: PrimeField> PoseidonBytesHasher for halo2::Halo2Poseidon<F> {
+    impl_hash_bytes!(
+        hash_bytes_le,
+        bytes_to_prime_field_element_le,
+        bigint_to_bytes_le
+    );
+    impl_hash_bytes!(
+        hash_bytes_be,
+        bytes_to_prime_field_element_be,
+        bigint_to_bytes_be
+    );
 }
 
 /// Checks whether a slice of bytes is not empty or its length does not exceed
@@ -468,8 +1407,55 @@ where
     Ok(input)
 }
 
+/// Reads a big integer from bytes, the least significant byte first. Returns
+/// `None` if the number doesn't fit in the integer.
+fn bigint_from_bytes<B: BigInteger>(bytes: impl Iterator<Item = u8>) -> Option<B> {
+    let mut bigint = B::default();
+    let limbs = bigint.as_mut();
+    for (i, byte) in bytes.enumerate() {
+        match limbs.get_mut(i / 8) {
+            Some(limb) => *limb |= u64::from(byte) << (8 * (i % 8)),
+            None if byte == 0 => {}
+            None => return None,
+        }
+    }
+    Some(bigint)
+}
+
+fn bigint_from_bytes_le<B: BigInteger>(input: &[u8]) -> Option<B> {
+    bigint_from_bytes(input.iter().copied())
+}
+
+fn bigint_from_bytes_be<B: BigInteger>(input: &[u8]) -> Option<B> {
+    bigint_from_bytes(input.iter().rev().copied())
+}
+
+/// Writes a big integer into [`HASH_LEN`] little-endian bytes, without
+/// allocating like [`BigInteger::to_bytes_le`](ark_ff::BigInteger::to_bytes_le).
+/// Returns an error if the integer isn't serialized into exactly
+/// [`HASH_LEN`] bytes.
+fn bigint_to_bytes_le<B: BigInteger>(bigint: B) -> Result<[u8; HASH_LEN], PoseidonError> {
+    let limbs = bigint.as_ref();
+    if limbs.len() * 8 != HASH_LEN {
+        return Err(PoseidonError::VecToArray);
+    }
+    let mut bytes = [0u8; HASH_LEN];
+    for (chunk, limb) in bytes.chunks_exact_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+/// Writes a big integer into [`HASH_LEN`] big-endian bytes, see
+/// [`bigint_to_bytes_le`].
+fn bigint_to_bytes_be<B: BigInteger>(bigint: B) -> Result<[u8; HASH_LEN], PoseidonError> {
+    let mut bytes = bigint_to_bytes_le(bigint)?;
+    bytes.reverse();
+    Ok(bytes)
+}
+
 macro_rules! impl_bytes_to_prime_field_element {
-    ($name:ident, $from_bytes_method:ident, $endianess:expr) => {
+    ($name:ident, $from_bytes_fn:ident, $endianess:expr) => {
         #[doc = "Converts a slice of "]
         #[doc = $endianess]
         #[doc = "-endian bytes into a prime field element, \
@@ -478,8 +1464,7 @@ macro_rules! impl_bytes_to_prime_field_element {
         where
             F: PrimeField,
         {
-            let element = num_bigint::BigUint::$from_bytes_method(input);
-            let element = F::BigInt::try_from(element).map_err(|_| PoseidonError::BytesToBigInt)?;
+            let element = $from_bytes_fn::<F::BigInt>(input).ok_or(PoseidonError::BytesToBigInt)?;
 
             // In theory, `F::from_bigint` should also perform a check whether input is
             // larger than modulus (and return `None` if it is), but it's not reliable...
@@ -494,9 +1479,14 @@ macro_rules! impl_bytes_to_prime_field_element {
     };
 }
 
-impl_bytes_to_prime_field_element!(bytes_to_prime_field_element_le, from_bytes_le, "little");
-impl_bytes_to_prime_field_element!(bytes_to_prime_field_element_be, from_bytes_be, "big");
+impl_bytes_to_prime_field_element!(
+    bytes_to_prime_field_element_le,
+    bigint_from_bytes_le,
+    "little"
+);
+impl_bytes_to_prime_field_element!(bytes_to_prime_field_element_be, bigint_from_bytes_be, "big");
 
+#[cfg(feature = "alloc")]
 impl<F: PrimeField> Poseidon<F> {
     pub fn new_circom(nr_inputs: usize) -> Result<Poseidon<Fr>, PoseidonError> {
         Self::with_domain_tag_circom(nr_inputs, Fr::zero())
@@ -520,3 +1510,36 @@ impl<F: PrimeField> Poseidon<F> {
         Ok(Poseidon::<Fr>::with_domain_tag(params, domain_tag))
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<F: PrimeField> Poseidon<F> {
+    /// Returns a new Poseidon hasher based on the BLS12-381 parameters
+    /// provided by the library, for the given number of inputs
+    /// (`1 ≤ nr_inputs ≤ 12`).
+    pub fn new_bls12_381(nr_inputs: usize) -> Result<Poseidon<ark_bls12_381::Fr>, PoseidonError> {
+        Self::with_domain_tag_bls12_381(nr_inputs, ark_bls12_381::Fr::zero())
+    }
+
+    /// Returns a new Poseidon hasher based on the BLS12-381 parameters
+    /// provided by the library, for the given number of inputs
+    /// (`1 ≤ nr_inputs ≤ 12`) and a domain tag.
+    pub fn with_domain_tag_bls12_381(
+        nr_inputs: usize,
+        domain_tag: ark_bls12_381::Fr,
+    ) -> Result<Poseidon<ark_bls12_381::Fr>, PoseidonError> {
+        let width = nr_inputs + 1;
+        if width > MAX_X5_LEN {
+            return Err(PoseidonError::InvalidWidth {
+                width,
+                max_limit: MAX_X5_LEN,
+            });
+        }
+
+        let params = crate::parameters::bls12_381_x5::get_poseidon_parameters::<ark_bls12_381::Fr>(
+            width.try_into().map_err(|_| PoseidonError::U64Tou8)?,
+        )?;
+        Ok(Poseidon::<ark_bls12_381::Fr>::with_domain_tag(
+            params, domain_tag,
+        ))
+    }
+}
@@ -0,0 +1,112 @@
+//! Helpers for operations on dense matrices of prime field elements,
+//! represented as vectors of rows.
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+
+/// Returns the identity matrix of size `n`.
+pub(crate) fn identity<F: PrimeField>(n: usize) -> Vec<Vec<F>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| F::from((i == j) as u64)).collect())
+        .collect()
+}
+
+/// Multiplies the matrix `a` by the matrix `b`.
+pub(crate) fn mat_mul<F: PrimeField>(a: &[Vec<F>], b: &[Vec<F>]) -> Vec<Vec<F>> {
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|j| {
+                    row.iter()
+                        .enumerate()
+                        .fold(F::zero(), |acc, (k, a)| acc + *a * b[k][j])
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Multiplies the matrix `m` by the column vector `v`.
+pub(crate) fn mat_vec_mul<F: PrimeField>(m: &[Vec<F>], v: &[F]) -> Vec<F> {
+    m.iter()
+        .map(|row| {
+            row.iter()
+                .zip(v.iter())
+                .fold(F::zero(), |acc, (a, b)| acc + *a * b)
+        })
+        .collect()
+}
+
+/// Returns the inverse of the square matrix `m`, computed with Gauss-Jordan
+/// elimination, or `None` if the matrix is singular.
+pub(crate) fn mat_inverse<F: PrimeField>(m: &[Vec<F>]) -> Option<Vec<Vec<F>>> {
+    let n = m.len();
+    let mut a: Vec<Vec<F>> = m.to_vec();
+    let mut inv = identity(n);
+
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| !a[row][col].is_zero())?;
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+
+        let pivot_inv = a[col][col].inverse()?;
+        for j in 0..n {
+            a[col][j] *= pivot_inv;
+            inv[col][j] *= pivot_inv;
+        }
+
+        for row in 0..n {
+            if row == col || a[row][col].is_zero() {
+                continue;
+            }
+            let factor = a[row][col];
+            for j in 0..n {
+                let (a_col, inv_col) = (a[col][j], inv[col][j]);
+                a[row][j] -= factor * a_col;
+                inv[row][j] -= factor * inv_col;
+            }
+        }
+    }
+
+    Some(inv)
+}
+
+/// Returns the non-zero rows of the row echelon form of `rows`, which are a
+/// basis of the space spanned by `rows`. Their number is the rank.
+pub(crate) fn row_echelon<F: PrimeField>(mut rows: Vec<Vec<F>>) -> Vec<Vec<F>> {
+    let n = rows.first().map_or(0, |row| row.len());
+    let mut rank = 0;
+
+    for col in 0..n {
+        let Some(pivot) = (rank..rows.len()).find(|&row| !rows[row][col].is_zero()) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+
+        // Non-zero, checked above.
+        let pivot_inv = rows[rank][col].inverse().unwrap();
+        let (pivot_rows, rest) = rows.split_at_mut(rank + 1);
+        let pivot_row = &pivot_rows[rank];
+        for row in rest.iter_mut() {
+            if row[col].is_zero() {
+                continue;
+            }
+            let factor = row[col] * pivot_inv;
+            row.iter_mut()
+                .zip(pivot_row.iter())
+                .skip(col)
+                .for_each(|(a, b)| *a -= factor * b);
+        }
+        rank += 1;
+    }
+
+    rows.truncate(rank);
+    rows
+}
+
+/// Checks whether `elements` contains any duplicates.
+pub(crate) fn has_duplicates<F: PrimeField>(elements: &[F]) -> bool {
+    elements
+        .iter()
+        .enumerate()
+        .any(|(i, a)| elements[i + 1..].contains(a))
+}
@@ -0,0 +1,257 @@
+//! Security checks of MDS matrices.
+//!
+//! Apart from being MDS, the matrix used by Poseidon must not admit
+//! infinitely long invariant subspace trails - subspaces of states for which
+//! the S-box of partial rounds is never active, making partial rounds linear.
+//! The checks follow the algorithms 1-3 from
+//! [Proving Resistance Against Infinitely Long Subspace Trails](https://eprint.iacr.org/2019/187)
+//! by Grassi et al., used by the
+//! [generate_parameters_grain.sage](https://extgit.iaik.tugraz.at/krypto/hadeshash/-/blob/master/code/generate_parameters_grain.sage)
+//! script to reject matrices.
+//!
+//! ```rust
+//! use light_poseidon::{mds, parameters::bn254_x5};
+//! use ark_bn254::Fr;
+//!
+//! let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+//! assert!(mds::check_mds_security(&params.mds, 3).is_ok());
+//! ```
+use alloc::{vec, vec::Vec};
+use ark_ff::PrimeField;
+
+use crate::{
+    matrix::{has_duplicates, identity, mat_inverse, mat_mul, mat_vec_mul, row_echelon},
+    PoseidonError,
+};
+
+/// Maximum size of matrices other than Cauchy matrices checked by
+/// [`is_mds`](crate::mds::is_mds). The check computes the determinants of all
+/// square submatrices, whose number grows exponentially with the size.
+pub const MAX_EXHAUSTIVE_MDS_WIDTH: usize = 12;
+
+/// Checks whether the given matrix is a secure MDS matrix for the state of
+/// `width` elements:
+///
+/// * it's a square matrix of size `width`,
+/// * it's invertible,
+/// * it's MDS (all its square submatrices are invertible),
+/// * it doesn't admit infinitely long invariant subspace trails.
+pub fn check_mds_security<F: PrimeField>(
+    mds: &[Vec<F>],
+    width: usize,
+) -> Result<(), PoseidonError> {
+    if width == 0 || mds.len() != width || mds.iter().any(|row| row.len() != width) {
+        return Err(PoseidonError::InvalidMdsSize { width });
+    }
+    if mat_inverse(mds).is_none() {
+        return Err(PoseidonError::MdsNotInvertible);
+    }
+    if !is_mds(mds)? {
+        return Err(PoseidonError::NotMds);
+    }
+
+    algorithm_1(mds)?;
+    algorithm_2(mds)?;
+    algorithm_3(mds)
+}
+
+/// Checks whether the given square matrix is MDS, which means that all its
+/// square submatrices are invertible.
+///
+/// Cauchy matrices (like the ones generated by the
+/// [`grain`](crate::grain) module) are recognized and checked in polynomial
+/// time. For other matrices, determinants of all the submatrices are
+/// computed, which is exponential in the size of the matrix, so they are
+/// rejected with [`PoseidonError::MdsTooLarge`](crate::PoseidonError::MdsTooLarge)
+/// if they are larger than
+/// [`MAX_EXHAUSTIVE_MDS_WIDTH`](crate::mds::MAX_EXHAUSTIVE_MDS_WIDTH).
+pub fn is_mds<F: PrimeField>(mds: &[Vec<F>]) -> Result<bool, PoseidonError> {
+    if mds.iter().flatten().any(|a| a.is_zero()) {
+        return Ok(false);
+    }
+    if let Some(is_mds) = is_cauchy_mds(mds) {
+        return Ok(is_mds);
+    }
+
+    let t = mds.len();
+    if t > MAX_EXHAUSTIVE_MDS_WIDTH {
+        return Err(PoseidonError::MdsTooLarge {
+            width: t,
+            max_limit: MAX_EXHAUSTIVE_MDS_WIDTH,
+        });
+    }
+    // Index of each mask of rows or columns among the masks with the same
+    // number of bits.
+    let mut index = vec![0usize; 1 << t];
+    // Determinants of the submatrices of the previous size, indexed by masks
+    // of rows and columns. There is one empty submatrix.
+    let mut prev_dets = vec![F::one()];
+    let mut prev_len = 1;
+
+    for k in 1..=t {
+        let masks: Vec<usize> = (0..1usize << t)
+            .filter(|mask| mask.count_ones() as usize == k)
+            .collect();
+        let mut dets = Vec::with_capacity(masks.len() * masks.len());
+
+        for rows in masks.iter() {
+            // Laplace expansion along the first row.
+            let first_row = rows.trailing_zeros() as usize;
+            let sub_rows = index[rows & (rows - 1)];
+
+            for cols in masks.iter() {
+                let mut det = F::zero();
+                for (i, col) in (0..t).filter(|col| cols & (1 << col) != 0).enumerate() {
+                    let sub_cols = index[cols & !(1 << col)];
+                    let term = mds[first_row][col] * prev_dets[sub_rows * prev_len + sub_cols];
+                    if i % 2 == 0 {
+                        det += term;
+                    } else {
+                        det -= term;
+                    }
+                }
+                if det.is_zero() {
+                    return Ok(false);
+                }
+                dets.push(det);
+            }
+        }
+
+        for (i, mask) in masks.iter().enumerate() {
+            index[*mask] = i;
+        }
+        prev_dets = dets;
+        prev_len = masks.len();
+    }
+
+    Ok(true)
+}
+
+/// If the matrix with non-zero elements is a Cauchy matrix
+/// `M[i][j] = 1 / (x_i + y_j)`, returns whether it's MDS, which is the case
+/// when all `x_i` are distinct and all `y_j` are distinct. Otherwise returns
+/// `None`.
+fn is_cauchy_mds<F: PrimeField>(mds: &[Vec<F>]) -> Option<bool> {
+    let inv: Vec<Vec<F>> = mds
+        .iter()
+        .map(|row| row.iter().map(|a| a.inverse()).collect())
+        .collect::<Option<_>>()?;
+
+    // `inv[i][j] = x_i + y_j` for all `i` and `j`.
+    let is_cauchy = inv.iter().all(|row| {
+        row.iter()
+            .zip(inv[0].iter())
+            .all(|(a, b)| *a - row[0] - b + inv[0][0] == F::zero())
+    });
+    if !is_cauchy {
+        return None;
+    }
+
+    let xs: Vec<F> = inv.iter().map(|row| row[0]).collect();
+    Some(!has_duplicates(&xs) && !has_duplicates(&inv[0]))
+}
+
+/// Algorithm 1: checks that for `1 ≤ r < width`, `M^r` is not a multiple of
+/// the identity and there is no subspace invariant under `M^r` for whose
+/// elements the S-box is inactive in `r` consecutive partial rounds.
+fn algorithm_1<F: PrimeField>(mds: &[Vec<F>]) -> Result<(), PoseidonError> {
+    let t = mds.len();
+    let mut power = identity(t);
+    // Rows `e_0 * M^j` for `j < r`. Their kernel is the subspace of states for
+    // which the S-box is inactive in `r` consecutive partial rounds.
+    let mut constraints = Vec::with_capacity(t);
+
+    for rounds in 1..t {
+        constraints.push(power[0].clone());
+        power = mat_mul(&power, mds);
+
+        if is_scalar(&power) || has_invariant_subspace(constraints.clone(), &power) {
+            return Err(PoseidonError::InvariantSubspaceTrail {
+                algorithm: 1,
+                rounds,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Algorithm 2: checks that the smallest subspace invariant under `M`
+/// containing the first unit vector is the whole space.
+fn algorithm_2<F: PrimeField>(mds: &[Vec<F>]) -> Result<(), PoseidonError> {
+    if !is_cyclic_from_first_unit_vector(mds) {
+        return Err(PoseidonError::InvariantSubspaceTrail {
+            algorithm: 2,
+            rounds: 1,
+        });
+    }
+    Ok(())
+}
+
+/// Algorithm 3: performs the check of the algorithm 2 for `M^r`, where
+/// `2 ≤ r ≤ 4 * width`.
+fn algorithm_3<F: PrimeField>(mds: &[Vec<F>]) -> Result<(), PoseidonError> {
+    let t = mds.len();
+    let mut power = mds.to_vec();
+
+    for rounds in 2..=4 * t {
+        power = mat_mul(&power, mds);
+        if !is_cyclic_from_first_unit_vector(&power) {
+            return Err(PoseidonError::InvariantSubspaceTrail {
+                algorithm: 3,
+                rounds,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether the matrix is a multiple of the identity matrix.
+fn is_scalar<F: PrimeField>(m: &[Vec<F>]) -> bool {
+    m.iter().enumerate().all(|(i, row)| {
+        row.iter()
+            .enumerate()
+            .all(|(j, a)| if i == j { *a == m[0][0] } else { a.is_zero() })
+    })
+}
+
+/// Checks whether the kernel of `constraints` contains a non-trivial subspace
+/// invariant under `m`.
+///
+/// The largest such subspace is computed as the limit of `U_0 = ker(C)`,
+/// `U_{k+1} = U_k ∩ m^-1(U_k)`, where `U_k` is represented by the rows of
+/// the matrix `C_k` whose kernel it is.
+fn has_invariant_subspace<F: PrimeField>(constraints: Vec<Vec<F>>, m: &[Vec<F>]) -> bool {
+    let t = m.len();
+    let mut basis = row_echelon(constraints);
+    loop {
+        if basis.len() == t {
+            return false;
+        }
+        let preimage = mat_mul(&basis, m);
+        let next = row_echelon([basis.clone(), preimage].concat());
+        if next.len() == basis.len() {
+            return true;
+        }
+        basis = next;
+    }
+}
+
+/// Checks whether the vectors `e_0, m * e_0, m^2 * e_0, ...` span the whole
+/// space.
+fn is_cyclic_from_first_unit_vector<F: PrimeField>(m: &[Vec<F>]) -> bool {
+    let t = m.len();
+    let mut v = identity(t).swap_remove(0);
+    let mut vectors = vec![v.clone()];
+
+    for rank in 1..t {
+        v = mat_vec_mul(m, &v);
+        vectors.push(v.clone());
+        if row_echelon(vectors.clone()).len() == rank {
+            return false;
+        }
+    }
+
+    true
+}
@@ -0,0 +1,242 @@
+//! Append-only incremental Merkle tree with Poseidon node hashing.
+//!
+//! Nodes are hashed with [`Poseidon::<Fr>::new_circom(2)`](crate::Poseidon::new_circom),
+//! i.e. `node = Poseidon(left, right)`, which is the layout used by the
+//! on-chain Merkle trees of Tornado Cash and Light Protocol and by the
+//! circomlib-based inclusion proof circuits:
+//!
+//! * leaves are appended from left to right,
+//! * empty subtrees are represented by the precomputed zero hashes
+//!   (`zero_hashes[0]` is the zero leaf, `zero_hashes[i + 1] =
+//!   Poseidon(zero_hashes[i], zero_hashes[i])`),
+//! * a [`MerkleProof`](crate::merkle::MerkleProof) consists of the sibling
+//!   nodes from the leaf level up (`pathElements`) and the bits of the leaf
+//!   index, least significant bit first (`pathIndices`), where the bit `1`
+//!   means that the node is the right child.
+//!
+//! ```rust
+//! use light_poseidon::merkle::MerkleTree;
+//! use ark_bn254::Fr;
+//!
+//! let mut tree = MerkleTree::new(20).unwrap();
+//!
+//! let index = tree.append(Fr::from(42u64)).unwrap();
+//! let proof = tree.proof(index).unwrap();
+//! assert!(proof.verify(Fr::from(42u64), tree.root()).unwrap());
+//! ```
+use alloc::{vec, vec::Vec};
+use ark_bn254::Fr;
+use ark_ff::Zero;
+
+use crate::{borrowed::BorrowedPoseidon, Poseidon, PoseidonError, PoseidonHasher};
+
+/// Maximum height of the tree, so the number of leaves fits in `usize` also on
+/// 32-bit targets.
+pub const MAX_HEIGHT: usize = 31;
+
+/// Returns the zero hashes of a tree with the given height, starting with
+/// `zero_leaf` and ending with the root of an empty tree.
+pub fn zero_hashes(height: usize, zero_leaf: Fr) -> Result<Vec<Fr>, PoseidonError> {
+    let poseidon = BorrowedPoseidon::new_circom(2)?;
+    let mut zero_hashes = Vec::with_capacity(height + 1);
+    zero_hashes.push(zero_leaf);
+    for i in 0..height {
+        let zero_hash = poseidon.hash(&[zero_hashes[i], zero_hashes[i]])?;
+        zero_hashes.push(zero_hash);
+    }
+    Ok(zero_hashes)
+}
+
+/// Inclusion proof of a single leaf.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Index of the leaf.
+    pub leaf_index: usize,
+    /// Sibling nodes, from the leaf level up to the children of the root.
+    pub path_elements: Vec<Fr>,
+}
+
+impl MerkleProof {
+    /// Bits of the leaf index, least significant bit first. `true` means that
+    /// the node on the given level is the right child.
+    pub fn path_indices(&self) -> Vec<bool> {
+        (0..self.path_elements.len())
+            .map(|level| {
+                self.leaf_index
+                    .checked_shr(level as u32)
+                    .is_some_and(|bits| bits & 1 == 1)
+            })
+            .collect()
+    }
+
+    /// Computes the root of the tree containing `leaf` at the proven index.
+    ///
+    /// Returns an error if the proof has more than
+    /// [`MAX_HEIGHT`](crate::merkle::MAX_HEIGHT) elements or if the leaf
+    /// index doesn't fit in the height of the proof - the bits above it
+    /// would be ignored otherwise.
+    pub fn compute_root(&self, leaf: Fr) -> Result<Fr, PoseidonError> {
+        let height = self.path_elements.len();
+        if height > MAX_HEIGHT {
+            return Err(PoseidonError::InvalidMerkleTreeHeight {
+                height,
+                max_limit: MAX_HEIGHT,
+            });
+        }
+        if self.leaf_index >> height != 0 {
+            return Err(PoseidonError::LeafIndexOutOfRange {
+                index: self.leaf_index,
+                height,
+            });
+        }
+
+        // Borrows the static parameters, so no allocation per call.
+        let poseidon = BorrowedPoseidon::new_circom(2)?;
+        let mut node = leaf;
+        for (sibling, is_right) in self.path_elements.iter().zip(self.path_indices()) {
+            node = if is_right {
+                poseidon.hash(&[*sibling, node])?
+            } else {
+                poseidon.hash(&[node, *sibling])?
+            };
+        }
+        Ok(node)
+    }
+
+    /// Checks whether `leaf` is included in the tree with the given `root`.
+    pub fn verify(&self, leaf: Fr, root: Fr) -> Result<bool, PoseidonError> {
+        Ok(self.compute_root(leaf)? == root)
+    }
+}
+
+/// Append-only Merkle tree of a fixed height.
+pub struct MerkleTree {
+    poseidon: Poseidon<Fr>,
+    height: usize,
+    zero_hashes: Vec<Fr>,
+    /// Non-empty nodes of every level, from the leaves up to the root. The
+    /// last node of a level is updated on every append, with zero hashes in
+    /// place of the missing right children.
+    layers: Vec<Vec<Fr>>,
+}
+
+impl MerkleTree {
+    /// Returns an empty tree of the given height (`1 ≤ height ≤ 31`), with
+    /// zero as the zero leaf (like in Light Protocol).
+    pub fn new(height: usize) -> Result<Self, PoseidonError> {
+        Self::with_zero_leaf(height, Fr::zero())
+    }
+
+    /// Returns an empty tree of the given height (`1 ≤ height ≤ 31`), with a
+    /// custom zero leaf (e.g. `keccak256("tornado") % p` in Tornado Cash).
+    pub fn with_zero_leaf(height: usize, zero_leaf: Fr) -> Result<Self, PoseidonError> {
+        if height == 0 || height > MAX_HEIGHT {
+            return Err(PoseidonError::InvalidMerkleTreeHeight {
+                height,
+                max_limit: MAX_HEIGHT,
+            });
+        }
+
+        Ok(Self {
+            poseidon: Poseidon::<Fr>::new_circom(2)?,
+            height,
+            zero_hashes: zero_hashes(height, zero_leaf)?,
+            layers: vec![Vec::new(); height + 1],
+        })
+    }
+
+    /// Height of the tree (number of levels below the root).
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Maximum number of leaves.
+    pub fn capacity(&self) -> usize {
+        1 << self.height
+    }
+
+    /// Number of appended leaves.
+    pub fn len(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers[0].is_empty()
+    }
+
+    /// Zero hashes of every level, starting with the zero leaf and ending
+    /// with the root of the empty tree.
+    pub fn zero_hashes(&self) -> &[Fr] {
+        &self.zero_hashes
+    }
+
+    /// Returns the leaf with the given index, if it was appended.
+    pub fn leaf(&self, index: usize) -> Option<Fr> {
+        self.layers[0].get(index).copied()
+    }
+
+    /// Current root of the tree.
+    pub fn root(&self) -> Fr {
+        self.layers[self.height]
+            .first()
+            .copied()
+            .unwrap_or(self.zero_hashes[self.height])
+    }
+
+    /// Appends the leaf and returns its index.
+    pub fn append(&mut self, leaf: Fr) -> Result<usize, PoseidonError> {
+        let index = self.len();
+        if index == self.capacity() {
+            return Err(PoseidonError::MerkleTreeFull {
+                capacity: self.capacity(),
+            });
+        }
+
+        self.layers[0].push(leaf);
+        let mut node_index = index;
+        let mut node = leaf;
+        for level in 0..self.height {
+            node = if node_index & 1 == 0 {
+                self.poseidon.hash(&[node, self.zero_hashes[level]])?
+            } else {
+                self.poseidon
+                    .hash(&[self.layers[level][node_index - 1], node])?
+            };
+            node_index /= 2;
+
+            let layer = &mut self.layers[level + 1];
+            if node_index == layer.len() {
+                layer.push(node);
+            } else {
+                layer[node_index] = node;
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Returns the inclusion proof of the leaf with the given index against
+    /// the current root.
+    pub fn proof(&self, index: usize) -> Result<MerkleProof, PoseidonError> {
+        if index >= self.len() {
+            return Err(PoseidonError::InvalidLeafIndex {
+                index,
+                len: self.len(),
+            });
+        }
+
+        let path_elements = (0..self.height)
+            .map(|level| {
+                self.layers[level]
+                    .get((index >> level) ^ 1)
+                    .copied()
+                    .unwrap_or(self.zero_hashes[level])
+            })
+            .collect();
+
+        Ok(MerkleProof {
+            leaf_index: index,
+            path_elements,
+        })
+    }
+}
@@ -0,0 +1,328 @@
+//! Optimized Poseidon permutation with sparse MDS matrices in partial rounds.
+//!
+//! The optimization follows the appendix B of the
+//! [Poseidon paper](https://eprint.iacr.org/2019/458) and is equivalent to the
+//! one used by `poseidon_constants_opt` in circomlib and by neptune:
+//!
+//! * Round constants of partial rounds are moved forward through the linear
+//!   layer, so each partial round adds only one constant (to the element which
+//!   goes through the S-box). The remaining constants are accumulated in the
+//!   first full round after the partial rounds.
+//! * The MDS matrix of each partial round is factorized into a sparse matrix
+//!   (non-zero first row, first column and diagonal) and a matrix which
+//!   doesn't touch the first element of the state. The latter commutes with
+//!   the partial S-box, so it's moved back to the previous round. What's left
+//!   after the first partial round is merged with the MDS matrix of the last
+//!   full round before the partial rounds.
+//!
+//! Partial rounds cost *O(width)* multiplications instead of *O(width²)*,
+//! while the output stays identical to [`permute`](crate::permute).
+use alloc::{vec, vec::Vec};
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, Zero};
+
+use crate::{
+    matrix::{mat_inverse, mat_mul, mat_vec_mul},
+    sbox::{self, PowerMap, Sbox},
+    with_stack_state, PoseidonError, PoseidonHasher, PoseidonParameters, MAX_X5_LEN,
+};
+
+/// Sparse matrix used in a partial round of the optimized permutation.
+///
+/// All its elements are zero, except the first row, the first column and the
+/// diagonal (which consists of ones, apart from the first element).
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseMdsMatrix<F: PrimeField> {
+    /// The first row of the matrix.
+    pub row: Vec<F>,
+    /// The first column of the matrix, without its first element (which is
+    /// already a part of `row`).
+    pub col_hat: Vec<F>,
+}
+
+impl<F: PrimeField> SparseMdsMatrix<F> {
+    #[inline(always)]
+    fn apply(&self, state: &mut [F]) {
+        let first = state[0];
+        state[0] = self
+            .row
+            .iter()
+            .zip(state.iter())
+            .fold(F::zero(), |acc, (m, a)| acc + *m * a);
+        state[1..]
+            .iter_mut()
+            .zip(self.col_hat.iter())
+            .for_each(|(a, m)| *a += *m * first);
+    }
+}
+
+/// Parameters of the optimized Poseidon permutation, derived from
+/// [`PoseidonParameters`](crate::PoseidonParameters).
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptimizedPoseidonParameters<F: PrimeField> {
+    /// Round constants of full rounds, `width` per round.
+    pub full_ark: Vec<F>,
+    /// Round constants of partial rounds, one per round.
+    pub partial_ark: Vec<F>,
+    /// MDS matrix used in full rounds.
+    pub mds: Vec<Vec<F>>,
+    /// Dense matrix used in the last full round before partial rounds.
+    pub pre_sparse_mds: Vec<Vec<F>>,
+    /// Sparse matrices used in partial rounds, one per round.
+    pub sparse_mds: Vec<SparseMdsMatrix<F>>,
+    /// Number of full rounds.
+    pub full_rounds: usize,
+    /// Number of partial rounds.
+    pub partial_rounds: usize,
+    /// Number of prime fields in the state.
+    pub width: usize,
+    /// S-box applied to elements of the state.
+    pub sbox: Sbox,
+}
+
+impl<F: PrimeField> OptimizedPoseidonParameters<F> {
+    /// Derives the optimized parameters from the given Poseidon parameters.
+    ///
+    /// The number of full rounds has to be even and greater than zero, the
+    /// number of round constants and the size of the MDS matrix have to match
+    /// the width and the MDS matrix without its first row and column has to
+    /// be invertible, which is always the case for MDS matrices.
+    pub fn new(params: &PoseidonParameters<F>) -> Result<Self, PoseidonError> {
+        let width = params.width;
+        if params.full_rounds == 0 || !params.full_rounds.is_multiple_of(2) {
+            return Err(PoseidonError::InvalidFullRounds {
+                full_rounds: params.full_rounds,
+            });
+        }
+        let expected = (params.full_rounds + params.partial_rounds) * width;
+        if params.ark.len() != expected {
+            return Err(PoseidonError::InvalidArkLength {
+                len: params.ark.len(),
+                expected,
+            });
+        }
+        if width < 2 || params.mds.len() != width || params.mds.iter().any(|row| row.len() != width)
+        {
+            return Err(PoseidonError::InvalidMdsSize { width });
+        }
+
+        let half_rounds = params.full_rounds / 2;
+        let all_rounds = params.full_rounds + params.partial_rounds;
+
+        let mut ark: Vec<Vec<F>> = params.ark.chunks(width).map(|c| c.to_vec()).collect();
+
+        // Move the constants of all elements except the first one forward
+        // through the linear layer, up to the first full round after the
+        // partial rounds.
+        for round in half_rounds..half_rounds + params.partial_rounds {
+            let mut tail = ark[round].clone();
+            tail[0] = F::zero();
+            let moved = mat_vec_mul(&params.mds, &tail);
+            ark[round + 1]
+                .iter_mut()
+                .zip(moved)
+                .for_each(|(c, m)| *c += m);
+            ark[round][1..].iter_mut().for_each(|c| *c = F::zero());
+        }
+
+        let partial_ark = ark[half_rounds..half_rounds + params.partial_rounds]
+            .iter()
+            .map(|c| c[0])
+            .collect();
+        let full_ark = ark[..half_rounds]
+            .iter()
+            .chain(ark[half_rounds + params.partial_rounds..all_rounds].iter())
+            .flatten()
+            .copied()
+            .collect();
+
+        // Factorize the matrix of each partial round (starting from the last
+        // one) into `sparse * [[1, 0], [0, m_hat]]` and move the second factor
+        // to the previous round.
+        let mut matrix = params.mds.clone();
+        let mut sparse_mds = Vec::with_capacity(params.partial_rounds);
+        for _ in 0..params.partial_rounds {
+            let m_hat: Vec<Vec<F>> = matrix[1..].iter().map(|row| row[1..].to_vec()).collect();
+            let m_hat_inv = mat_inverse(&m_hat).ok_or(PoseidonError::MdsNotInvertible)?;
+
+            let w = mat_mul(&[matrix[0][1..].to_vec()], &m_hat_inv).remove(0);
+            let mut row = vec![matrix[0][0]];
+            row.extend(w);
+            let col_hat = matrix[1..].iter().map(|row| row[0]).collect();
+            sparse_mds.push(SparseMdsMatrix { row, col_hat });
+
+            let mut moved = vec![vec![F::zero(); width]; width];
+            moved[0][0] = F::one();
+            for (i, row) in m_hat.into_iter().enumerate() {
+                moved[i + 1][1..].copy_from_slice(&row);
+            }
+            matrix = mat_mul(&moved, &params.mds);
+        }
+        sparse_mds.reverse();
+
+        Ok(Self {
+            full_ark,
+            partial_ark,
+            mds: params.mds.clone(),
+            pre_sparse_mds: matrix,
+            sparse_mds,
+            full_rounds: params.full_rounds,
+            partial_rounds: params.partial_rounds,
+            width,
+            sbox: params.sbox,
+        })
+    }
+}
+
+/// Applies the optimized Poseidon permutation to the given state. The output
+/// is identical to the output of [`permute`](crate::permute) called with the
+/// parameters the optimized ones were derived from.
+pub fn permute<F: PrimeField>(
+    params: &OptimizedPoseidonParameters<F>,
+    state: &mut [F],
+) -> Result<(), PoseidonError> {
+    if state.len() != params.width {
+        return Err(PoseidonError::InvalidStateLength {
+            len: state.len(),
+            width: params.width,
+        });
+    }
+
+    apply_rounds(params, state);
+    Ok(())
+}
+
+#[inline(always)]
+fn apply_ark<F: PrimeField>(
+    params: &OptimizedPoseidonParameters<F>,
+    state: &mut [F],
+    round: usize,
+) {
+    state.iter_mut().enumerate().for_each(|(i, a)| {
+        *a += params.full_ark[round * params.width + i];
+    });
+}
+
+#[inline(always)]
+fn apply_sbox_full<F: PrimeField, P: PowerMap>(
+    params: &OptimizedPoseidonParameters<F>,
+    state: &mut [F],
+) {
+    P::apply_all(state, params.sbox);
+}
+
+#[inline(always)]
+fn apply_mds<F: PrimeField>(mds: &[Vec<F>], state: &mut [F]) {
+    with_stack_state(state.len(), |result| {
+        for (r, row) in result.iter_mut().zip(mds) {
+            *r = row
+                .iter()
+                .zip(state.iter())
+                .fold(F::zero(), |acc, (m, a)| acc + *m * a);
+        }
+        state.copy_from_slice(result);
+    })
+}
+
+fn apply_rounds<F: PrimeField>(params: &OptimizedPoseidonParameters<F>, state: &mut [F]) {
+    sbox::select_rounds!(apply_rounds_with, params.sbox)(params, state)
+}
+
+/// [`apply_rounds`] with the S-box map `P`.
+fn apply_rounds_with<F: PrimeField, P: PowerMap>(
+    params: &OptimizedPoseidonParameters<F>,
+    state: &mut [F],
+) {
+    let half_rounds = params.full_rounds / 2;
+
+    for round in 0..half_rounds - 1 {
+        apply_ark(params, state, round);
+        apply_sbox_full::<F, P>(params, state);
+        apply_mds(&params.mds, state);
+    }
+
+    apply_ark(params, state, half_rounds - 1);
+    apply_sbox_full::<F, P>(params, state);
+    apply_mds(&params.pre_sparse_mds, state);
+
+    for (c, sparse_mds) in params.partial_ark.iter().zip(params.sparse_mds.iter()) {
+        state[0] += c;
+        state[0] = P::apply(state[0], params.sbox);
+        sparse_mds.apply(state);
+    }
+
+    for round in half_rounds..params.full_rounds {
+        apply_ark(params, state, round);
+        apply_sbox_full::<F, P>(params, state);
+        apply_mds(&params.mds, state);
+    }
+}
+
+/// A Poseidon hasher using the optimized permutation. Produces the same
+/// hashes as [`Poseidon`](crate::Poseidon) with the same parameters.
+pub struct OptimizedPoseidon<F: PrimeField> {
+    params: OptimizedPoseidonParameters<F>,
+    domain_tag: F,
+    rounds: fn(&OptimizedPoseidonParameters<F>, &mut [F]),
+}
+
+impl<F: PrimeField> OptimizedPoseidon<F> {
+    /// Returns a new optimized Poseidon hasher based on the given parameters.
+    pub fn new(params: OptimizedPoseidonParameters<F>) -> Self {
+        Self::with_domain_tag(params, F::zero())
+    }
+
+    /// Returns a new optimized Poseidon hasher based on the given parameters
+    /// and a domain tag.
+    pub fn with_domain_tag(params: OptimizedPoseidonParameters<F>, domain_tag: F) -> Self {
+        Self {
+            domain_tag,
+            rounds: sbox::select_rounds!(apply_rounds_with, params.sbox),
+            params,
+        }
+    }
+}
+
+impl<F: PrimeField> PoseidonHasher<F> for OptimizedPoseidon<F> {
+    fn hash(&self, inputs: &[F]) -> Result<F, PoseidonError> {
+        if inputs.len() != self.params.width - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: inputs.len(),
+                max_limit: self.params.width - 1,
+                width: self.params.width,
+            });
+        }
+
+        Ok(with_stack_state(self.params.width, |state| {
+            state[0] = self.domain_tag;
+            state[1..].copy_from_slice(inputs);
+            (self.rounds)(&self.params, state);
+            state[0]
+        }))
+    }
+}
+
+impl<F: PrimeField> OptimizedPoseidon<F> {
+    pub fn new_circom(nr_inputs: usize) -> Result<OptimizedPoseidon<Fr>, PoseidonError> {
+        Self::with_domain_tag_circom(nr_inputs, Fr::zero())
+    }
+
+    pub fn with_domain_tag_circom(
+        nr_inputs: usize,
+        domain_tag: Fr,
+    ) -> Result<OptimizedPoseidon<Fr>, PoseidonError> {
+        let width = nr_inputs + 1;
+        if width > MAX_X5_LEN {
+            return Err(PoseidonError::InvalidWidthCircom {
+                width,
+                max_limit: MAX_X5_LEN,
+            });
+        }
+
+        let params = crate::parameters::bn254_x5::get_poseidon_parameters::<Fr>(
+            (width).try_into().map_err(|_| PoseidonError::U64Tou8)?,
+        )?;
+        let params = OptimizedPoseidonParameters::new(&params)?;
+        Ok(OptimizedPoseidon::<Fr>::with_domain_tag(params, domain_tag))
+    }
+}
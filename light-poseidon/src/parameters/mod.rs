@@ -1 +1,13 @@
+#[cfg(feature = "alloc")]
+pub mod bls12_381_x5;
+#[cfg(feature = "alloc")]
+pub mod bn254_inverse;
 pub mod bn254_x5;
+#[cfg(feature = "alloc")]
+pub mod goldilocks_x7;
+#[cfg(feature = "alloc")]
+pub mod pallas_x5;
+#[cfg(feature = "alloc")]
+pub mod poseidon2_bn254_x5;
+#[cfg(feature = "alloc")]
+pub mod vesta_x5;
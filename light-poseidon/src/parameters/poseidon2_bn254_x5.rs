@@ -0,0 +1,1562 @@
+// This file is generated by xtask. Do not edit it manually.
+
+//! Constants and internal matrices of Poseidon2 for the BN254 curve with the
+//! following properties:
+//!
+//! * x^5 S-boxes
+//! * width - *2 ≤ t ≤ 4*
+//! * 8 full rounds and 56 partial rounds
+//!
+//! Round constants were generated with the Grain LFSR (see the
+//! [`grain`](crate::grain) module), which produces the same output as the
+//! [poseidon2_rust_params.sage](https://github.com/HorizenLabs/poseidon2/blob/main/poseidon2_rust_params.sage)
+//! script from the reference implementation with the following parameters:
+//!
+//! ```bash
+//! sage poseidon2_rust_params.sage 1 0 254 <t> 8 56 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
+//! ```
+//!
+//! The internal matrices for *t = 2* and *t = 3* are the fixed ones
+//! (`[[2, 1], [1, 3]]` and `[[2, 1, 1], [1, 2, 1], [1, 1, 3]]`). The diagonal
+//! of the internal matrix for *t = 4* is the one used by the reference
+//! implementation and Barretenberg.
+pub const FULL_ROUNDS: usize = 8;
+pub const PARTIAL_ROUNDS: usize = 56;
+pub const ALPHA: u64 = 5;
+
+use crate::{poseidon2::Poseidon2Parameters, PoseidonError, Sbox};
+use alloc::vec;
+use ark_ff::PrimeField;
+
+/// Returns Poseidon2 parameters for the BN254 curve with the following
+/// properties:
+///
+/// * x^5 S-boxes
+/// * width - *2 ≤ t ≤ 4*
+/// * 8 full rounds and 56 partial rounds
+///
+/// The type argument is a type which implements
+/// [`ark_ff::PrimeField`](ark_ff::PrimeField).
+pub fn get_poseidon2_parameters<F: PrimeField + core::convert::From<ark_ff::BigInteger256>>(
+    t: u8,
+) -> Result<Poseidon2Parameters<F>, PoseidonError> {
+    if t == 0_u8 {
+        Err(PoseidonError::InvalidWidth {
+            width: t as usize,
+            max_limit: 4usize,
+        })
+    } else if 2 == t {
+        let ark = vec![
+            F::from(ark_ff::BigInteger256::new([
+                7817616578516342695,
+                12041114087599168795,
+                18311542326445259320,
+                703809069991762900,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4331763550625516417,
+                14201023056939694132,
+                3069182888826189263,
+                865630468011650756,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3566474286914161200,
+                11533337848482785469,
+                1454188893941615860,
+                2173164774482348753,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5546713666651497759,
+                1033391138293734294,
+                579106019186219236,
+                2859554329514026824,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8767088134270176771,
+                4695306501711252744,
+                4209243863731017562,
+                2192991121640820561,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17805298208823395027,
+                307985100983112664,
+                9602098390607458494,
+                3055472776560074307,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3909282107706262992,
+                13368341216138434697,
+                1588073989677247728,
+                821623203604019809,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15755426777904750113,
+                803549746874466029,
+                9443029210981129783,
+                85256046229640304,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3247251991413109177,
+                7841264143924049595,
+                18269122915921683453,
+                167401055085182909,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17247355753810440945,
+                6068911149477229014,
+                7812597725061720419,
+                1699111382917971815,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9086616471667026833,
+                11249876738920507827,
+                10531948423244928237,
+                3180709101917510650,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6045638319889455877,
+                14048485700492070853,
+                12407180079391136579,
+                2072174480898319934,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6746110078988393302,
+                33862480341740752,
+                12893055833257516694,
+                2688747040681077131,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12762121410920968153,
+                12583693102466367891,
+                8782570957304574643,
+                2901298802732085988,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3713045009585348675,
+                1753181911864277204,
+                2064049868111244977,
+                2891709276830441329,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1377859005849043657,
+                17213302918879987073,
+                2250098761913978943,
+                3179338808143010527,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7236023902221217737,
+                15698137225854421603,
+                9669998922288655229,
+                552725606465661892,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4024542742229642300,
+                10676338206571928785,
+                2835917563533532670,
+                157668069081079593,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8178076668328059049,
+                12133895062126733470,
+                11597165300144392768,
+                2002257975968913669,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3056574329694873596,
+                15152835891335545375,
+                2336296560329179629,
+                3322569079616917512,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12034835072381781688,
+                11425246710696692215,
+                1304950287065922640,
+                1307437582895567736,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7625324869890990606,
+                15546400408522906137,
+                10556636253062694543,
+                208545047093026385,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7874519155181355406,
+                5699848720424976135,
+                9177764430905078766,
+                2242454803620536339,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16918768489580524930,
+                1451360297318946841,
+                8369495911896295217,
+                1617545695573194041,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2102853383857409991,
+                14818696684473492633,
+                6265715398128913129,
+                2899825424943956492,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7012031407790312396,
+                7509561033518701014,
+                15049632849442349075,
+                2029501729809436367,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10865896729759530941,
+                1457647043961857778,
+                17836922434109140734,
+                2409290043080073586,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1270566041830377352,
+                14891794189842536868,
+                7258252898854293803,
+                3174236381978470231,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14648639749528776515,
+                1660232324255600783,
+                5210356524639407775,
+                1649235115699342888,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5246011419136659397,
+                8583939463376414991,
+                3988539085160041585,
+                1683479457214066523,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15620172062379863602,
+                7684285279883317076,
+                18351497879714015238,
+                897786720739953686,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10540561140639464845,
+                8506771935836737836,
+                14152240211101678991,
+                1890431831991524277,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10793138928641828495,
+                600618435932543590,
+                13877963886452429632,
+                923200061044958949,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16205809318213838742,
+                7397066335257516860,
+                3160198027865047320,
+                1510601990922667766,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9866801240052691026,
+                14702449656399697352,
+                10881482235068204458,
+                676305374017174802,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12677365286172731501,
+                1380435406082811253,
+                13772227141419739068,
+                1911790097914468531,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5489908542194921755,
+                2355146368963641151,
+                14798397298817373515,
+                1210238513617216306,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8460619148043677965,
+                18025577457283642374,
+                13675017155226021032,
+                395862298079042366,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12655186675713445102,
+                13358011002929926133,
+                15297284634119018636,
+                2917372813350125739,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8034273717284931869,
+                2185982791782152782,
+                12951413382393270415,
+                2440726185204156347,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4539500151132553907,
+                8691376595824906200,
+                4478210354694403277,
+                2010233294366166640,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5248272632199042497,
+                15189336710541847786,
+                7498899605340474637,
+                2778056244288907515,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13620651143643946710,
+                14256350727656881413,
+                14270146554555854060,
+                1015468296665738832,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                292554386543128384,
+                10515732338267378288,
+                8858109166056940592,
+                348791369923272870,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3104459525386259054,
+                3706509501996465542,
+                9346457373566313197,
+                998628960351368213,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9955704627847062859,
+                3816150674512517976,
+                8539215117740000962,
+                2851037169766766418,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9563222423978047977,
+                4134090717682400521,
+                4931886900955358576,
+                2181286346732320574,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14179168894901588400,
+                18055191287518198047,
+                7173507097360274271,
+                2426987407020299813,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6365929671746204939,
+                9605027792591653205,
+                5594195411713214760,
+                250501994217948813,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10132915144326598729,
+                14559655054176323244,
+                7670837463673856084,
+                12761215476027430,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1135687413980796326,
+                15748351523129783305,
+                2119141318581095358,
+                1310926709634850203,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6973840191868739265,
+                1133547488339520846,
+                7358060163725254722,
+                856422543455136065,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2537781629768649754,
+                3952926295132561306,
+                6300924289786463548,
+                1930189990533446405,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5893579505698628222,
+                6778702467274214034,
+                6866130142038735678,
+                1555505760660470978,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15478953192464200210,
+                17403454457297442222,
+                7721529128250960608,
+                1360294703589482407,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                353188889562277050,
+                14892186255215643417,
+                11432173571329174096,
+                1137487999655651371,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                11276615796778280802,
+                12776567518989086543,
+                15488694486527196992,
+                2277707700932731970,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10166206986740036819,
+                364106907529202693,
+                17928232400318857972,
+                1062132786353612405,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8828067068861466321,
+                4253212145573359161,
+                7613892610833863104,
+                3222308960075869855,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10432355210345241023,
+                14877110326726120038,
+                6198435499051035424,
+                1917810163665871564,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9789163323282868015,
+                15042360471542282800,
+                6820767863046574897,
+                3031296629605872474,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10751711984671309920,
+                16661266258360878322,
+                1565276276401255440,
+                2592303526438568973,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2742332881621149863,
+                11803538530403661451,
+                2637639035157985731,
+                398335568721703586,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3126142991524034215,
+                151352264296347077,
+                14564917722384201204,
+                1369303116767000612,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                517437299484701558,
+                10194692261461724337,
+                16589035092694756392,
+                1853713095538544151,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2639673604112600959,
+                1428143039648754754,
+                13095801650290172662,
+                97933583764930581,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16902791728491724753,
+                18311104903645370571,
+                1988066165513084494,
+                2977850697400937375,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5541440123987594748,
+                9591427865345548116,
+                9669088081248008287,
+                794484372090197528,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5717472324945572905,
+                6174074361583416910,
+                1577632871765737854,
+                3481129492503380273,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13325680800061527036,
+                12309078185183640140,
+                16763311912973755637,
+                1566110581857700361,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12249548568115988678,
+                11209261032218673420,
+                4940466595080791277,
+                771487592615259358,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2674544028287596442,
+                17248485300825939625,
+                10949481555690624629,
+                1023121073329425969,
+            ])),
+        ];
+        let mat_internal_diag_m_1 = vec![
+            F::from(ark_ff::BigInteger256::new([1, 0, 0, 0])),
+            F::from(ark_ff::BigInteger256::new([2, 0, 0, 0])),
+        ];
+        Poseidon2Parameters::new(
+            ark,
+            mat_internal_diag_m_1,
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            t.into(),
+            Sbox::Power(ALPHA),
+        )
+    } else if 3 == t {
+        let ark = vec![
+            F::from(ark_ff::BigInteger256::new([
+                6458331304642816022,
+                9191791745966588794,
+                10078444264992802031,
+                2091475785534126077,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13196537876657739280,
+                13242552074285594254,
+                12411648568120336672,
+                3015986462747209158,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                11424359946556596689,
+                2698455998639397967,
+                2219105332273731329,
+                2246365349865385472,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15782428245603856869,
+                12762929946389366497,
+                13652592920561494567,
+                769322212625167858,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                656627337302130472,
+                3963883011537030758,
+                15489675571286521695,
+                2473147239071445900,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15321051510152419125,
+                10502964042640599943,
+                6513797472532144639,
+                1883790224900438088,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5753026235266483322,
+                738961277496629169,
+                8934088858439642033,
+                2520967471806658381,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17282094170399272525,
+                16580379156546658773,
+                11299574250627272826,
+                1178195396411754989,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                11843547806398251470,
+                2387753460292841813,
+                17734952367284543128,
+                197758656190898648,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                11673112823341286564,
+                12369618416529278774,
+                2844207100017432099,
+                1065502144839691590,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5258847900301915518,
+                6505352688770326422,
+                6071654207755516479,
+                2967749481702258496,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                892821802737558910,
+                14727071363237210694,
+                2792543592443165093,
+                2830937070444722279,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5302271620417049781,
+                14906098032514729777,
+                13131960320030414264,
+                1881667084089419620,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13338254911895600183,
+                586415510492166035,
+                9483754624098305591,
+                2786538975574068840,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                809569393612520424,
+                528120150944813735,
+                9342005836485684535,
+                899704706212247635,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17137185606063142037,
+                13415865686895593136,
+                11391838450430455070,
+                2086742809232669367,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8843393565329129737,
+                1838180464329137089,
+                6218527723244609675,
+                1107386903008522679,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4809630522644666222,
+                17535228293073829195,
+                10801336483130722817,
+                1792268399693959384,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5634636821807834076,
+                15053876840384767471,
+                7947578098813132826,
+                357964892642556076,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16527458989744188390,
+                16677040905594745484,
+                4341476110168786225,
+                3010494594414878830,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                63306881032388373,
+                7327841382898372030,
+                13858532193471856477,
+                1841200778148548229,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5319782128374767476,
+                12105534410073556125,
+                7706728210739274202,
+                2733165640907410782,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13905011766727611265,
+                397545458774684082,
+                2618297833076747029,
+                1409249416379998688,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4617094559105111687,
+                4793267458613048010,
+                17791403032818154766,
+                3486234691922257694,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9674187949040268832,
+                2439672703657190660,
+                3127451649443712794,
+                1877012939541750851,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12273040699418823327,
+                17004437659862682988,
+                4609551378284273612,
+                3118377810905829727,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                18106409021383810916,
+                7005941813576523867,
+                142065113101712121,
+                1356675148274829111,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13417219845588204063,
+                18098831554155340941,
+                8139412102635653279,
+                2752703017946725000,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9030911001104498125,
+                17918615306737643293,
+                777016847745710800,
+                726472871956080806,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15584670787591111609,
+                2863790524049332765,
+                1900103773593318106,
+                2948408348452667051,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16867895570996871532,
+                9840099798364396799,
+                680609925832583881,
+                2047644432353969731,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3617622047312850068,
+                5723491105472485545,
+                3108891270768987616,
+                2290053041924610425,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16635599629165910696,
+                13889630767821251854,
+                16393349863559499543,
+                279513358749840108,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4991402318615115981,
+                5063502445310142847,
+                7620324954341078828,
+                3257591428770437702,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15081224770952329547,
+                5919227265182789027,
+                17764430127524375836,
+                669620769166038202,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15360420589354587895,
+                16261191412625806761,
+                1854549200062090405,
+                1833065620299829980,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6975783924512399374,
+                9737821894529518142,
+                2291263480286660018,
+                702362307909111359,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6069171416060354126,
+                3752469561037779032,
+                5834479341906125444,
+                1730499498393076028,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15285076325592677980,
+                13794437892125540525,
+                3483915936988995384,
+                1117085899585373406,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9062361212365479288,
+                12380341930130305002,
+                15528130627563932004,
+                1328247396736374211,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17259757026730354065,
+                1151600880302546424,
+                7620006839548456097,
+                2576626133210285583,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8960531168600849300,
+                4461975299424440180,
+                17009261745893778992,
+                3038488690496386825,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13165667200848562987,
+                14110818871887028667,
+                5180761908053230572,
+                555646921363871471,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1381758840622748093,
+                9986911534659916114,
+                9826174633534139965,
+                2824156456769809917,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3413617401377599094,
+                17258722595927485798,
+                15605954241271186193,
+                2115646988530496184,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8945482950897992840,
+                702592287789103350,
+                3686043328539833836,
+                3425852069776866095,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7376743520743437269,
+                2111267269708684676,
+                15878419741432144411,
+                2191075595829647030,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2259619124330372427,
+                12001441661304539563,
+                6902370926577093337,
+                1282945395516133573,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16729565964201713852,
+                6956698123409511863,
+                3383169071491634900,
+                2405817082479101412,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5320921342946068874,
+                2691715029914560996,
+                10930922554022577632,
+                1001529153797943229,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                11171553171900432536,
+                15969234436173575856,
+                16356721817558633473,
+                3085620780208706067,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15228643841329377399,
+                4455358404709847991,
+                7982463345877531625,
+                2119812921062617820,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14097760453325158398,
+                856786123610740072,
+                13335858100674396141,
+                1612490154809976395,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10854412943914486782,
+                14892522000790554512,
+                1247299884213565152,
+                28614983888150324,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14929667005143385101,
+                3836713032172734107,
+                8315888069836105516,
+                2238834806901519768,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1227321958173010238,
+                4310456818987391418,
+                12986075078568625117,
+                2763362288870467025,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7441031895532537359,
+                9599919680464559728,
+                8931488460254525139,
+                1654118242821888559,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16259374832042614367,
+                6237462359444241861,
+                11048481482555464692,
+                1927010586885088017,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                147832287409453657,
+                11483538217836605698,
+                15607570256914320247,
+                1027503282893456593,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12124132488385046214,
+                31921346119029583,
+                5274767504760454943,
+                1043762827231794559,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5509839628418401949,
+                7018542083821702829,
+                1594960980879474461,
+                581703325927526154,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3325744805996203348,
+                7543499699481574044,
+                13522420521837659136,
+                2424535168622248373,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8264568362009840078,
+                13623416661050546942,
+                12497801165521077958,
+                140459674360179844,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5605477589105515659,
+                10084396541222391775,
+                183568619191429726,
+                797512561166172386,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3774123395629030959,
+                2881218385746366730,
+                17361136136679132462,
+                2033634766325428180,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12812937017348375870,
+                3412938269445494740,
+                4781767212785234811,
+                2056393341899272936,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10048060944186642319,
+                2426675488974593715,
+                17785911234421064746,
+                230367907622696299,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6619695695594038473,
+                5832859038197865707,
+                7480173222580839341,
+                2774347868835657535,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8705540160700578512,
+                6746830576917213441,
+                7092845282961761070,
+                1931309519409552049,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14595188708134663589,
+                2015698889624141507,
+                14187205567353648784,
+                1871427985608321894,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16271429997482961043,
+                468818470830000426,
+                7996775362779455505,
+                2021487601170175912,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1733406684830268733,
+                18152518321385677508,
+                5836227034235395097,
+                1491710151164857790,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12660150021064259934,
+                13267881929723736097,
+                10315712918880346551,
+                2108981438329286341,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8525962534445484028,
+                9934399915059914018,
+                6988064890623532430,
+                2207548493368319419,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1390870045172465649,
+                6422021221791678552,
+                13078062013033884631,
+                322243138126305932,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2080203751875985086,
+                7362864967529529522,
+                17728687880877874386,
+                1812467639561021094,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                260056793786564966,
+                12888488316328472437,
+                14360227994345277611,
+                1450833317726038835,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17947676061038389710,
+                12834170303949236259,
+                11384599308972499080,
+                143388347964205391,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6546071725730114135,
+                12601887796104903258,
+                8919969549330983706,
+                1794029064565484592,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1021565994830986662,
+                10934776881971040877,
+                12375328443065392690,
+                1135395077632659701,
+            ])),
+        ];
+        let mat_internal_diag_m_1 = vec![
+            F::from(ark_ff::BigInteger256::new([1, 0, 0, 0])),
+            F::from(ark_ff::BigInteger256::new([1, 0, 0, 0])),
+            F::from(ark_ff::BigInteger256::new([2, 0, 0, 0])),
+        ];
+        Poseidon2Parameters::new(
+            ark,
+            mat_internal_diag_m_1,
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            t.into(),
+            Sbox::Power(ALPHA),
+        )
+    } else if 4 == t {
+        let ark = vec![
+            F::from(ark_ff::BigInteger256::new([
+                10018390284920759269,
+                196898842818127395,
+                5249540449481148995,
+                1853312570062057576,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12486221224710452438,
+                2372038863109147677,
+                8230667498854222355,
+                2764611904404804029,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4466505105966356650,
+                4686185096558265002,
+                16210260819355521378,
+                1844031548168280073,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15002325471271702008,
+                5581154705073500415,
+                1229208533183169201,
+                1549225070791782920,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                18309653156114024706,
+                798761732958817262,
+                6904962453156279281,
+                3335412762186210716,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2824096028161810206,
+                14640933461146357672,
+                957840840567621315,
+                1024001058677493842,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14339023814126516630,
+                12239068001133297662,
+                428134084092645147,
+                2673682960814460689,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6214865908119297870,
+                17923963059035301363,
+                10985380589240272449,
+                1430464474809378870,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5109255232332580664,
+                11913027714091798733,
+                4449570166290740355,
+                864862123557185234,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                2323272968957708806,
+                354488099726909104,
+                115174089281514891,
+                80808271106704719,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9646436663147525449,
+                3404572679246369876,
+                2350204275212843361,
+                1069216089054537871,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5059356740217174171,
+                4245857056683447103,
+                2426504795124362174,
+                350059533408463330,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14876286709841668328,
+                6932857857384975351,
+                7976037835777844091,
+                738350885205242785,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16522097747524989503,
+                4157368317794149558,
+                10343110624935622906,
+                2709590753056582169,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8805379462752425633,
+                8594508728147436821,
+                15629690186821248127,
+                2936193411053712582,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17046614324338172999,
+                14086280776151114414,
+                2804088968006330580,
+                728643340397380469,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12986735346000814543,
+                6140074342411686364,
+                6041575944194691717,
+                896092723329689904,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9573905030842087441,
+                12243211539080976096,
+                15287161151491266826,
+                1310836290481124728,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8865134002163281525,
+                6813849753829831047,
+                9066778847678578696,
+                2801725307463304665,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4931814869361681093,
+                13712769805002511750,
+                1776191062268299644,
+                2068661504023016414,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8161631444256445904,
+                3049786034047984668,
+                1021328518293651309,
+                2147500022207188878,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12766468767470212468,
+                926098071429114297,
+                17691598410912255471,
+                76565467953470566,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15547843034426617484,
+                13465733818561903358,
+                11157089789589945854,
+                3107062195097242290,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16908372174309343397,
+                17264932925429761530,
+                11508063480483774160,
+                2682419245684831641,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4870692136216401181,
+                17645600130793395310,
+                2758876031472241166,
+                874943362207641089,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4540479402638267003,
+                13477556963426049071,
+                6055112305493291757,
+                1810598527648098537,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7894770769272900997,
+                9595210915998428021,
+                7642295683223718917,
+                2210716392790471408,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10910178561156475899,
+                15811627963917441510,
+                16460518660187536520,
+                1698297851221778809,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7831732902708890908,
+                1464390598836302271,
+                8568564606321342514,
+                3007171090439369509,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12758232712903990792,
+                5937193763836963893,
+                4629415695575460109,
+                2476198378403296665,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16185652584871361881,
+                3161867062328690813,
+                8447947510117581907,
+                452436262606194895,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10531967515434376071,
+                5577695765815843856,
+                9164856352050088505,
+                1205339682110411496,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3898841196333713180,
+                14650521577519770525,
+                5736581618852866049,
+                1010789789328495026,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12103741763020280571,
+                14760208106156268938,
+                15246749619665902195,
+                1987439155030896717,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                326429241861474059,
+                11335157279655967493,
+                16233357323017397007,
+                2124770605461456708,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13507610432344102875,
+                9765425316929074945,
+                10455054851855122687,
+                3371280263716451574,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9433430149246843174,
+                16916651192445074064,
+                12002862125451454299,
+                3293088726774108791,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15895963712096768440,
+                10975964170403460506,
+                7594578539046143282,
+                441635248990433378,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                55564641555031451,
+                2316046008873247993,
+                6273091099984972305,
+                531938487375579818,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17845282940759944461,
+                6735239388814238924,
+                3181517889518583601,
+                2376846283559998361,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14097127963645492314,
+                1165420652731038559,
+                12527303660854712762,
+                2717289076364278965,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15600044695084040011,
+                255324662529267034,
+                11859356122961343981,
+                2571979992654075442,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1589817027469470176,
+                1086723465680833706,
+                6948011514366564799,
+                2482410610948543635,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6071201116374785253,
+                16554668458221199618,
+                16319484688832471879,
+                2792452762383364279,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13535048470209809113,
+                1831807297936988201,
+                16757520396573457190,
+                508291910620511162,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6946737468087619802,
+                14033399912488027565,
+                12701200401813783486,
+                1348363389498465135,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6788008051328210729,
+                13866524545426155292,
+                4317879914214157329,
+                2633928310905799638,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1183626302001490602,
+                10035686235057284266,
+                1656321729167440177,
+                1887128381037099784,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                964566190254741199,
+                17650087760652370459,
+                14904592615785317921,
+                2929864473487096026,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13584300701347139198,
+                512534187550045064,
+                13489711551083721364,
+                41824696873363624,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17586611824788147557,
+                6430987250922925699,
+                9294838151373947091,
+                348446557360066429,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                15025298913764434311,
+                14393211163878018166,
+                7154440178410267241,
+                3057088631006286899,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13451769229280519155,
+                17839347496757587523,
+                10553299811918798519,
+                2523373819901075642,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16267315463205810352,
+                13830706729545301172,
+                15413288900478726729,
+                287556136711008934,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4573780169675443044,
+                8758089751960064775,
+                2470295096511057988,
+                51551212240288730,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7093949836145798554,
+                12771428392262798771,
+                17021632567931004395,
+                1558106578814965657,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8205915653008540447,
+                10376314495036230740,
+                5774593793305666491,
+                2231830927015656581,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10783762484003267341,
+                10229708558604896492,
+                1831638669050696278,
+                2190429714552610800,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                7310961803978392383,
+                12793746113455595394,
+                17036245927795997300,
+                3106081169494120044,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17421859032088162675,
+                7339791467855418851,
+                4622175020331968961,
+                590786792834928630,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14242884250645212438,
+                12806057845811725595,
+                7743423753614082490,
+                213381026777379804,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1110713325513004805,
+                8318407684973846516,
+                15952888485475298710,
+                1018983205230111328,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                533883137631233338,
+                333001117808183237,
+                16968583542443855481,
+                329716098711096173,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4449676039486426793,
+                7760073051300251162,
+                5615103291054015906,
+                2516053143677338215,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16503526645482286870,
+                6358830762575712333,
+                12313512559299087688,
+                2716767262544184013,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                5426798011730033104,
+                13085704829880126552,
+                6356732802364281819,
+                2175930396888807151,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8262282602783970021,
+                2576069526442506486,
+                14199683559983367515,
+                3432491072538425468,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14778817021916755205,
+                6110468871588391807,
+                2850248286812407967,
+                3411084787375678665,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4906200604739023933,
+                12096549814065429793,
+                5988343102643160344,
+                309820751832846301,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8709336210313678885,
+                10520000332606345601,
+                4756441214598660785,
+                2483744946546306397,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                9617950371599090517,
+                6702332727289490762,
+                7078214601245292934,
+                215269160536524476,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                14694170287735041964,
+                13462371741453101277,
+                7691247574208617782,
+                1078917709155142535,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17559938410729200952,
+                12326273425107991305,
+                8641129484519639030,
+                1699848340767391255,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3946956839294125797,
+                10123891284815211853,
+                3676846437799665248,
+                753827773683953838,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10815195850656127580,
+                17940782720817522247,
+                11666428030894512886,
+                2305765957929457259,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                437280840171101279,
+                6885928680245806601,
+                6031863836827793624,
+                2698250255620259624,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16961604592822056794,
+                12516844188945734293,
+                2404426354458718742,
+                901141949721836097,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                3152898413090790038,
+                16108523113696338432,
+                11492645026300260534,
+                1417477149741880787,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10578217394647568846,
+                6637113826221079930,
+                1364449097464563400,
+                2379869735503406314,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                6332539588517624153,
+                17422837239624809585,
+                12296960536238467913,
+                2434905421004621494,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10311634121439582299,
+                2959376558854333994,
+                6697398963915560134,
+                417944321386245900,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                16872849857899172004,
+                1640712307042701286,
+                16457516735210998920,
+                1084862449077757478,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                10329879351081882815,
+                5178010365334480003,
+                7014208314719145622,
+                385149140585498380,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                13199866221884806229,
+                10541991787372042848,
+                14909749656931548440,
+                708152185224876794,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1717216310632203061,
+                17455832130858697862,
+                5278085098799702411,
+                227655898188482835,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                17164141620747686731,
+                16689913387728553544,
+                2568326884589391367,
+                3166155980659486882,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1233442753680249567,
+                15490006495937952898,
+                7249042245074469654,
+                2138985910652398451,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                4115849303762846724,
+                2230284817967990783,
+                5095423606777193313,
+                1685862792723606183,
+            ])),
+        ];
+        let mat_internal_diag_m_1 = vec![
+            F::from(ark_ff::BigInteger256::new([
+                13071735289386612455,
+                937867514930142591,
+                338297992309721356,
+                1214967615784395659,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                12135856085615145995,
+                11087747206803725188,
+                92802976007797685,
+                875972510381039422,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                8072276821399088149,
+                12835106910674049377,
+                12882375598172350360,
+                23726925003953432,
+            ])),
+            F::from(ark_ff::BigInteger256::new([
+                1422103134736368267,
+                5972060781611222310,
+                3327741120806881763,
+                2462344296021899375,
+            ])),
+        ];
+        Poseidon2Parameters::new(
+            ark,
+            mat_internal_diag_m_1,
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            t.into(),
+            Sbox::Power(ALPHA),
+        )
+    } else {
+        Err(PoseidonError::InvalidWidth {
+            width: t as usize,
+            max_limit: 4usize,
+        })
+    }
+}
@@ -0,0 +1,27 @@
+//! Base fields of the [Pasta curves](https://electriccoin.co/blog/the-pasta-curves-for-halo-2-and-beyond/)
+//! Pallas and Vesta, used by the [`pallas_x5`](crate::parameters::pallas_x5)
+//! and [`vesta_x5`](crate::parameters::vesta_x5) parameters.
+//!
+//! The naming follows [pasta_curves](https://github.com/zcash/pasta_curves)
+//! and halo2: [`Fp`](crate::pasta::Fp) is the base field of Pallas (and the
+//! scalar field of Vesta), [`Fq`](crate::pasta::Fq) is the base field of Vesta
+//! (and the scalar field of Pallas). In arkworks, they correspond to
+//! `ark_pallas::Fq` and `ark_pallas::Fr` respectively.
+// The `MontConfig` derive emits code gated on the `asm` feature of ark-ff.
+#![allow(unexpected_cfgs)]
+
+use ark_ff::fields::{Fp256, MontBackend, MontConfig};
+
+#[derive(MontConfig)]
+#[modulus = "28948022309329048855892746252171976963363056481941560715954676764349967630337"]
+#[generator = "5"]
+pub struct PallasBaseConfig;
+/// Base field of the Pallas curve.
+pub type Fp = Fp256<MontBackend<PallasBaseConfig, 4>>;
+
+#[derive(MontConfig)]
+#[modulus = "28948022309329048855892746252171976963363056481941647379679742748393362948097"]
+#[generator = "5"]
+pub struct VestaBaseConfig;
+/// Base field of the Vesta curve.
+pub type Fq = Fp256<MontBackend<VestaBaseConfig, 4>>;
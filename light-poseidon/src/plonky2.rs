@@ -0,0 +1,114 @@
+//! Poseidon hash compatible with [Plonky2](https://github.com/0xPolygonZero/plonky2).
+//!
+//! [`Plonky2Poseidon`](crate::plonky2::Plonky2Poseidon) computes the same
+//! output as `PoseidonHash::hash_no_pad` from Plonky2, which uses a sponge in
+//! the *overwrite mode*:
+//!
+//! * the rate part comes first and the last 4 elements of the state are the
+//!   capacity, initialized with zeros,
+//! * inputs overwrite the rate part in chunks and the state is permuted after
+//!   each chunk, without any padding,
+//! * outputs are read from the rate part, the hash consists of the first 4
+//!   elements of the state.
+//!
+//! With the [`goldilocks_x7`](crate::parameters::goldilocks_x7) parameters:
+//!
+//! ```rust
+//! use light_poseidon::{goldilocks::Goldilocks, plonky2::Plonky2Poseidon};
+//!
+//! let poseidon = Plonky2Poseidon::<Goldilocks>::new_goldilocks().unwrap();
+//!
+//! let hash = poseidon.hash_no_pad(&[Goldilocks::from(1u64), Goldilocks::from(2u64)]);
+//!
+//! // Do something with `hash`.
+//! ```
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+
+use crate::{
+    goldilocks::Goldilocks, parameters, with_stack_state, Poseidon, PoseidonError,
+    PoseidonParameters,
+};
+
+/// Number of capacity elements of the state.
+pub const SPONGE_CAPACITY: usize = 4;
+/// Number of field elements of the hash.
+pub const NUM_HASH_OUT_ELTS: usize = 4;
+
+/// A Poseidon sponge in the overwrite mode, compatible with Plonky2.
+pub struct Plonky2Poseidon<F: PrimeField> {
+    poseidon: Poseidon<F>,
+}
+
+impl<F: PrimeField> Plonky2Poseidon<F> {
+    /// Returns a new hasher based on the given parameters. The last
+    /// [`SPONGE_CAPACITY`](crate::plonky2::SPONGE_CAPACITY) elements of the
+    /// state are used as capacity, the rest of them as rate, so the width has
+    /// to be greater than the capacity.
+    pub fn new(params: PoseidonParameters<F>) -> Result<Self, PoseidonError> {
+        let width = params.width;
+        if width <= SPONGE_CAPACITY.max(NUM_HASH_OUT_ELTS) {
+            return Err(PoseidonError::InvalidCapacity {
+                capacity: SPONGE_CAPACITY,
+                max_limit: width.saturating_sub(1),
+                width,
+            });
+        }
+
+        Ok(Self {
+            poseidon: Poseidon::new(params),
+        })
+    }
+
+    /// Number of inputs absorbed per permutation.
+    pub fn rate(&self) -> usize {
+        self.poseidon.params.width - SPONGE_CAPACITY
+    }
+
+    /// Hashes the given inputs without padding, like `PoseidonHash::hash_no_pad`
+    /// in Plonky2.
+    ///
+    /// Note that without padding, inputs of different lengths (e.g. `[a]` and
+    /// `[a, 0]`) can have the same hash. Plonky2 relies on the length being
+    /// fixed by the circuit.
+    pub fn hash_no_pad(&self, inputs: &[F]) -> [F; NUM_HASH_OUT_ELTS] {
+        let outputs = self.hash_n_to_m_no_pad(inputs, NUM_HASH_OUT_ELTS);
+        let mut hash = [F::zero(); NUM_HASH_OUT_ELTS];
+        hash.copy_from_slice(&outputs);
+        hash
+    }
+
+    /// Hashes the given inputs without padding and squeezes `num_outputs`
+    /// elements, like `hash_n_to_m_no_pad` in Plonky2.
+    pub fn hash_n_to_m_no_pad(&self, inputs: &[F], num_outputs: usize) -> Vec<F> {
+        let rate = self.rate();
+        with_stack_state(self.poseidon.params.width, |state| {
+            for chunk in inputs.chunks(rate) {
+                state[..chunk.len()].copy_from_slice(chunk);
+                self.poseidon.permute_unchecked(state);
+            }
+
+            let mut outputs = Vec::with_capacity(num_outputs);
+            loop {
+                let remaining = num_outputs - outputs.len();
+                outputs.extend_from_slice(&state[..rate.min(remaining)]);
+                if outputs.len() == num_outputs {
+                    break;
+                }
+                self.poseidon.permute_unchecked(state);
+            }
+            outputs
+        })
+    }
+}
+
+impl<F: PrimeField> Plonky2Poseidon<F> {
+    /// Returns a new hasher over the Goldilocks field with the Plonky2
+    /// parameters, equivalent to `PoseidonHash` of Plonky2.
+    pub fn new_goldilocks() -> Result<Plonky2Poseidon<Goldilocks>, PoseidonError> {
+        let params = parameters::goldilocks_x7::get_poseidon_parameters::<Goldilocks>(
+            parameters::goldilocks_x7::WIDTH as u8,
+        )?;
+        Plonky2Poseidon::<Goldilocks>::new(params)
+    }
+}
@@ -0,0 +1,339 @@
+//! [Poseidon2](https://eprint.iacr.org/2023/323) hash implementation.
+//!
+//! Poseidon2 replaces the dense MDS matrix of Poseidon with two cheap linear
+//! layers:
+//!
+//! * an *external* matrix used in full rounds (and before the first round),
+//!   fixed for the given width and computable with additions only,
+//! * an *internal* matrix used in partial rounds, `1 + diag(d)`, which costs
+//!   *width* multiplications.
+//!
+//! Partial rounds also add only one round constant. The implementation is
+//! compatible with the [reference implementation](https://github.com/HorizenLabs/poseidon2)
+//! and Barretenberg.
+use alloc::{vec, vec::Vec};
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, Zero};
+
+use crate::{
+    sbox::{self, PowerMap, Sbox},
+    validate_sbox, with_stack_state, PoseidonError, PoseidonHasher,
+};
+
+/// Widths supported by the external linear layer of Poseidon2.
+pub const SUPPORTED_WIDTHS: [usize; 8] = [2, 3, 4, 8, 12, 16, 20, 24];
+
+/// Maximum width of the Poseidon2 parameters for BN254 provided by the
+/// library.
+pub const MAX_BN254_X5_LEN: usize = 4;
+
+/// Parameters for the Poseidon2 hash algorithm.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Poseidon2Parameters<F: PrimeField> {
+    /// Round constants. `width` constants for each full round and one
+    /// constant for each partial round, in the order of rounds.
+    pub ark: Vec<F>,
+    /// Diagonal of the internal matrix minus one. The internal matrix is a
+    /// matrix of ones with these elements added to its diagonal.
+    pub mat_internal_diag_m_1: Vec<F>,
+    /// Number of full rounds (where S-box is applied to all elements of the
+    /// state).
+    pub full_rounds: usize,
+    /// Number of partial rounds (where S-box is applied only to the first
+    /// element of the state).
+    pub partial_rounds: usize,
+    /// Number of prime fields in the state.
+    pub width: usize,
+    /// S-box used to map elements of the state.
+    pub sbox: Sbox,
+}
+
+impl<F: PrimeField> Poseidon2Parameters<F> {
+    /// Returns new Poseidon2 parameters. The width has to be one of
+    /// [`SUPPORTED_WIDTHS`](crate::poseidon2::SUPPORTED_WIDTHS), `ark` has to
+    /// contain `full_rounds * width + partial_rounds` constants,
+    /// `mat_internal_diag_m_1` `width` elements and the S-box has to be a
+    /// permutation of the field.
+    pub fn new(
+        ark: Vec<F>,
+        mat_internal_diag_m_1: Vec<F>,
+        full_rounds: usize,
+        partial_rounds: usize,
+        width: usize,
+        sbox: Sbox,
+    ) -> Result<Self, PoseidonError> {
+        if !SUPPORTED_WIDTHS.contains(&width) {
+            return Err(PoseidonError::InvalidWidthPoseidon2 { width });
+        }
+        let expected = full_rounds * width + partial_rounds;
+        if ark.len() != expected {
+            return Err(PoseidonError::InvalidArkLength {
+                len: ark.len(),
+                expected,
+            });
+        }
+        if mat_internal_diag_m_1.len() != width {
+            return Err(PoseidonError::InvalidMatInternalDiagLength {
+                len: mat_internal_diag_m_1.len(),
+                width,
+            });
+        }
+        validate_sbox::<F>(sbox)?;
+
+        Ok(Self {
+            ark,
+            mat_internal_diag_m_1,
+            full_rounds,
+            partial_rounds,
+            width,
+            sbox,
+        })
+    }
+
+    /// Returns the external matrix used in full rounds as a dense matrix.
+    pub fn mat_external(&self) -> Vec<Vec<F>> {
+        let identity = (0..self.width).map(|i| {
+            let mut column = vec![F::zero(); self.width];
+            column[i] = F::one();
+            column
+        });
+        let columns: Vec<Vec<F>> = identity
+            .map(|mut column| {
+                apply_mat_external(&mut column);
+                column
+            })
+            .collect();
+        (0..self.width)
+            .map(|i| columns.iter().map(|column| column[i]).collect())
+            .collect()
+    }
+
+    /// Returns the internal matrix used in partial rounds as a dense matrix.
+    pub fn mat_internal(&self) -> Vec<Vec<F>> {
+        (0..self.width)
+            .map(|i| {
+                (0..self.width)
+                    .map(|j| {
+                        if i == j {
+                            F::one() + self.mat_internal_diag_m_1[i]
+                        } else {
+                            F::one()
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Applies the Poseidon2 permutation defined by `params` to the given state
+/// and leaves the whole output state in `state`. The length of `state` has to
+/// be equal to the width of the parameters.
+pub fn permute<F: PrimeField>(
+    params: &Poseidon2Parameters<F>,
+    state: &mut [F],
+) -> Result<(), PoseidonError> {
+    if state.len() != params.width {
+        return Err(PoseidonError::InvalidStateLength {
+            len: state.len(),
+            width: params.width,
+        });
+    }
+
+    apply_rounds(params, state);
+    Ok(())
+}
+
+/// Multiplies 4-element chunks of the state by the matrix
+///
+/// ```norust
+/// [5, 7, 1, 3]
+/// [4, 6, 1, 1]
+/// [1, 3, 5, 7]
+/// [1, 1, 4, 6]
+/// ```
+#[inline(always)]
+fn apply_m4<F: PrimeField>(state: &mut [F]) {
+    state.chunks_exact_mut(4).for_each(|chunk| {
+        let t0 = chunk[0] + chunk[1];
+        let t1 = chunk[2] + chunk[3];
+        let t2 = chunk[1].double() + t1;
+        let t3 = chunk[3].double() + t0;
+        let t4 = t1.double().double() + t3;
+        let t5 = t0.double().double() + t2;
+        let t6 = t3 + t5;
+        let t7 = t2 + t4;
+        chunk[0] = t6;
+        chunk[1] = t5;
+        chunk[2] = t7;
+        chunk[3] = t4;
+    });
+}
+
+#[inline(always)]
+fn apply_mat_external<F: PrimeField>(state: &mut [F]) {
+    match state.len() {
+        // circ(2, 1) and circ(2, 1, 1)
+        2 | 3 => {
+            let sum: F = state.iter().sum();
+            state.iter_mut().for_each(|a| *a += sum);
+        }
+        4 => apply_m4(state),
+        _ => {
+            apply_m4(state);
+
+            let mut sums = [F::zero(); 4];
+            state.chunks_exact(4).for_each(|chunk| {
+                sums.iter_mut().zip(chunk).for_each(|(s, a)| *s += a);
+            });
+            state
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, a)| *a += sums[i % 4]);
+        }
+    }
+}
+
+#[inline(always)]
+fn apply_mat_internal<F: PrimeField>(params: &Poseidon2Parameters<F>, state: &mut [F]) {
+    let sum: F = state.iter().sum();
+    state
+        .iter_mut()
+        .zip(params.mat_internal_diag_m_1.iter())
+        .for_each(|(a, d)| {
+            *a *= d;
+            *a += sum;
+        });
+}
+
+#[inline(always)]
+fn apply_full_round<F: PrimeField, P: PowerMap>(
+    params: &Poseidon2Parameters<F>,
+    state: &mut [F],
+    ark: &[F],
+) {
+    state.iter_mut().zip(ark).for_each(|(a, c)| {
+        *a += c;
+        *a = P::apply(*a, params.sbox);
+    });
+    apply_mat_external(state);
+}
+
+fn apply_rounds<F: PrimeField>(params: &Poseidon2Parameters<F>, state: &mut [F]) {
+    sbox::select_rounds!(apply_rounds_with, params.sbox)(params, state)
+}
+
+/// [`apply_rounds`] with the S-box map `P`.
+fn apply_rounds_with<F: PrimeField, P: PowerMap>(params: &Poseidon2Parameters<F>, state: &mut [F]) {
+    let width = params.width;
+    let half_rounds = params.full_rounds / 2;
+    let partial_ark_start = half_rounds * width;
+    let partial_ark_end = partial_ark_start + params.partial_rounds;
+
+    // Linear layer at the beginning.
+    apply_mat_external(state);
+
+    for ark in params.ark[..partial_ark_start].chunks_exact(width) {
+        apply_full_round::<F, P>(params, state, ark);
+    }
+
+    for c in params.ark[partial_ark_start..partial_ark_end].iter() {
+        state[0] += c;
+        state[0] = P::apply(state[0], params.sbox);
+        apply_mat_internal(params, state);
+    }
+
+    for ark in params.ark[partial_ark_end..].chunks_exact(width) {
+        apply_full_round::<F, P>(params, state, ark);
+    }
+}
+
+/// A Poseidon2 hasher.
+///
+/// It uses the same state layout as [`Poseidon`](crate::Poseidon) - the domain
+/// tag followed by `width - 1` inputs - and returns the first element of the
+/// permuted state, so it can be used as a drop-in alternative.
+pub struct Poseidon2<F: PrimeField> {
+    params: Poseidon2Parameters<F>,
+    domain_tag: F,
+    rounds: fn(&Poseidon2Parameters<F>, &mut [F]),
+}
+
+impl<F: PrimeField> Poseidon2<F> {
+    /// Returns a new Poseidon2 hasher based on the given parameters.
+    pub fn new(params: Poseidon2Parameters<F>) -> Self {
+        Self::with_domain_tag(params, F::zero())
+    }
+
+    /// Returns a new Poseidon2 hasher based on the given parameters and a
+    /// domain tag.
+    pub fn with_domain_tag(params: Poseidon2Parameters<F>, domain_tag: F) -> Self {
+        Self {
+            domain_tag,
+            rounds: sbox::select_rounds!(apply_rounds_with, params.sbox),
+            params,
+        }
+    }
+
+    /// Applies the Poseidon2 permutation to the given state, using the
+    /// parameters of this hasher.
+    pub fn permute(&self, state: &mut [F]) -> Result<(), PoseidonError> {
+        if state.len() != self.params.width {
+            return Err(PoseidonError::InvalidStateLength {
+                len: state.len(),
+                width: self.params.width,
+            });
+        }
+
+        (self.rounds)(&self.params, state);
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> PoseidonHasher<F> for Poseidon2<F> {
+    fn hash(&self, inputs: &[F]) -> Result<F, PoseidonError> {
+        if inputs.len() != self.params.width - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: inputs.len(),
+                max_limit: self.params.width - 1,
+                width: self.params.width,
+            });
+        }
+
+        Ok(with_stack_state(self.params.width, |state| {
+            state[0] = self.domain_tag;
+            state[1..].copy_from_slice(inputs);
+            (self.rounds)(&self.params, state);
+            state[0]
+        }))
+    }
+}
+
+impl<F: PrimeField> Poseidon2<F> {
+    /// Returns a new Poseidon2 hasher based on the BN254 parameters provided by
+    /// the library, for the given number of inputs (`1 ≤ nr_inputs ≤ 3`).
+    pub fn new_bn254(nr_inputs: usize) -> Result<Poseidon2<Fr>, PoseidonError> {
+        Self::with_domain_tag_bn254(nr_inputs, Fr::zero())
+    }
+
+    /// Returns a new Poseidon2 hasher based on the BN254 parameters provided by
+    /// the library, for the given number of inputs (`1 ≤ nr_inputs ≤ 3`) and
+    /// a domain tag.
+    pub fn with_domain_tag_bn254(
+        nr_inputs: usize,
+        domain_tag: Fr,
+    ) -> Result<Poseidon2<Fr>, PoseidonError> {
+        let width = nr_inputs + 1;
+        if width > MAX_BN254_X5_LEN {
+            return Err(PoseidonError::InvalidWidth {
+                width,
+                max_limit: MAX_BN254_X5_LEN,
+            });
+        }
+
+        let params = crate::parameters::poseidon2_bn254_x5::get_poseidon2_parameters::<Fr>(
+            (width).try_into().map_err(|_| PoseidonError::U64Tou8)?,
+        )?;
+        Ok(Poseidon2::<Fr>::with_domain_tag(params, domain_tag))
+    }
+}
@@ -0,0 +1,188 @@
+//! R1CS gadget of the Poseidon hash function, for circuits built with
+//! [ark-r1cs-std](https://github.com/arkworks-rs/r1cs-std).
+//!
+//! The gadget consumes the same [`PoseidonParameters`](crate::PoseidonParameters)
+//! as the native hasher, so the constants used in circuits cannot drift from
+//! the ones used outside of them.
+//!
+//! Linear layers (round constants and the MDS matrix) are applied to linear
+//! combinations and do not produce any constraints, so the only constraints
+//! come from the S-boxes:
+//!
+//! * *x^alpha* - one per multiplication in the square-and-multiply chain,
+//!   e.g. 3 for *x^5*,
+//! * *x^-1* - 3 (the inverse of zero is zero).
+//!
+//! S-boxes applied to constants (e.g. the domain tag in the first round) are
+//! computed natively, like circom does, so the number of constraints of
+//! [`PoseidonGadget::new_circom`](crate::r1cs::PoseidonGadget::new_circom)
+//! matches circomlib's `Poseidon` template.
+//!
+//! ```rust
+//! use ark_bn254::Fr;
+//! use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+//! use ark_relations::r1cs::ConstraintSystem;
+//! use light_poseidon::{r1cs::PoseidonGadget, Poseidon, PoseidonHasher};
+//!
+//! let cs = ConstraintSystem::<Fr>::new_ref();
+//! let inputs = [Fr::from(1u64), Fr::from(2u64)];
+//! let input_vars = inputs
+//!     .iter()
+//!     .map(|input| FpVar::new_witness(cs.clone(), || Ok(*input)))
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//!
+//! let gadget = PoseidonGadget::<Fr>::new_circom(2).unwrap();
+//! let hash = gadget.hash(&input_vars).unwrap();
+//!
+//! let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+//! assert_eq!(hash.value().unwrap(), poseidon.hash(&inputs).unwrap());
+//! assert!(cs.is_satisfied().unwrap());
+//! assert_eq!(cs.num_constraints(), 240);
+//! ```
+use alloc::vec::Vec;
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, Zero};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    fields::{fp::FpVar, FieldVar},
+    R1CSVar,
+};
+
+use crate::{parameters::bn254_x5, sbox, PoseidonError, PoseidonParameters, Sbox, MAX_X5_LEN};
+
+/// Poseidon gadget over [`FpVar`](ark_r1cs_std::fields::fp::FpVar).
+pub struct PoseidonGadget<F: PrimeField> {
+    params: PoseidonParameters<F>,
+    domain_tag: F,
+}
+
+impl<F: PrimeField> PoseidonGadget<F> {
+    pub fn new(params: PoseidonParameters<F>) -> Self {
+        Self::with_domain_tag(params, F::zero())
+    }
+
+    /// Returns a gadget which puts the given domain tag in the first element
+    /// of the state, like [`Poseidon::with_domain_tag_circom`](crate::Poseidon::with_domain_tag_circom).
+    pub fn with_domain_tag(params: PoseidonParameters<F>, domain_tag: F) -> Self {
+        Self { params, domain_tag }
+    }
+
+    pub fn params(&self) -> &PoseidonParameters<F> {
+        &self.params
+    }
+
+    /// Enforces the permutation of the given state, which has to have the
+    /// length of `params.width`.
+    pub fn permute(&self, state: &mut [FpVar<F>]) -> Result<(), PoseidonError> {
+        if state.len() != self.params.width {
+            return Err(PoseidonError::InvalidStateLength {
+                len: state.len(),
+                width: self.params.width,
+            });
+        }
+
+        let all_rounds = self.params.full_rounds + self.params.partial_rounds;
+        let half_rounds = self.params.full_rounds / 2;
+        for round in 0..all_rounds {
+            self.apply_ark(state, round);
+            if round < half_rounds || round >= half_rounds + self.params.partial_rounds {
+                for a in state.iter_mut() {
+                    *a = self.apply_sbox(a)?;
+                }
+            } else {
+                state[0] = self.apply_sbox(&state[0])?;
+            }
+            self.apply_mds(state);
+        }
+
+        Ok(())
+    }
+
+    /// Enforces the hash of `width - 1` inputs, returning the first element of
+    /// the permuted state, like [`PoseidonHasher::hash`](crate::PoseidonHasher::hash).
+    pub fn hash(&self, inputs: &[FpVar<F>]) -> Result<FpVar<F>, PoseidonError> {
+        if inputs.len() != self.params.width - 1 {
+            return Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: inputs.len(),
+                max_limit: self.params.width - 1,
+                width: self.params.width,
+            });
+        }
+
+        let mut state = Vec::with_capacity(self.params.width);
+        state.push(FpVar::Constant(self.domain_tag));
+        state.extend_from_slice(inputs);
+        self.permute(&mut state)?;
+
+        Ok(state.swap_remove(0))
+    }
+
+    fn apply_ark(&self, state: &mut [FpVar<F>], round: usize) {
+        let width = self.params.width;
+        for (a, c) in state
+            .iter_mut()
+            .zip(&self.params.ark[round * width..(round + 1) * width])
+        {
+            *a += *c;
+        }
+    }
+
+    fn apply_sbox(&self, x: &FpVar<F>) -> Result<FpVar<F>, PoseidonError> {
+        match self.params.sbox {
+            Sbox::Power(alpha) => Ok(x.pow_by_constant([alpha])?),
+            Sbox::Inverse => {
+                if let FpVar::Constant(x) = x {
+                    return Ok(FpVar::Constant(sbox::inverse(*x)));
+                }
+                // `y` is the inverse of `x`, or zero if `x` is zero:
+                // `t = x * y`, `t * x = x`, `t * y = y`.
+                let y = FpVar::new_witness(x.cs(), || x.value().map(sbox::inverse))?;
+                let t = x * &y;
+                t.mul_equals(x, x)?;
+                t.mul_equals(&y, &y)?;
+                Ok(y)
+            }
+        }
+    }
+
+    fn apply_mds(&self, state: &mut [FpVar<F>]) {
+        let result: Vec<FpVar<F>> = self
+            .params
+            .mds
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .fold(FpVar::zero(), |acc, (m, a)| acc + a * *m)
+            })
+            .collect();
+        state.clone_from_slice(&result);
+    }
+}
+
+impl PoseidonGadget<Fr> {
+    /// Returns a gadget for the given number of inputs, using the
+    /// circom-compatible BN254 parameters.
+    pub fn new_circom(nr_inputs: usize) -> Result<PoseidonGadget<Fr>, PoseidonError> {
+        Self::with_domain_tag_circom(nr_inputs, Fr::zero())
+    }
+
+    pub fn with_domain_tag_circom(
+        nr_inputs: usize,
+        domain_tag: Fr,
+    ) -> Result<PoseidonGadget<Fr>, PoseidonError> {
+        let width = nr_inputs + 1;
+        if width > MAX_X5_LEN {
+            return Err(PoseidonError::InvalidWidthCircom {
+                width,
+                max_limit: MAX_X5_LEN,
+            });
+        }
+
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(
+            width.try_into().map_err(|_| PoseidonError::U64Tou8)?,
+        )?;
+        Ok(PoseidonGadget::with_domain_tag(params, domain_tag))
+    }
+}
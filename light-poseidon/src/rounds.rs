@@ -0,0 +1,278 @@
+//! Calculation of secure round numbers for Poseidon.
+//!
+//! This module is a Rust port of the
+//! [calc_round_numbers.py](https://extgit.iaik.tugraz.at/krypto/hadeshash/-/blob/master/code/calc_round_numbers.py)
+//! script from the Poseidon paper. For the given prime field, S-box, width
+//! and security level, it finds the numbers of full and partial rounds which
+//! satisfy all the bounds:
+//!
+//! * the statistical bound (differential and linear attacks),
+//! * the interpolation bound,
+//! * three Gröbner basis bounds from the Poseidon paper (two for *x^-1*),
+//! * the Gröbner basis bound from [eprint 2023/537](https://eprint.iacr.org/2023/537)
+//!   (only for *x^alpha*),
+//!
+//! and which have the lowest number of S-boxes. The security margin of the
+//! paper (two more full rounds and 7.5% more partial rounds) is added on top.
+//!
+//! ```rust
+//! use light_poseidon::{rounds, Sbox};
+//! use ark_bn254::Fr;
+//!
+//! assert_eq!(
+//!     rounds::calc_round_numbers_for_field::<Fr>(3, Sbox::Power(5), 128),
+//!     Some((8, 56))
+//! );
+//! ```
+//!
+//! The round numbers of the BN254 parameters provided by the library (and by
+//! circomlib) have the partial rounds rounded up to a multiple of the width,
+//! like in the reference implementation of the paper.
+//! [`calc_round_numbers_for_field_circom`](crate::rounds::calc_round_numbers_for_field_circom)
+//! reproduces them:
+//!
+//! ```rust
+//! use light_poseidon::{parameters::bn254_x5, rounds, Sbox};
+//! use ark_bn254::Fr;
+//!
+//! assert_eq!(
+//!     rounds::calc_round_numbers_for_field_circom::<Fr>(3, Sbox::Power(5), 128),
+//!     Some((bn254_x5::FULL_ROUNDS, bn254_x5::PARTIAL_ROUNDS[1]))
+//! );
+//! ```
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::Sbox;
+
+/// Maximum number of partial rounds considered by the search.
+const MAX_PARTIAL_ROUNDS: usize = 500;
+/// Maximum number of full rounds considered by the search.
+const MAX_FULL_ROUNDS: usize = 100;
+
+/// Returns the binary logarithm of the modulus of the prime field `F`.
+pub fn log2_modulus<F: PrimeField>() -> f64 {
+    let bits = F::MODULUS.to_bits_be();
+    let bits = &bits[bits.iter().position(|b| *b).unwrap_or(bits.len())..];
+
+    // The 53 most significant bits are enough to fill the mantissa.
+    let len = bits.len().min(53);
+    let top = bits[..len]
+        .iter()
+        .fold(0u64, |acc, b| (acc << 1) | u64::from(*b));
+    (top as f64).log2() + (bits.len() - len) as f64
+}
+
+/// Returns `ceil(log_base(x))` computed on integers, to avoid rounding errors
+/// when `x` is a power of `base`.
+fn ceil_log(x: usize, base: u64) -> f64 {
+    let mut power = 1u128;
+    let mut exp = 0;
+    while power < x as u128 {
+        power *= base as u128;
+        exp += 1;
+    }
+    exp as f64
+}
+
+/// Returns `log2(binomial(n, k))`.
+fn log2_binomial(n: f64, k: f64) -> f64 {
+    let k = k.min(n - k);
+    (1..=k as u64)
+        .map(|i| ((n - k + i as f64) / i as f64).log2())
+        .sum()
+}
+
+/// Checks whether the given numbers of rounds satisfy all the security
+/// bounds, without the security margin.
+///
+/// * `log2_p` - binary logarithm of the modulus of the prime field
+/// * `width` - number of prime fields in the state
+/// * `sbox` - S-box, the exponent of [`Sbox::Power`](crate::Sbox::Power) has
+///   to be at least 3
+/// * `security_level` - security level in bits
+pub fn is_secure(
+    log2_p: f64,
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    sbox: Sbox,
+    security_level: u32,
+) -> bool {
+    match sbox {
+        Sbox::Power(alpha) => is_secure_power(
+            log2_p,
+            width,
+            full_rounds,
+            partial_rounds,
+            alpha,
+            security_level,
+        ),
+        Sbox::Inverse => {
+            is_secure_inverse(log2_p, width, full_rounds, partial_rounds, security_level)
+        }
+    }
+}
+
+fn is_secure_power(
+    log2_p: f64,
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+    security_level: u32,
+) -> bool {
+    let t = width as f64;
+    let r_f = full_rounds as f64;
+    let r_p = partial_rounds as f64;
+    let alpha_f = alpha as f64;
+    let m = security_level as f64;
+    let field_size = log2_p.ceil();
+    // log_alpha(2)
+    let log_alpha_2 = 1.0 / alpha_f.log2();
+
+    // Statistical
+    let r_f_1 = if m <= (log2_p - (alpha_f - 1.0) / 2.0).floor() * (t + 1.0) {
+        6.0
+    } else {
+        10.0
+    };
+    // Interpolation
+    let r_f_2 = 1.0 + (log_alpha_2 * m.min(field_size)).ceil() + ceil_log(width, alpha) - r_p;
+    // Gröbner 1
+    let r_f_3 = log_alpha_2 * m.min(log2_p) - r_p;
+    // Gröbner 2
+    let r_f_4 = t - 1.0 + log_alpha_2 * (m / (t + 1.0)).min(log2_p / 2.0) - r_p;
+    // Gröbner 3
+    let r_f_5 = (t - 2.0 + m / (2.0 * alpha_f.log2()) - r_p) / (t - 1.0);
+
+    let r_f_max = [r_f_1, r_f_2, r_f_3, r_f_4, r_f_5]
+        .into_iter()
+        .map(f64::ceil)
+        .fold(f64::MIN, f64::max);
+    if r_f < r_f_max {
+        return false;
+    }
+
+    // Gröbner 4, from eprint 2023/537. The paper uses the 2.3727 exponent, the
+    // script is more conservative.
+    let r_temp = (t / 3.0).floor();
+    let over = (r_f - 1.0) * t + r_p + r_temp + r_temp * (r_f / 2.0) + r_p + alpha_f;
+    let under = r_temp * (r_f / 2.0) + r_p + alpha_f;
+    let cost_gb4 = (2.0 * log2_binomial(over, under)).ceil();
+    cost_gb4 >= m
+}
+
+fn is_secure_inverse(
+    log2_p: f64,
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    security_level: u32,
+) -> bool {
+    let t = width as f64;
+    let r_f = full_rounds as f64;
+    let r_p = partial_rounds as f64;
+    let m = security_level as f64;
+    let field_size = log2_p.ceil();
+    // Full rounds reduce the number of partial rounds by log2(t) each.
+    let full_rounds_gain = (r_f * t.log2()).floor();
+
+    // Statistical
+    let r_f_1 = if m <= (log2_p - 2.0).floor() * (t + 1.0) {
+        6.0
+    } else {
+        10.0
+    };
+    if r_f < r_f_1 {
+        return false;
+    }
+
+    // Interpolation and Gröbner 1
+    let r_p_1 = 1.0 + (0.5 * m.min(field_size)).ceil() + ceil_log(width, 2) - full_rounds_gain;
+    // Gröbner 2
+    let r_p_2 = t - 1.0 + ceil_log(width, 2) + (m / (t + 1.0)).ceil().min((0.5 * log2_p).ceil())
+        - full_rounds_gain;
+
+    r_p >= r_p_1.max(r_p_2)
+}
+
+/// Returns the numbers of full and partial rounds with the lowest number of
+/// S-boxes which satisfy all the security bounds (see
+/// [`is_secure`](crate::rounds::is_secure)). If `security_margin` is `true`,
+/// two full rounds and 7.5% of partial rounds are added.
+///
+/// Returns `None` if the exponent of [`Sbox::Power`](crate::Sbox::Power) is
+/// smaller than 3 or no secure round numbers were found.
+pub fn calc_round_numbers(
+    log2_p: f64,
+    width: usize,
+    sbox: Sbox,
+    security_level: u32,
+    security_margin: bool,
+) -> Option<(usize, usize)> {
+    if matches!(sbox, Sbox::Power(alpha) if alpha < 3) || width == 0 {
+        return None;
+    }
+
+    let mut best: Option<(usize, usize)> = None;
+    // Cost is the number of S-boxes, ties are broken by fewer full rounds.
+    let cost = |(full_rounds, partial_rounds): (usize, usize)| {
+        (width * full_rounds + partial_rounds, full_rounds)
+    };
+
+    for partial_rounds in 1..MAX_PARTIAL_ROUNDS {
+        for full_rounds in (4..MAX_FULL_ROUNDS).step_by(2) {
+            if !is_secure(
+                log2_p,
+                width,
+                full_rounds,
+                partial_rounds,
+                sbox,
+                security_level,
+            ) {
+                continue;
+            }
+
+            let candidate = if security_margin {
+                (
+                    full_rounds + 2,
+                    (partial_rounds as f64 * 1.075).ceil() as usize,
+                )
+            } else {
+                (full_rounds, partial_rounds)
+            };
+            if best.is_none_or(|best| cost(candidate) < cost(best)) {
+                best = Some(candidate);
+            }
+            // More full rounds only increase the cost.
+            break;
+        }
+    }
+
+    best
+}
+
+/// Returns the numbers of full and partial rounds, including the security
+/// margin, for the prime field `F`. See
+/// [`calc_round_numbers`](crate::rounds::calc_round_numbers).
+pub fn calc_round_numbers_for_field<F: PrimeField>(
+    width: usize,
+    sbox: Sbox,
+    security_level: u32,
+) -> Option<(usize, usize)> {
+    calc_round_numbers(log2_modulus::<F>(), width, sbox, security_level, true)
+}
+
+/// Returns the numbers of full and partial rounds like
+/// [`calc_round_numbers_for_field`](crate::rounds::calc_round_numbers_for_field),
+/// with the partial rounds rounded up to a multiple of the width. These are
+/// the round numbers of the BN254 parameters provided by the library and by
+/// circomlib.
+pub fn calc_round_numbers_for_field_circom<F: PrimeField>(
+    width: usize,
+    sbox: Sbox,
+    security_level: u32,
+) -> Option<(usize, usize)> {
+    calc_round_numbers_for_field::<F>(width, sbox, security_level)
+        .map(|(full_rounds, partial_rounds)| (full_rounds, partial_rounds.div_ceil(width) * width))
+}
@@ -0,0 +1,253 @@
+//! SAFE (Sponge API for Field Elements) on top of the Poseidon permutation.
+//!
+//! [SAFE](https://eprint.iacr.org/2023/522) is a domain-separated usage of a
+//! sponge, where the whole sequence of calls - an [`IoPattern`](crate::safe::IoPattern)
+//! of `ABSORB(n)` and `SQUEEZE(n)` operations - is declared up front:
+//!
+//! * the tag derived from the IO pattern and a domain separator is put in the
+//!   first capacity element (the domain tag of [`Poseidon`](crate::Poseidon)),
+//!   so sponges used for different patterns or purposes never share a state,
+//! * every call is checked against the declared pattern and a call which
+//!   deviates from it returns an error,
+//! * [`finish`](crate::safe::SafeSponge::finish) checks whether all declared
+//!   operations were performed.
+//!
+//! The tag is computed like in the SAFE implementation of
+//! [neptune](https://github.com/argumentcomputer/neptune) - consecutive
+//! operations of the same kind are aggregated, each of them is encoded as a
+//! 32-bit word (with the most significant bit set for `ABSORB`) and the words,
+//! followed by the domain separator, are hashed with a polynomial hash modulo
+//! *2^128*, so the tags are compatible with that implementation.
+//!
+//! ```rust
+//! use light_poseidon::safe::{IoPattern, SafeSponge, SpongeOp};
+//! use ark_bn254::Fr;
+//!
+//! let pattern = IoPattern::new(vec![SpongeOp::Absorb(3), SpongeOp::Squeeze(1)]);
+//! let mut sponge = SafeSponge::<Fr>::new_circom(2, pattern, 0).unwrap();
+//!
+//! sponge
+//!     .absorb(&[Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)])
+//!     .unwrap();
+//! let output = sponge.squeeze(1).unwrap();
+//! sponge.finish().unwrap();
+//!
+//! // Do something with `output`.
+//! ```
+use alloc::{format, string::ToString, vec::Vec};
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+
+use crate::{sponge::PoseidonSponge, PoseidonError, PoseidonParameters};
+
+/// Maximum number of elements absorbed or squeezed by a single operation.
+/// The most significant bit of the 32-bit encoding marks absorbing.
+pub const MAX_OP_LEN: u32 = (1 << 31) - 1;
+
+/// Base of the polynomial hash computing the tag - the largest 128-bit prime,
+/// *2^128 - 159*.
+const TAG_HASHER_BASE: u128 = 0u128.wrapping_sub(159);
+
+/// A single operation of an IO pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpongeOp {
+    /// Absorbs the given number of elements.
+    Absorb(u32),
+    /// Squeezes the given number of elements.
+    Squeeze(u32),
+}
+
+impl SpongeOp {
+    /// Number of elements absorbed or squeezed.
+    pub fn len(&self) -> u32 {
+        match self {
+            SpongeOp::Absorb(n) | SpongeOp::Squeeze(n) => *n,
+        }
+    }
+
+    /// Whether the operation absorbs or squeezes no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the 32-bit encoding of the operation used in the tag.
+    fn encode(&self) -> u32 {
+        match self {
+            SpongeOp::Absorb(n) => (1 << 31) | n,
+            SpongeOp::Squeeze(n) => *n,
+        }
+    }
+}
+
+/// Sequence of operations declared before using a [`SafeSponge`](crate::safe::SafeSponge).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IoPattern(pub Vec<SpongeOp>);
+
+impl IoPattern {
+    pub fn new(ops: Vec<SpongeOp>) -> Self {
+        Self(ops)
+    }
+
+    /// Checks whether every operation absorbs or squeezes between 1 and
+    /// [`MAX_OP_LEN`](crate::safe::MAX_OP_LEN) elements and whether that's
+    /// still the case after aggregating consecutive operations of the same
+    /// kind.
+    pub fn validate(&self) -> Result<(), PoseidonError> {
+        self.aggregate().map(|_| ())
+    }
+
+    /// Returns the tag of the pattern and the given domain separator, or an
+    /// error if the pattern is not [valid](crate::safe::IoPattern::validate).
+    ///
+    /// Consecutive operations of the same kind are aggregated first, so e.g.
+    /// `ABSORB(1), ABSORB(1), SQUEEZE(2)` has the same tag as
+    /// `ABSORB(2), SQUEEZE(2)`.
+    pub fn tag(&self, domain_separator: u32) -> Result<u128, PoseidonError> {
+        let mut words: Vec<u32> = self.aggregate()?.iter().map(SpongeOp::encode).collect();
+        words.push(domain_separator);
+
+        let mut power = 1u128;
+        Ok(words.iter().fold(0u128, |tag, word| {
+            power = power.wrapping_mul(TAG_HASHER_BASE);
+            tag.wrapping_add(power.wrapping_mul(u128::from(*word)))
+        }))
+    }
+
+    /// Validates the operations and aggregates consecutive operations of the
+    /// same kind. The aggregated lengths have to fit in
+    /// [`MAX_OP_LEN`](crate::safe::MAX_OP_LEN) as well, a larger one would
+    /// set the most significant bit of the encoding of `SQUEEZE` or overflow.
+    fn aggregate(&self) -> Result<Vec<SpongeOp>, PoseidonError> {
+        let mut aggregated: Vec<SpongeOp> = Vec::with_capacity(self.0.len());
+        for (index, op) in self.0.iter().enumerate() {
+            if op.is_empty() || op.len() > MAX_OP_LEN {
+                return Err(PoseidonError::InvalidIoPattern {
+                    index,
+                    op: format!("{:?}", op),
+                });
+            }
+            match (aggregated.last_mut(), op) {
+                (Some(SpongeOp::Absorb(a)), SpongeOp::Absorb(b))
+                | (Some(SpongeOp::Squeeze(a)), SpongeOp::Squeeze(b)) => {
+                    *a = a.checked_add(*b).filter(|n| *n <= MAX_OP_LEN).ok_or(
+                        PoseidonError::InvalidIoPatternAggregate {
+                            index,
+                            max_limit: MAX_OP_LEN,
+                        },
+                    )?;
+                }
+                _ => aggregated.push(*op),
+            }
+        }
+        Ok(aggregated)
+    }
+}
+
+/// A sponge which follows the declared [`IoPattern`](crate::safe::IoPattern).
+pub struct SafeSponge<F: PrimeField> {
+    sponge: PoseidonSponge<F>,
+    pattern: IoPattern,
+    /// Number of operations performed so far.
+    io_count: usize,
+}
+
+impl<F: PrimeField> SafeSponge<F> {
+    /// Returns a new sponge based on the given parameters and `capacity`,
+    /// which accepts only the calls declared in `pattern`. The first capacity
+    /// element is initialized with the tag of the pattern and the domain
+    /// separator (see [`IoPattern::tag`](crate::safe::IoPattern::tag)).
+    pub fn new(
+        params: PoseidonParameters<F>,
+        capacity: usize,
+        pattern: IoPattern,
+        domain_separator: u32,
+    ) -> Result<Self, PoseidonError> {
+        let tag = F::from(pattern.tag(domain_separator)?);
+
+        Ok(Self {
+            sponge: PoseidonSponge::with_domain_tag(params, capacity, tag)?,
+            pattern,
+            io_count: 0,
+        })
+    }
+
+    /// The declared IO pattern.
+    pub fn pattern(&self) -> &IoPattern {
+        &self.pattern
+    }
+
+    /// Current state of the sponge, capacity elements first.
+    pub fn state(&self) -> &[F] {
+        self.sponge.state()
+    }
+
+    /// Absorbs the given elements. The next declared operation has to be
+    /// `ABSORB(inputs.len())`, otherwise an error is returned and the state
+    /// is not modified.
+    pub fn absorb(&mut self, inputs: &[F]) -> Result<(), PoseidonError> {
+        let len = inputs.len().try_into().unwrap_or(u32::MAX);
+        self.next_op(SpongeOp::Absorb(len))?;
+        self.sponge.absorb(inputs);
+        Ok(())
+    }
+
+    /// Squeezes `n` elements. The next declared operation has to be
+    /// `SQUEEZE(n)`, otherwise an error is returned and the state is not
+    /// modified.
+    pub fn squeeze(&mut self, n: u32) -> Result<Vec<F>, PoseidonError> {
+        self.next_op(SpongeOp::Squeeze(n))?;
+        Ok(self.sponge.squeeze(n as usize))
+    }
+
+    /// Finishes using the sponge, checking whether all declared operations
+    /// were performed.
+    pub fn finish(self) -> Result<(), PoseidonError> {
+        if self.io_count != self.pattern.0.len() {
+            return Err(PoseidonError::IoPatternNotFinished {
+                performed: self.io_count,
+                expected: self.pattern.0.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks whether `op` is the next declared operation and moves on to the
+    /// following one.
+    fn next_op(&mut self, op: SpongeOp) -> Result<(), PoseidonError> {
+        match self.pattern.0.get(self.io_count) {
+            Some(expected) if *expected == op => {
+                self.io_count += 1;
+                Ok(())
+            }
+            Some(expected) => Err(PoseidonError::IoPatternViolation {
+                index: self.io_count,
+                expected: format!("{:?}", expected),
+                op: format!("{:?}", op),
+            }),
+            None => Err(PoseidonError::IoPatternViolation {
+                index: self.io_count,
+                expected: "end of the pattern".to_string(),
+                op: format!("{:?}", op),
+            }),
+        }
+    }
+}
+
+impl SafeSponge<Fr> {
+    /// Returns a new sponge based on the BN254 parameters provided by the
+    /// library, with capacity 1 and the given rate (`1 ≤ rate ≤ 12`), which
+    /// accepts only the calls declared in `pattern`.
+    pub fn new_circom(
+        rate: usize,
+        pattern: IoPattern,
+        domain_separator: u32,
+    ) -> Result<SafeSponge<Fr>, PoseidonError> {
+        let tag = Fr::from(pattern.tag(domain_separator)?);
+
+        Ok(Self {
+            sponge: PoseidonSponge::with_domain_tag_circom(rate, tag)?,
+            pattern,
+            io_count: 0,
+        })
+    }
+}
@@ -0,0 +1,164 @@
+//! S-boxes - power maps (*x^alpha*) and the inverse map (*x^-1*).
+//!
+//! The exponents used in practice - *3*, *5*, *7* and *17* - have
+//! hand-unrolled square-and-multiply chains. Other exponents fall back to
+//! [`Field::pow`](ark_ff::Field::pow).
+//!
+//! The inverse S-box maps *0* to *0*, as specified in the Poseidon paper, and
+//! inverts the whole state of full rounds with a single field inversion
+//! (Montgomery's batch inversion).
+//!
+//! The hashers ([`Poseidon`](crate::Poseidon),
+//! [`OptimizedPoseidon`](crate::optimized::OptimizedPoseidon) and
+//! [`Poseidon2`](crate::poseidon2::Poseidon2)) pick the permutation
+//! specialized for their exponent once, when they are created, so there is
+//! no dispatch on the exponent in the rounds.
+use ark_ff::{batch_inversion, Field};
+
+/// S-box of the Poseidon permutation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sbox {
+    /// *x^alpha*, where `alpha` has to be greater than 1 and coprime with
+    /// *p - 1*.
+    Power(u64),
+    /// *x^-1*, with *0* mapped to *0*. It's a permutation of every prime
+    /// field, so it can be used for fields where no small power map is.
+    Inverse,
+}
+
+impl Sbox {
+    /// Returns the S-box type used to seed the Grain LFSR (`SBOX` in
+    /// `generate_parameters_grain.sage`) - `0` for *x^alpha* and `1` for
+    /// *x^-1*.
+    pub fn grain_flag(&self) -> u64 {
+        match self {
+            Sbox::Power(_) => 0,
+            Sbox::Inverse => 1,
+        }
+    }
+}
+
+/// Exponents with specialized S-box implementations.
+pub const SPECIALIZED_ALPHAS: [u64; 4] = [3, 5, 7, 17];
+
+/// Computes *x^3* with 2 multiplications.
+#[inline(always)]
+pub fn pow3<F: Field>(x: F) -> F {
+    x.square() * x
+}
+
+/// Computes *x^5* with 3 multiplications.
+#[inline(always)]
+pub fn pow5<F: Field>(x: F) -> F {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// Computes *x^7* with 4 multiplications.
+#[inline(always)]
+pub fn pow7<F: Field>(x: F) -> F {
+    let x3 = x.square() * x;
+    x3.square() * x
+}
+
+/// Computes *x^17* with 5 multiplications.
+#[inline(always)]
+pub fn pow17<F: Field>(x: F) -> F {
+    let x16 = x.square().square().square().square();
+    x16 * x
+}
+
+/// Computes *x^-1*, mapping *0* to *0*.
+#[inline(always)]
+pub fn inverse<F: Field>(x: F) -> F {
+    x.inverse().unwrap_or_default()
+}
+
+/// An S-box map used by the S-box layer, resolved at compile time.
+pub(crate) trait PowerMap {
+    fn apply<F: Field>(x: F, sbox: Sbox) -> F;
+
+    /// Applies the S-box to all elements of the state.
+    #[inline(always)]
+    fn apply_all<F: Field>(state: &mut [F], sbox: Sbox) {
+        state.iter_mut().for_each(|a| *a = Self::apply(*a, sbox));
+    }
+}
+
+pub(crate) struct Pow3;
+pub(crate) struct Pow5;
+pub(crate) struct Pow7;
+pub(crate) struct Pow17;
+/// Any exponent, computed with [`Field::pow`](ark_ff::Field::pow).
+pub(crate) struct PowAny;
+pub(crate) struct Inv;
+
+impl PowerMap for Pow3 {
+    #[inline(always)]
+    fn apply<F: Field>(x: F, _sbox: Sbox) -> F {
+        pow3(x)
+    }
+}
+
+impl PowerMap for Pow5 {
+    #[inline(always)]
+    fn apply<F: Field>(x: F, _sbox: Sbox) -> F {
+        pow5(x)
+    }
+}
+
+impl PowerMap for Pow7 {
+    #[inline(always)]
+    fn apply<F: Field>(x: F, _sbox: Sbox) -> F {
+        pow7(x)
+    }
+}
+
+impl PowerMap for Pow17 {
+    #[inline(always)]
+    fn apply<F: Field>(x: F, _sbox: Sbox) -> F {
+        pow17(x)
+    }
+}
+
+impl PowerMap for PowAny {
+    #[inline(always)]
+    fn apply<F: Field>(x: F, sbox: Sbox) -> F {
+        match sbox {
+            Sbox::Power(alpha) => x.pow([alpha]),
+            Sbox::Inverse => inverse(x),
+        }
+    }
+}
+
+impl PowerMap for Inv {
+    #[inline(always)]
+    fn apply<F: Field>(x: F, _sbox: Sbox) -> F {
+        inverse(x)
+    }
+
+    #[inline(always)]
+    fn apply_all<F: Field>(state: &mut [F], _sbox: Sbox) {
+        // Zeros are skipped, so they stay zeros.
+        batch_inversion(state);
+    }
+}
+
+/// Returns the instance of the generic function `$rounds`, with the field as
+/// the first type parameter and a [`PowerMap`] as the second one, matching
+/// the [`Sbox`] `$sbox`. Additional generic arguments of `$rounds` (e.g. the
+/// width) can be passed after `$sbox`.
+macro_rules! select_rounds {
+    ($rounds:ident, $sbox:expr $(, $generic:tt)*) => {
+        match $sbox {
+            $crate::sbox::Sbox::Power(3) => $rounds::<_, $crate::sbox::Pow3 $(, $generic)*>,
+            $crate::sbox::Sbox::Power(5) => $rounds::<_, $crate::sbox::Pow5 $(, $generic)*>,
+            $crate::sbox::Sbox::Power(7) => $rounds::<_, $crate::sbox::Pow7 $(, $generic)*>,
+            $crate::sbox::Sbox::Power(17) => $rounds::<_, $crate::sbox::Pow17 $(, $generic)*>,
+            $crate::sbox::Sbox::Power(_) => $rounds::<_, $crate::sbox::PowAny $(, $generic)*>,
+            $crate::sbox::Sbox::Inverse => $rounds::<_, $crate::sbox::Inv $(, $generic)*>,
+        }
+    };
+}
+
+pub(crate) use select_rounds;
@@ -0,0 +1,453 @@
+//! Sparse Merkle tree keyed by prime field elements, with inclusion and
+//! non-inclusion proofs.
+//!
+//! The tree follows the layout of iden3's sparse Merkle trees, verified by
+//! the circomlib `SMTVerifier` template:
+//!
+//! * a leaf is hashed as `Poseidon(key, value, 1)` and a middle node as
+//!   `Poseidon(left, right)` (both with the circom-compatible parameters),
+//! * the path of a key is given by its bits, least significant bit first,
+//!   where the bit `1` means the right child,
+//! * an empty subtree hashes to zero on every level, so the default hashes of
+//!   empty subtrees are constant and never have to be recomputed,
+//! * a leaf is stored on the shallowest level where its path is not shared
+//!   with any other key, so the root depends only on the set of key-value
+//!   pairs, not on the order of insertion.
+//!
+//! A non-inclusion proof either ends in an empty subtree or in the leaf of a
+//! different key occupying the path (stored as the auxiliary node).
+//!
+//! ```rust
+//! use light_poseidon::smt::SparseMerkleTree;
+//! use ark_bn254::Fr;
+//!
+//! let mut tree = SparseMerkleTree::new(64).unwrap();
+//!
+//! tree.insert(Fr::from(1u64), Fr::from(42u64)).unwrap();
+//! assert_eq!(tree.get(&Fr::from(1u64)).unwrap(), Some(Fr::from(42u64)));
+//!
+//! let proof = tree.proof(&Fr::from(1u64)).unwrap();
+//! assert!(proof
+//!     .verify_inclusion(tree.root(), Fr::from(1u64), Fr::from(42u64))
+//!     .unwrap());
+//!
+//! let proof = tree.proof(&Fr::from(2u64)).unwrap();
+//! assert!(proof.verify_non_inclusion(tree.root(), Fr::from(2u64)).unwrap());
+//!
+//! // Proofs can be sent in a compact binary form.
+//! let bytes = proof.to_bytes();
+//! # assert_eq!(light_poseidon::smt::SparseMerkleProof::from_bytes(&bytes).unwrap(), proof);
+//! ```
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+
+use crate::{bytes_to_prime_field_element_be, Poseidon, PoseidonError, PoseidonHasher, HASH_LEN};
+
+/// Maximum depth of the tree - the number of bits of BN254 scalar field
+/// elements, so every key has a unique path.
+pub const MAX_DEPTH: usize = 254;
+
+/// Proof flag set for inclusion proofs.
+const FLAG_EXISTENCE: u8 = 1;
+/// Proof flag set when the proof contains the auxiliary node.
+const FLAG_NODE_AUX: u8 = 1 << 1;
+
+/// Returns the bit of the key deciding the direction on the given level.
+fn path_bit(key: &Fr, level: usize) -> bool {
+    key.into_bigint().get_bit(level)
+}
+
+/// Hashes a leaf as `Poseidon(key, value, 1)`.
+fn hash_leaf(poseidon: &Poseidon<Fr>, key: Fr, value: Fr) -> Result<Fr, PoseidonError> {
+    poseidon.hash(&[key, value, Fr::from(1u64)])
+}
+
+/// Hashes the path from `node` on the level `siblings.len()` up to the root.
+fn hash_path(
+    poseidon: &Poseidon<Fr>,
+    key: &Fr,
+    siblings: &[Fr],
+    node: Fr,
+) -> Result<Fr, PoseidonError> {
+    siblings
+        .iter()
+        .enumerate()
+        .rev()
+        .try_fold(node, |node, (level, sibling)| {
+            if path_bit(key, level) {
+                poseidon.hash(&[*sibling, node])
+            } else {
+                poseidon.hash(&[node, *sibling])
+            }
+        })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Node {
+    Leaf { key: Fr, value: Fr },
+    Middle { left: Fr, right: Fr },
+}
+
+/// Result of walking down the path of a key.
+struct Path {
+    /// Siblings from the root down to the reached node.
+    siblings: Vec<Fr>,
+    /// Hashes of the middle nodes from the root down to the reached node,
+    /// which get replaced when the path is updated.
+    middle_nodes: Vec<Fr>,
+    /// Key and value of the reached leaf, `None` if the path ends in an empty
+    /// subtree.
+    leaf: Option<(Fr, Fr)>,
+}
+
+/// Inclusion or non-inclusion proof of a key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMerkleProof {
+    /// Whether the key is included in the tree.
+    pub existence: bool,
+    /// Siblings from the root down to the leaf of the key (or to the node
+    /// where its path ends).
+    pub siblings: Vec<Fr>,
+    /// Key and value of the leaf occupying the path of a non-included key.
+    pub node_aux: Option<(Fr, Fr)>,
+}
+
+impl SparseMerkleProof {
+    /// Checks whether `key` with `value` is included in the tree with the
+    /// given `root`.
+    pub fn verify_inclusion(&self, root: Fr, key: Fr, value: Fr) -> Result<bool, PoseidonError> {
+        if !self.existence || self.siblings.len() > MAX_DEPTH {
+            return Ok(false);
+        }
+        let poseidon = Poseidon::<Fr>::new_circom(3)?;
+        let leaf = hash_leaf(&poseidon, key, value)?;
+
+        let poseidon = Poseidon::<Fr>::new_circom(2)?;
+        Ok(hash_path(&poseidon, &key, &self.siblings, leaf)? == root)
+    }
+
+    /// Checks whether `key` is not included in the tree with the given
+    /// `root`.
+    pub fn verify_non_inclusion(&self, root: Fr, key: Fr) -> Result<bool, PoseidonError> {
+        if self.existence || self.siblings.len() > MAX_DEPTH {
+            return Ok(false);
+        }
+        let node = match self.node_aux {
+            Some((aux_key, aux_value)) => {
+                // The auxiliary leaf has to be a different key sharing the
+                // path.
+                if aux_key == key
+                    || (0..self.siblings.len())
+                        .any(|level| path_bit(&aux_key, level) != path_bit(&key, level))
+                {
+                    return Ok(false);
+                }
+                let poseidon = Poseidon::<Fr>::new_circom(3)?;
+                hash_leaf(&poseidon, aux_key, aux_value)?
+            }
+            None => Fr::zero(),
+        };
+
+        let poseidon = Poseidon::<Fr>::new_circom(2)?;
+        Ok(hash_path(&poseidon, &key, &self.siblings, node)? == root)
+    }
+
+    /// Serializes the proof as:
+    ///
+    /// * 1 byte of flags (`1` - existence, `2` - auxiliary node present),
+    /// * 1 byte with the number of siblings `n`,
+    /// * `ceil(n / 8)` bytes of the bitmap of non-empty siblings (bit `i % 8`
+    ///   of byte `i / 8` set for a non-zero sibling `i`),
+    /// * 32-byte big-endian non-empty siblings,
+    /// * 32-byte big-endian key and value of the auxiliary node, if present.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.existence {
+            flags |= FLAG_EXISTENCE;
+        }
+        if self.node_aux.is_some() {
+            flags |= FLAG_NODE_AUX;
+        }
+
+        let mut bitmap = vec![0u8; self.siblings.len().div_ceil(8)];
+        let mut elements = Vec::new();
+        for (i, sibling) in self.siblings.iter().enumerate() {
+            if !sibling.is_zero() {
+                bitmap[i / 8] |= 1 << (i % 8);
+                elements.push(*sibling);
+            }
+        }
+        if let Some((key, value)) = self.node_aux {
+            elements.extend([key, value]);
+        }
+
+        let mut bytes = vec![flags, self.siblings.len() as u8];
+        bytes.extend(bitmap);
+        for element in elements {
+            bytes.extend(element.into_bigint().to_bytes_be());
+        }
+        bytes
+    }
+
+    /// Deserializes a proof serialized with
+    /// [`to_bytes`](crate::smt::SparseMerkleProof::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PoseidonError> {
+        let invalid = || PoseidonError::InvalidSparseMerkleProof;
+
+        let (&flags, bytes) = bytes.split_first().ok_or_else(invalid)?;
+        let (&len, bytes) = bytes.split_first().ok_or_else(invalid)?;
+        let len = len as usize;
+        let existence = flags & FLAG_EXISTENCE != 0;
+        let has_node_aux = flags & FLAG_NODE_AUX != 0;
+        if flags & !(FLAG_EXISTENCE | FLAG_NODE_AUX) != 0
+            || (existence && has_node_aux)
+            || len > MAX_DEPTH
+        {
+            return Err(invalid());
+        }
+
+        let bitmap_len = len.div_ceil(8);
+        if bytes.len() < bitmap_len {
+            return Err(invalid());
+        }
+        let (bitmap, bytes) = bytes.split_at(bitmap_len);
+        let is_set = |i: usize| bitmap[i / 8] & (1 << (i % 8)) != 0;
+        // Bits beyond the number of siblings have to be unset.
+        if (len..bitmap_len * 8).any(is_set) {
+            return Err(invalid());
+        }
+
+        let nr_elements = (0..len).filter(|i| is_set(*i)).count() + 2 * has_node_aux as usize;
+        if bytes.len() != nr_elements * HASH_LEN {
+            return Err(invalid());
+        }
+        let mut elements = bytes
+            .chunks(HASH_LEN)
+            .map(bytes_to_prime_field_element_be::<Fr>);
+
+        let mut siblings = Vec::with_capacity(len);
+        for i in 0..len {
+            let sibling = if is_set(i) {
+                let sibling = elements.next().ok_or_else(invalid)??;
+                // Zero siblings have to be encoded in the bitmap.
+                if sibling.is_zero() {
+                    return Err(invalid());
+                }
+                sibling
+            } else {
+                Fr::zero()
+            };
+            siblings.push(sibling);
+        }
+        let node_aux = if has_node_aux {
+            let key = elements.next().ok_or_else(invalid)??;
+            let value = elements.next().ok_or_else(invalid)??;
+            Some((key, value))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            existence,
+            siblings,
+            node_aux,
+        })
+    }
+}
+
+/// Sparse Merkle tree with the depth up to 254.
+pub struct SparseMerkleTree {
+    leaf_hasher: Poseidon<Fr>,
+    node_hasher: Poseidon<Fr>,
+    depth: usize,
+    root: Fr,
+    /// Non-empty nodes of the current tree by their hashes. Nodes replaced
+    /// by updates are removed. Empty subtrees are not stored, their hash is
+    /// zero.
+    nodes: BTreeMap<Fr, Node>,
+}
+
+impl SparseMerkleTree {
+    /// Returns an empty tree of the given depth (`1 ≤ depth ≤ 254`).
+    pub fn new(depth: usize) -> Result<Self, PoseidonError> {
+        if depth == 0 || depth > MAX_DEPTH {
+            return Err(PoseidonError::InvalidMerkleTreeHeight {
+                height: depth,
+                max_limit: MAX_DEPTH,
+            });
+        }
+
+        Ok(Self {
+            leaf_hasher: Poseidon::<Fr>::new_circom(3)?,
+            node_hasher: Poseidon::<Fr>::new_circom(2)?,
+            depth,
+            root: Fr::zero(),
+            nodes: BTreeMap::new(),
+        })
+    }
+
+    /// Maximum depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Current root of the tree, zero for an empty tree.
+    pub fn root(&self) -> Fr {
+        self.root
+    }
+
+    /// Number of stored non-empty nodes, leaves included.
+    pub fn nr_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the value stored under the key.
+    pub fn get(&self, key: &Fr) -> Result<Option<Fr>, PoseidonError> {
+        match self.find(key)?.leaf {
+            Some((leaf_key, value)) if leaf_key == *key => Ok(Some(value)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Inserts a new key. Returns an error if the key is already present or if
+    /// it shares the path of the first `depth` bits with another key.
+    pub fn insert(&mut self, key: Fr, value: Fr) -> Result<(), PoseidonError> {
+        let Path {
+            mut siblings,
+            middle_nodes,
+            leaf,
+        } = self.find(&key)?;
+
+        if let Some((old_key, old_value)) = leaf {
+            if old_key == key {
+                return Err(PoseidonError::SparseMerkleKeyExists);
+            }
+            // Push the old leaf down until the paths diverge.
+            let mut level = siblings.len();
+            loop {
+                if level >= self.depth {
+                    return Err(PoseidonError::SparseMerkleMaxDepth { depth: self.depth });
+                }
+                if path_bit(&key, level) != path_bit(&old_key, level) {
+                    break;
+                }
+                level += 1;
+            }
+            siblings.resize(level, Fr::zero());
+            siblings.push(hash_leaf(&self.leaf_hasher, old_key, old_value)?);
+        }
+
+        // The old leaf (if any) is pushed down and kept.
+        self.update_path(key, value, &siblings, &middle_nodes)
+    }
+
+    /// Updates the value of an existing key.
+    pub fn update(&mut self, key: Fr, value: Fr) -> Result<(), PoseidonError> {
+        let Path {
+            siblings,
+            mut middle_nodes,
+            leaf,
+        } = self.find(&key)?;
+        match leaf {
+            Some((leaf_key, old_value)) if leaf_key == key => {
+                middle_nodes.push(hash_leaf(&self.leaf_hasher, key, old_value)?);
+                self.update_path(key, value, &siblings, &middle_nodes)
+            }
+            _ => Err(PoseidonError::SparseMerkleKeyNotFound),
+        }
+    }
+
+    /// Returns the inclusion proof of the key if it is present, otherwise its
+    /// non-inclusion proof.
+    pub fn proof(&self, key: &Fr) -> Result<SparseMerkleProof, PoseidonError> {
+        let Path { siblings, leaf, .. } = self.find(key)?;
+        let (existence, node_aux) = match leaf {
+            Some((leaf_key, _)) if leaf_key == *key => (true, None),
+            leaf => (false, leaf),
+        };
+
+        Ok(SparseMerkleProof {
+            existence,
+            siblings,
+            node_aux,
+        })
+    }
+
+    /// Walks down the path of the key until a leaf or an empty subtree.
+    fn find(&self, key: &Fr) -> Result<Path, PoseidonError> {
+        let mut siblings = Vec::new();
+        let mut middle_nodes = Vec::new();
+        let mut hash = self.root;
+        loop {
+            if hash.is_zero() {
+                return Ok(Path {
+                    siblings,
+                    middle_nodes,
+                    leaf: None,
+                });
+            }
+            match self
+                .nodes
+                .get(&hash)
+                .ok_or(PoseidonError::SparseMerkleNodeNotFound)?
+            {
+                Node::Leaf { key, value } => {
+                    return Ok(Path {
+                        siblings,
+                        middle_nodes,
+                        leaf: Some((*key, *value)),
+                    })
+                }
+                Node::Middle { left, right } => {
+                    middle_nodes.push(hash);
+                    let (left, right) = (*left, *right);
+                    if path_bit(key, siblings.len()) {
+                        siblings.push(left);
+                        hash = right;
+                    } else {
+                        siblings.push(right);
+                        hash = left;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stores the leaf on the level `siblings.len()` and the middle nodes
+    /// above it, updating the root. The `stale` nodes of the old path are
+    /// removed first.
+    ///
+    /// Every stored node is a part of the current tree exactly once - its hash
+    /// commits to the keys of its leaves, which are unique - so the removed
+    /// nodes aren't referenced anywhere else.
+    fn update_path(
+        &mut self,
+        key: Fr,
+        value: Fr,
+        siblings: &[Fr],
+        stale: &[Fr],
+    ) -> Result<(), PoseidonError> {
+        for hash in stale {
+            self.nodes.remove(hash);
+        }
+
+        let mut node = hash_leaf(&self.leaf_hasher, key, value)?;
+        self.nodes.insert(node, Node::Leaf { key, value });
+
+        for (level, sibling) in siblings.iter().enumerate().rev() {
+            let (left, right) = if path_bit(&key, level) {
+                (*sibling, node)
+            } else {
+                (node, *sibling)
+            };
+            node = self.node_hasher.hash(&[left, right])?;
+            self.nodes.insert(node, Node::Middle { left, right });
+        }
+        self.root = node;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+//! Duplex sponge construction built on top of the Poseidon permutation.
+//!
+//! [`PoseidonSponge`](crate::sponge::PoseidonSponge) splits the state of the
+//! permutation into a *capacity* part (the first `capacity` elements, the
+//! first of them holding the domain tag) and a *rate* part (the remaining
+//! `width - capacity` elements). Inputs are added to the rate part, which gets
+//! permuted every time it's full, and outputs are read from the rate part
+//! after a permutation. That allows to hash inputs of any length, absorbed in
+//! any number of calls, and to squeeze any number of outputs.
+//!
+//! [`hash_bytes_var`](crate::sponge::PoseidonSponge::hash_bytes_var) hashes
+//! byte strings of any length. The bytes are padded with the *10\** rule -
+//! a `0x01` byte followed by as many zeros as needed to fill the last limb -
+//! and split into limbs which are small enough to always be smaller than the
+//! modulus (31 bytes for 254 and 255 bit fields, see
+//! [`bytes_to_limbs`](crate::sponge::bytes_to_limbs)). The padding is always
+//! appended, so inputs differing only in trailing zeros have different limbs.
+use alloc::{vec, vec::Vec};
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+
+use crate::{with_stack_state, Poseidon, PoseidonError, PoseidonParameters, HASH_LEN, MAX_X5_LEN};
+
+/// Returns the number of bytes packed into one prime field element by
+/// [`bytes_to_limbs`](crate::sponge::bytes_to_limbs) - the largest number of
+/// bytes which always fits below the modulus. It's `0` for fields with a
+/// modulus of 8 bits or less, which can't hold a whole byte.
+pub fn limb_bytes_len<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize - 1) / 8
+}
+
+/// Pads the given bytes with the *10\** rule (a `0x01` byte and zeros up to
+/// the end of the last limb) and converts them into prime field elements,
+/// reading [`limb_bytes_len`](crate::sponge::limb_bytes_len) big-endian bytes
+/// into each element.
+///
+/// The padding makes the conversion injective - the last limb is never zero
+/// and the original bytes can be recovered by stripping the trailing zeros
+/// and the `0x01` byte. An empty input results in one limb.
+///
+/// Returns an error for fields with a modulus of 8 bits or less.
+pub fn bytes_to_limbs<F: PrimeField>(input: &[u8]) -> Result<Vec<F>, PoseidonError> {
+    let limb_len = limb_bytes_len::<F>();
+    if limb_len == 0 {
+        return Err(PoseidonError::UnsupportedPrimeField {
+            modulus_bit_size: F::MODULUS_BIT_SIZE,
+        });
+    }
+    let mut padded = Vec::with_capacity((input.len() / limb_len + 1) * limb_len);
+    padded.extend_from_slice(input);
+    padded.push(1);
+    padded.resize(padded.len().div_ceil(limb_len) * limb_len, 0);
+
+    Ok(padded
+        .chunks(limb_len)
+        .map(F::from_be_bytes_mod_order)
+        .collect())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SpongeMode {
+    /// The sponge is absorbing. `next_index` is the position in the rate part
+    /// where the next input will be added.
+    Absorbing { next_index: usize },
+    /// The sponge is squeezing. `next_index` is the position in the rate part
+    /// from which the next output will be read.
+    Squeezing { next_index: usize },
+}
+
+/// A duplex sponge performing Poseidon hash computation over inputs of an
+/// arbitrary length.
+pub struct PoseidonSponge<F: PrimeField> {
+    poseidon: Poseidon<F>,
+    state: Vec<F>,
+    capacity: usize,
+    rate: usize,
+    mode: SpongeMode,
+}
+
+impl<F: PrimeField> PoseidonSponge<F> {
+    /// Returns a new sponge based on the given parameters, with `capacity`
+    /// elements of the state reserved as capacity and the rest of the state
+    /// used as rate.
+    ///
+    /// The capacity has to be between 1 and `width - 1`.
+    pub fn new(params: PoseidonParameters<F>, capacity: usize) -> Result<Self, PoseidonError> {
+        Self::with_domain_tag(params, capacity, F::zero())
+    }
+
+    /// Returns a new sponge based on the given parameters, with `capacity`
+    /// elements of the state reserved as capacity and the first capacity
+    /// element initialized with the given domain tag.
+    pub fn with_domain_tag(
+        params: PoseidonParameters<F>,
+        capacity: usize,
+        domain_tag: F,
+    ) -> Result<Self, PoseidonError> {
+        let width = params.width;
+        if capacity == 0 || capacity >= width {
+            return Err(PoseidonError::InvalidCapacity {
+                capacity,
+                max_limit: width.saturating_sub(1),
+                width,
+            });
+        }
+
+        let mut state = vec![F::zero(); width];
+        state[0] = domain_tag;
+
+        Ok(Self {
+            poseidon: Poseidon::with_domain_tag(params, domain_tag),
+            state,
+            capacity,
+            rate: width - capacity,
+            mode: SpongeMode::Absorbing { next_index: 0 },
+        })
+    }
+
+    /// Number of state elements reserved as capacity.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of state elements absorbed or squeezed per permutation.
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// Current state of the sponge, capacity elements first.
+    pub fn state(&self) -> &[F] {
+        &self.state
+    }
+
+    /// Absorbs the given prime field elements into the rate part of the
+    /// state, permuting the state every time the rate part is full.
+    ///
+    /// Can be called multiple times - absorbing `[a, b]` and then `[c]` is
+    /// equivalent to absorbing `[a, b, c]` at once.
+    pub fn absorb(&mut self, inputs: &[F]) {
+        let mut next_index = match self.mode {
+            SpongeMode::Absorbing { next_index } => next_index,
+            SpongeMode::Squeezing { .. } => 0,
+        };
+
+        for input in inputs {
+            if next_index == self.rate {
+                self.poseidon.permute_unchecked(&mut self.state);
+                next_index = 0;
+            }
+            self.state[self.capacity + next_index] += input;
+            next_index += 1;
+        }
+
+        self.mode = SpongeMode::Absorbing { next_index };
+    }
+
+    /// Squeezes `n` prime field elements from the rate part of the state,
+    /// permuting the state before the first output after absorbing and every
+    /// time the rate part is exhausted. After an explicit
+    /// [`permute`](crate::sponge::PoseidonSponge::permute), the output starts
+    /// with the rate part of the already permuted state.
+    ///
+    /// Can be called multiple times - squeezing 1 and then 2 elements is
+    /// equivalent to squeezing 3 elements at once.
+    pub fn squeeze(&mut self, n: usize) -> Vec<F> {
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let mut next_index = match self.mode {
+            // Absorbed inputs have to be permuted before producing the output.
+            SpongeMode::Absorbing { .. } => self.rate,
+            SpongeMode::Squeezing { next_index } => next_index,
+        };
+
+        let mut output = Vec::with_capacity(n);
+        for _ in 0..n {
+            if next_index == self.rate {
+                self.poseidon.permute_unchecked(&mut self.state);
+                next_index = 0;
+            }
+            output.push(self.state[self.capacity + next_index]);
+            next_index += 1;
+        }
+
+        self.mode = SpongeMode::Squeezing { next_index };
+        output
+    }
+
+    /// Hashes a byte string of any length and returns the hash as big-endian
+    /// bytes.
+    ///
+    /// The bytes are converted into prime field elements with
+    /// [`bytes_to_limbs`](crate::sponge::bytes_to_limbs), absorbed into a
+    /// fresh state (holding only the domain tag) and one element is squeezed,
+    /// like with a new sponge. The state of this sponge is left untouched, so
+    /// hashing can be interleaved with
+    /// [`absorb`](crate::sponge::PoseidonSponge::absorb) and
+    /// [`squeeze`](crate::sponge::PoseidonSponge::squeeze).
+    ///
+    /// The hash is returned as [`HASH_LEN`](crate::HASH_LEN) bytes, so only
+    /// fields with elements serialized into 32 bytes (like BN254 and
+    /// BLS12-381) are supported. Other fields result in an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use light_poseidon::sponge::PoseidonSponge;
+    /// use ark_bn254::Fr;
+    ///
+    /// let sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    ///
+    /// let hash = sponge.hash_bytes_var(b"Hello, Poseidon!").unwrap();
+    ///
+    /// // No collision with the same string followed by zeros.
+    /// assert_ne!(hash, sponge.hash_bytes_var(b"Hello, Poseidon!\0").unwrap());
+    /// ```
+    pub fn hash_bytes_var(&self, input: &[u8]) -> Result<[u8; HASH_LEN], PoseidonError> {
+        if F::BigInt::NUM_LIMBS * 8 != HASH_LEN {
+            return Err(PoseidonError::UnsupportedPrimeField {
+                modulus_bit_size: F::MODULUS_BIT_SIZE,
+            });
+        }
+        let limbs = bytes_to_limbs::<F>(input)?;
+
+        let hash = with_stack_state(self.state.len(), |state| {
+            state[0] = self.poseidon.domain_tag;
+            for (i, chunk) in limbs.chunks(self.rate).enumerate() {
+                if i > 0 {
+                    self.poseidon.permute_unchecked(state);
+                }
+                for (a, limb) in state[self.capacity..].iter_mut().zip(chunk) {
+                    *a += limb;
+                }
+            }
+            self.poseidon.permute_unchecked(state);
+            state[self.capacity]
+        });
+
+        hash.into_bigint()
+            .to_bytes_be()
+            .try_into()
+            .map_err(|_| PoseidonError::VecToArray)
+    }
+
+    /// Explicitly applies the permutation to the whole state. The next call to
+    /// [`absorb`](crate::sponge::PoseidonSponge::absorb) starts at the
+    /// beginning of the rate part and the next call to
+    /// [`squeeze`](crate::sponge::PoseidonSponge::squeeze) returns the rate
+    /// part of the permuted state, without permuting it again.
+    pub fn permute(&mut self) {
+        self.poseidon.permute_unchecked(&mut self.state);
+        self.mode = SpongeMode::Squeezing { next_index: 0 };
+    }
+}
+
+impl PoseidonSponge<Fr> {
+    /// Returns a new sponge based on the BN254 parameters provided by the
+    /// library, with capacity 1 and the given rate (`1 ≤ rate ≤ 12`).
+    pub fn new_circom(rate: usize) -> Result<PoseidonSponge<Fr>, PoseidonError> {
+        Self::with_domain_tag_circom(rate, Fr::zero())
+    }
+
+    /// Returns a new sponge based on the BN254 parameters provided by the
+    /// library, with capacity 1, the given rate (`1 ≤ rate ≤ 12`) and the
+    /// given domain tag.
+    pub fn with_domain_tag_circom(
+        rate: usize,
+        domain_tag: Fr,
+    ) -> Result<PoseidonSponge<Fr>, PoseidonError> {
+        let width = rate + 1;
+        if width > MAX_X5_LEN {
+            return Err(PoseidonError::InvalidWidthCircom {
+                width,
+                max_limit: MAX_X5_LEN,
+            });
+        }
+
+        let params = crate::parameters::bn254_x5::get_poseidon_parameters::<Fr>(
+            (width).try_into().map_err(|_| PoseidonError::U64Tou8)?,
+        )?;
+        Self::with_domain_tag(params, 1, domain_tag)
+    }
+}
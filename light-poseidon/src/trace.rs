@@ -0,0 +1,165 @@
+//! Round-by-round traces of the Poseidon permutation, for debugging
+//! mismatches between circuits and the native hash.
+//!
+//! A [`PermutationTrace`](crate::trace::PermutationTrace) contains the state
+//! after every step (adding the round constants, applying the S-boxes and
+//! multiplying by the MDS matrix) of every round. It can be dumped to JSON
+//! with [`to_json`](crate::trace::PermutationTrace::to_json), with all
+//! elements encoded as decimal strings like in snarkjs witness files.
+//!
+//! The states follow the textbook permutation (e.g. circomlib's
+//! `poseidon_old.circom` template). Circuits using the optimized constants
+//! (like the current `poseidon.circom`) compute different intermediate
+//! states, only their outputs match.
+//!
+//! ```rust
+//! use light_poseidon::Poseidon;
+//! use ark_bn254::Fr;
+//!
+//! let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+//! let (hash, trace) = poseidon
+//!     .hash_with_trace(&[Fr::from(1u64), Fr::from(2u64)])
+//!     .unwrap();
+//!
+//! assert_eq!(trace.rounds.len(), 65);
+//! assert_eq!(trace.output()[0], hash);
+//!
+//! let json = trace.to_json();
+//! // Write `json` to a file and diff it against the witness.
+//! ```
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Write;
+
+use ark_ff::PrimeField;
+
+use crate::{
+    apply_ark, apply_mds, apply_sbox_full, apply_sbox_partial, sbox::PowerMap, PoseidonParameters,
+};
+
+/// States of a single round of the permutation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundTrace<F: PrimeField> {
+    /// Index of the round, starting with 0.
+    pub round: usize,
+    /// Whether the round is a full round (S-boxes applied to the whole
+    /// state) or a partial round (S-box applied to the first element).
+    pub full: bool,
+    /// State after adding the round constants.
+    pub after_ark: Vec<F>,
+    /// State after applying the S-boxes.
+    pub after_sbox: Vec<F>,
+    /// State after multiplying by the MDS matrix.
+    pub after_mds: Vec<F>,
+}
+
+/// States of all rounds of the permutation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermutationTrace<F: PrimeField> {
+    /// State before the first round.
+    pub input: Vec<F>,
+    pub rounds: Vec<RoundTrace<F>>,
+}
+
+impl<F: PrimeField> PermutationTrace<F> {
+    /// State after the last round.
+    pub fn output(&self) -> &[F] {
+        self.rounds
+            .last()
+            .map(|round| round.after_mds.as_slice())
+            .unwrap_or(&self.input)
+    }
+
+    /// Returns the trace as a JSON object:
+    ///
+    /// ```json
+    /// {
+    ///   "input": ["0", "1", "2"],
+    ///   "rounds": [
+    ///     {
+    ///       "round": 0,
+    ///       "type": "full",
+    ///       "after_ark": [...],
+    ///       "after_sbox": [...],
+    ///       "after_mds": [...]
+    ///     },
+    ///     ...
+    ///   ],
+    ///   "output": [...]
+    /// }
+    /// ```
+    ///
+    /// Elements are encoded as decimal strings.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"input\": {},", json_array(&self.input));
+        json.push_str("  \"rounds\": [");
+        for (i, round) in self.rounds.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(
+                json,
+                "\n    {{\n      \"round\": {},\n      \"type\": \"{}\",\n      \
+                 \"after_ark\": {},\n      \"after_sbox\": {},\n      \"after_mds\": {}\n    }}",
+                round.round,
+                if round.full { "full" } else { "partial" },
+                json_array(&round.after_ark),
+                json_array(&round.after_sbox),
+                json_array(&round.after_mds),
+            );
+        }
+        if !self.rounds.is_empty() {
+            json.push_str("\n  ");
+        }
+        json.push_str("],\n");
+        let _ = writeln!(json, "  \"output\": {}", json_array(self.output()));
+        json.push('}');
+        json
+    }
+}
+
+/// Returns a JSON array of decimal strings.
+fn json_array<F: PrimeField>(elements: &[F]) -> String {
+    let elements: Vec<String> = elements
+        .iter()
+        .map(|element| format!("\"{}\"", element.into_bigint()))
+        .collect();
+    format!("[{}]", elements.join(", "))
+}
+
+/// Applies all rounds of the permutation to the given state with the S-box
+/// map `P`, recording the state after every step.
+pub(crate) fn trace_rounds_with<F: PrimeField, P: PowerMap>(
+    params: &PoseidonParameters<F>,
+    state: &mut [F],
+) -> PermutationTrace<F> {
+    let all_rounds = params.full_rounds + params.partial_rounds;
+    let half_rounds = params.full_rounds / 2;
+
+    let input = state.to_vec();
+    let rounds = (0..all_rounds)
+        .map(|round| {
+            let full = round < half_rounds || round >= half_rounds + params.partial_rounds;
+
+            apply_ark(params, state, round);
+            let after_ark = state.to_vec();
+            if full {
+                apply_sbox_full::<F, P>(params, state);
+            } else {
+                apply_sbox_partial::<F, P>(params, state);
+            }
+            let after_sbox = state.to_vec();
+            apply_mds(params, state);
+
+            RoundTrace {
+                round,
+                full,
+                after_ark,
+                after_sbox,
+                after_mds: state.to_vec(),
+            }
+        })
+        .collect();
+
+    PermutationTrace { input, rounds }
+}
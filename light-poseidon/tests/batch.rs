@@ -0,0 +1,147 @@
+use ark_bn254::Fr;
+use ark_ff::{One, UniformRand};
+use light_poseidon::{
+    fixed::FixedPoseidon, Poseidon, PoseidonBytesHasher, PoseidonError, PoseidonHasher,
+};
+
+macro_rules! test_hash_batch_matches_hash {
+    ($($nr_inputs:literal),+) => {
+        $(
+            {
+                let mut rng = rand::thread_rng();
+                let inputs: Vec<[Fr; $nr_inputs]> = (0..100)
+                    .map(|_| std::array::from_fn(|_| Fr::rand(&mut rng)))
+                    .collect();
+
+                let hasher =
+                    Poseidon::<Fr>::with_domain_tag_circom($nr_inputs, Fr::one()).unwrap();
+                let expected: Vec<Fr> = inputs
+                    .iter()
+                    .map(|input| hasher.hash(input).unwrap())
+                    .collect();
+
+                assert_eq!(hasher.hash_batch(&inputs).unwrap(), expected);
+
+                let fixed =
+                    FixedPoseidon::<Fr, { $nr_inputs + 1 }>::with_domain_tag_circom(Fr::one())
+                        .unwrap();
+                assert_eq!(fixed.hash_batch(&inputs), expected);
+            }
+        )+
+    };
+}
+
+#[test]
+fn test_hash_batch_matches_hash() {
+    test_hash_batch_matches_hash!(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12);
+}
+
+#[test]
+fn test_hash_batch_empty() {
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    assert!(hasher.hash_batch::<2>(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_hash_batch_invalid_number_of_inputs() {
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    assert!(matches!(
+        hasher.hash_batch(&[[Fr::one(); 3]]),
+        Err(PoseidonError::InvalidNumberOfInputs {
+            inputs: 3,
+            max_limit: 2,
+            width: 3
+        })
+    ));
+}
+
+#[test]
+fn test_hash_bytes_batch_matches_hash_bytes() {
+    let inputs: Vec<[Vec<u8>; 2]> = (0..50u8)
+        .map(|i| {
+            let mut left = [0u8; 32];
+            left[30] = i;
+            let mut right = [0u8; 32];
+            right[1] = i;
+            [left.to_vec(), right.to_vec()]
+        })
+        .collect();
+    let inputs: Vec<[&[u8]; 2]> = inputs
+        .iter()
+        .map(|[left, right]| [left.as_slice(), right.as_slice()])
+        .collect();
+
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let expected_be: Vec<_> = inputs
+        .iter()
+        .map(|input| hasher.hash_bytes_be(input).unwrap())
+        .collect();
+    let expected_le: Vec<_> = inputs
+        .iter()
+        .map(|input| hasher.hash_bytes_le(input).unwrap())
+        .collect();
+
+    assert_eq!(hasher.hash_bytes_be_batch(&inputs).unwrap(), expected_be);
+    assert_eq!(hasher.hash_bytes_le_batch(&inputs).unwrap(), expected_le);
+}
+
+#[test]
+fn test_hash_bytes_batch_input_ones_twos() {
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hashes = hasher
+        .hash_bytes_be_batch(&[[&[1u8; 32], &[2u8; 32]]])
+        .unwrap();
+    assert_eq!(
+        hashes,
+        [[
+            13, 84, 225, 147, 143, 138, 140, 28, 125, 235, 94, 3, 85, 242, 99, 25, 32, 123, 132,
+            254, 156, 162, 206, 27, 38, 231, 53, 200, 41, 130, 25, 144
+        ]]
+    );
+}
+
+#[test]
+fn test_hash_bytes_batch_invalid_input() {
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let valid = [1u8; 32];
+    let too_short = [1u8; 31];
+    let larger_than_modulus = [0xffu8; 32];
+
+    assert!(matches!(
+        hasher.hash_bytes_be_batch(&[[&valid, &valid], [&valid, &too_short]]),
+        Err(PoseidonError::InvalidInputLength { len: 31, .. })
+    ));
+    assert!(matches!(
+        hasher.hash_bytes_le_batch(&[[&valid, &valid], [&larger_than_modulus, &valid]]),
+        Err(PoseidonError::InputLargerThanModulus)
+    ));
+}
+
+/// The error of the invalid input with the lowest index is returned, no matter
+/// which one is found first by the threads.
+#[test]
+fn test_hash_bytes_batch_first_error() {
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let valid = [1u8; 32];
+    let too_short = [1u8; 31];
+    let larger_than_modulus = [0xffu8; 32];
+
+    let mut inputs: Vec<[&[u8]; 2]> = vec![[&valid, &valid]; 1000];
+    inputs[500] = [&valid, &larger_than_modulus];
+    inputs[999] = [&too_short, &valid];
+    for _ in 0..10 {
+        assert_eq!(
+            hasher.hash_bytes_be_batch(&inputs),
+            Err(PoseidonError::InputLargerThanModulus)
+        );
+    }
+
+    inputs[0] = [&valid, &too_short];
+    assert_eq!(
+        hasher.hash_bytes_le_batch(&inputs),
+        Err(PoseidonError::InvalidInputLength {
+            len: 31,
+            modulus_bytes_len: 32
+        })
+    );
+}
@@ -0,0 +1,136 @@
+use ark_bls12_381::Fr;
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use light_poseidon::{
+    grain, parameters::bls12_381_x5, permute, rounds, Poseidon, PoseidonBytesHasher, PoseidonError,
+    PoseidonHasher, Sbox,
+};
+
+fn fr(hex_str: &str) -> Fr {
+    Fr::from_be_bytes_mod_order(&hex::decode(hex_str).unwrap())
+}
+
+/// Checks the permutation of `[0, 1]` and `[0, 1, 2]` against a snapshot of
+/// the output of this library, to catch regressions. These are not published
+/// test vectors, the compatibility of the parameters with other
+/// implementations is covered by the round constant test below.
+#[test]
+fn test_bls12_381_x5_regression() {
+    let params = bls12_381_x5::get_poseidon_parameters::<Fr>(2).unwrap();
+    let mut state = [Fr::from(0u64), Fr::from(1u64)];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(
+        state,
+        [
+            fr("1dc37ce34aeee058292bb73bff9acffce73a8a92f3d6d1daa8b77d9516b5c837"),
+            fr("534cc8001b9c21da25d62749e136ea3d702651ba129f0d5ed7847cf81bc8b042"),
+        ]
+    );
+
+    let params = bls12_381_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let mut state = [Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(
+        state,
+        [
+            fr("200e6982ac00df8fa65cef1fde9f21373fdbbfd98f2df1eb5fa04f3302ab0397"),
+            fr("2233c9a40d91c1f643b700f836a1ac231c3f3a8d438ad1609355e1b7317a47e5"),
+            fr("2eae6736db3c086ad29938869dedbf969dd9804a58aa228ec467b7d5a08dc765"),
+        ]
+    );
+}
+
+/// Checks the round constants for *t = 9* against the ones published by
+/// neptune (`round_constants-1-1-255-9-8-57-...txt`).
+#[test]
+fn test_bls12_381_x5_neptune_round_constants() {
+    let params = bls12_381_x5::get_poseidon_parameters::<Fr>(9).unwrap();
+
+    assert_eq!(params.ark.len(), 585);
+    assert_eq!(
+        params.ark[..3],
+        [
+            fr("6ce90d12c4045fe08c3caddb776dd84ac52b4ae7e48cd49443984154f2f4c2f9"),
+            fr("0ccd9b480c84022b25a75b52c8e89d80b4fa6378d32e58f3a175504940bcec39"),
+            fr("513ad11016ab7bb8c7ce9ae51256040e062487edf7168d206ba1b86dfe963629"),
+        ]
+    );
+    assert_eq!(
+        params.ark[584],
+        fr("3b3a75eb91b0f723bcc4ab7e449e4a011b8960063efe74670c05e545519d9a8c")
+    );
+}
+
+/// Checks whether the parameters are reproducible with the Grain LFSR and the
+/// round numbers calculator.
+#[test]
+fn test_bls12_381_x5_generated() {
+    for t in 2..14u8 {
+        let width = t as usize;
+        let params = bls12_381_x5::get_poseidon_parameters::<Fr>(t).unwrap();
+        assert_eq!(params.validate(), Ok(()));
+
+        let (full_rounds, partial_rounds) = rounds::calc_round_numbers_for_field::<Fr>(
+            width,
+            Sbox::Power(bls12_381_x5::ALPHA),
+            128,
+        )
+        .unwrap();
+        assert_eq!(full_rounds, bls12_381_x5::FULL_ROUNDS);
+        assert_eq!(partial_rounds, bls12_381_x5::PARTIAL_ROUNDS[width - 2]);
+
+        let generated = grain::generate_parameters_with_sbox_flag::<Fr>(
+            1,
+            width,
+            full_rounds,
+            partial_rounds,
+            Sbox::Power(bls12_381_x5::ALPHA),
+        );
+        assert_eq!(generated.ark, params.ark);
+        assert_eq!(generated.mds, params.mds);
+    }
+}
+
+#[test]
+fn test_poseidon_bls12_381_x5_hash() {
+    let mut rng = rand::thread_rng();
+    for nr_inputs in 1..13 {
+        let inputs: Vec<Fr> = (0..nr_inputs).map(|_| Fr::rand(&mut rng)).collect();
+
+        let hasher = Poseidon::<Fr>::new_bls12_381(nr_inputs).unwrap();
+        let hash = hasher.hash(&inputs).unwrap();
+
+        let mut state = [vec![Fr::zero()], inputs.clone()].concat();
+        hasher.permute(&mut state).unwrap();
+        assert_eq!(hash, state[0]);
+
+        let hasher = Poseidon::<Fr>::with_domain_tag_bls12_381(nr_inputs, Fr::one()).unwrap();
+        assert_ne!(hasher.hash(&inputs).unwrap(), hash);
+    }
+}
+
+#[test]
+fn test_poseidon_bls12_381_x5_hash_bytes() {
+    let hasher = Poseidon::<Fr>::new_bls12_381(2).unwrap();
+
+    let hash_be = hasher.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    let hash_le = hasher.hash_bytes_le(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    let hash = hasher
+        .hash(&[
+            Fr::from_be_bytes_mod_order(&[1u8; 32]),
+            Fr::from_be_bytes_mod_order(&[2u8; 32]),
+        ])
+        .unwrap();
+
+    assert_eq!(Fr::from_be_bytes_mod_order(&hash_be), hash);
+    assert_eq!(Fr::from_le_bytes_mod_order(&hash_le), hash);
+}
+
+#[test]
+fn test_poseidon_bls12_381_x5_invalid_width() {
+    for nr_inputs in [0, 13, 14] {
+        assert!(matches!(
+            Poseidon::<Fr>::new_bls12_381(nr_inputs),
+            Err(PoseidonError::InvalidWidth { max_limit: 13, .. })
+        ));
+    }
+}
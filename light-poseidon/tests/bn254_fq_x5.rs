@@ -10,7 +10,7 @@ use rand::Rng;
 /// Checks the hash of `1` as a prime field element.
 #[test]
 fn test_poseidon_one() {
-    let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
 
     let expected = [
         0, 122, 243, 70, 226, 211, 4, 39, 158, 121, 224, 169, 243, 2, 63, 119, 18, 148, 167, 138,
@@ -38,7 +38,7 @@ fn test_poseidon_one() {
 fn test_poseidon_bn254_x5_fq_input_ones_twos() {
     let input1 = Fr::from_be_bytes_mod_order(&[1u8; 32]);
     let input2 = Fr::from_be_bytes_mod_order(&[2u8; 32]);
-    let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
     let hash = hasher.hash(&[input1, input2]).unwrap();
     assert_eq!(
         hash.into_bigint().to_bytes_be(),
@@ -73,7 +73,7 @@ fn test_poseidon_bn254_x5_fq_input_ones_twos() {
 fn test_poseidon_bn254_x5_fq_with_domain_tag() {
     let input1 = Fr::from_be_bytes_mod_order(&[1u8; 32]);
     let input2 = Fr::from_be_bytes_mod_order(&[2u8; 32]);
-    let mut hasher = Poseidon::<Fr>::with_domain_tag_circom(2, Fr::zero()).unwrap();
+    let hasher = Poseidon::<Fr>::with_domain_tag_circom(2, Fr::zero()).unwrap();
     let hash = hasher.hash(&[input1, input2]).unwrap();
 
     let expected_tag_zero = [
@@ -83,7 +83,7 @@ fn test_poseidon_bn254_x5_fq_with_domain_tag() {
 
     assert_eq!(hash.into_bigint().to_bytes_be(), expected_tag_zero);
 
-    let mut hasher = Poseidon::<Fr>::with_domain_tag_circom(2, Fr::one()).unwrap();
+    let hasher = Poseidon::<Fr>::with_domain_tag_circom(2, Fr::one()).unwrap();
     let hash = hasher.hash(&[input1, input2]).unwrap();
 
     assert_ne!(hash.into_bigint().to_bytes_be(), expected_tag_zero);
@@ -95,7 +95,7 @@ fn test_poseidon_bn254_x5_fq_input_one_two() {
     let input1 = Fr::from_be_bytes_mod_order(&[1]);
     let input2 = Fr::from_be_bytes_mod_order(&[2]);
 
-    let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
     let hash = hasher.hash(&[input1, input2]).unwrap();
 
     assert_eq!(
@@ -120,7 +120,7 @@ fn test_poseidon_bn254_x5_fq_input_random() {
         0x30, 0x0b,
     ]);
 
-    let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
     let hash = hasher.hash(&[input1, input2]).unwrap();
     assert_eq!(
         hash.into_bigint().to_bytes_le(),
@@ -138,7 +138,7 @@ fn test_poseidon_bn254_x5_fq_too_many_inputs() {
     let mut rng = rand::thread_rng();
 
     for i in 1..13 {
-        let mut hasher = Poseidon::<Fr>::new_circom(i).unwrap();
+        let hasher = Poseidon::<Fr>::new_circom(i).unwrap();
 
         for j in 1..13 {
             if i != j {
@@ -212,7 +212,7 @@ fn test_poseidon_bn254_x5_fq_bytes() {
 
     for _ in 0..100 {
         for nr_inputs in 1..12 {
-            let mut hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+            let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
 
             // Hash prime field elements.
             let mut inputs = Vec::with_capacity(nr_inputs);
@@ -295,6 +295,40 @@ test_bytes_to_prime_field_element!(
     bytes_to_prime_field_element_le
 );
 
+/// Checks whether `bytes_to_prime_field_element_*` functions return the
+/// original element and accept inputs longer than the big integer only if
+/// the additional most significant bytes are zeros.
+#[test]
+fn test_bytes_to_prime_field_element_roundtrip() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let f = Fr::rand(&mut rng);
+
+        let bytes_be = f.into_bigint().to_bytes_be();
+        assert_eq!(bytes_to_prime_field_element_be::<Fr>(&bytes_be), Ok(f));
+        let bytes_le = f.into_bigint().to_bytes_le();
+        assert_eq!(bytes_to_prime_field_element_le::<Fr>(&bytes_le), Ok(f));
+
+        let mut padded_be = vec![0u8; 8];
+        padded_be.extend_from_slice(&bytes_be);
+        assert_eq!(bytes_to_prime_field_element_be::<Fr>(&padded_be), Ok(f));
+        let mut padded_le = bytes_le.clone();
+        padded_le.extend_from_slice(&[0u8; 8]);
+        assert_eq!(bytes_to_prime_field_element_le::<Fr>(&padded_le), Ok(f));
+
+        padded_be[0] = 1;
+        assert_eq!(
+            bytes_to_prime_field_element_be::<Fr>(&padded_be),
+            Err(PoseidonError::BytesToBigInt)
+        );
+        padded_le[39] = 1;
+        assert_eq!(
+            bytes_to_prime_field_element_le::<Fr>(&padded_le),
+            Err(PoseidonError::BytesToBigInt)
+        );
+    }
+}
+
 macro_rules! test_random_input_same_results {
     ($name:ident, $method:ident) => {
         /// Check whether hashing the same input twice, separately, produces the
@@ -304,7 +338,7 @@ macro_rules! test_random_input_same_results {
             let input = [1u8; 32];
 
             for nr_inputs in 1..12 {
-                let mut hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+                let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
 
                 let mut inputs = Vec::with_capacity(nr_inputs);
                 for _ in 0..nr_inputs {
@@ -343,7 +377,7 @@ macro_rules! test_invalid_input_length {
                 let input = vec![1u8; len];
 
                 for nr_inputs in 1..12 {
-                    let mut hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+                    let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
 
                     let mut inputs = Vec::with_capacity(nr_inputs);
                     for _ in 0..nr_inputs {
@@ -389,7 +423,7 @@ macro_rules! test_fuzz_input_gte_field_size {
             assert_eq!(greater_than_field_size.len(), 32);
 
             for nr_inputs in 1..12 {
-                let mut hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+                let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
 
                 let mut inputs = Vec::with_capacity(nr_inputs);
                 for _ in 0..nr_inputs {
@@ -422,7 +456,7 @@ macro_rules! test_input_gte_field_size {
         #[test]
         fn $name() {
             for nr_inputs in 1..12 {
-                let mut hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+                let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
 
                 let mut inputs = Vec::with_capacity(nr_inputs);
                 for _ in 0..nr_inputs {
@@ -478,7 +512,7 @@ macro_rules! test_input_eq_field_size {
         /// an error.
         #[test]
         fn $name() {
-            let mut hasher = Poseidon::<Fr>::new_circom(1).unwrap();
+            let hasher = Poseidon::<Fr>::new_circom(1).unwrap();
             let input = Fr::MODULUS.$to_bytes_method();
             let hash = hasher.$method(&[&input]);
             assert_eq!(hash, Err(PoseidonError::InputLargerThanModulus));
@@ -492,7 +526,7 @@ test_input_eq_field_size!(test_input_eq_field_size_le, hash_bytes_le, to_bytes_l
 /// Checks that endianness is honored correctly and produces expected hashes.
 #[test]
 fn test_endianness() {
-    let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
 
     let le_input: &[u8] = &[
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -534,7 +568,7 @@ fn test_empty_input() {
 
     // All inputs empty.
     for nr_inputs in 1..12 {
-        let mut hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+        let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
 
         let mut inputs = Vec::with_capacity(nr_inputs);
         for _ in 0..nr_inputs {
@@ -550,7 +584,7 @@ fn test_empty_input() {
 
     // One empty input.
     for nr_inputs in 1..12 {
-        let mut hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+        let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
 
         let mut inputs = Vec::with_capacity(nr_inputs);
         for _ in 0..(nr_inputs - 1) {
@@ -624,7 +658,7 @@ fn test_circom_1_to_12_inputs() {
     let value = [vec![0u8; 31], vec![1u8]].concat();
     for i in 1..13 {
         inputs.push(value.as_slice());
-        let mut hasher = Poseidon::<Fr>::new_circom(i).unwrap();
+        let hasher = Poseidon::<Fr>::new_circom(i).unwrap();
         let hash = hasher.hash_bytes_be(&inputs[..]).unwrap();
         assert_eq!(hash, TEST_CASES[i - 1]);
     }
@@ -632,7 +666,7 @@ fn test_circom_1_to_12_inputs() {
     let value = [vec![0u8; 31], vec![2u8]].concat();
     for i in 1..13 {
         inputs.push(value.as_slice());
-        let mut hasher = Poseidon::<Fr>::new_circom(i).unwrap();
+        let hasher = Poseidon::<Fr>::new_circom(i).unwrap();
         let hash = hasher.hash_bytes_be(&inputs[..]).unwrap();
         assert!(hash != TEST_CASES[i - 1]);
     }
@@ -677,11 +711,75 @@ fn test_circom_t_0_fails() {
     }
 }
 
+/// Checks whether the first element of the state permuted with
+/// [`permute`](light_poseidon::permute) matches the hash of the same inputs.
+#[test]
+fn test_permute_matches_hash() {
+    use light_poseidon::{parameters::bn254_x5, permute};
+
+    let mut rng = rand::thread_rng();
+    for nr_inputs in 1..13 {
+        let inputs: Vec<Fr> = (0..nr_inputs).map(|_| Fr::rand(&mut rng)).collect();
+
+        let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+        let hash = hasher.hash(&inputs).unwrap();
+
+        let mut state = [vec![Fr::zero()], inputs].concat();
+        let params = bn254_x5::get_poseidon_parameters::<Fr>((nr_inputs + 1) as u8).unwrap();
+        permute(&params, &mut state).unwrap();
+        assert_eq!(state[0], hash);
+
+        let mut state_hasher = [vec![Fr::zero()], state[1..].to_vec()].concat();
+        let mut state_params = state_hasher.clone();
+        hasher.permute(&mut state_hasher).unwrap();
+        permute(&params, &mut state_params).unwrap();
+        assert_eq!(state_hasher, state_params);
+    }
+}
+
+/// Checks the whole output state of the permutation for the `[0, 1, 2]`
+/// state against the test vector of the reference implementation (hadeshash),
+/// which is also the output of circomlibjs.
+#[test]
+fn test_permute_one_two() {
+    use light_poseidon::{parameters::bn254_x5, permute};
+
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let mut state = [Fr::zero(), Fr::from(1u64), Fr::from(2u64)];
+    permute(&params, &mut state).unwrap();
+
+    assert_eq!(
+        state,
+        [
+            "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a",
+            "0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29",
+            "0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c",
+        ]
+        .map(|element| Fr::from_be_bytes_mod_order(&hex::decode(element).unwrap()))
+    );
+}
+
+/// Checks whether permuting a state which length doesn't match the width
+/// results in an error.
+#[test]
+fn test_permute_invalid_state_length() {
+    use light_poseidon::{parameters::bn254_x5, permute};
+
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    for len in [0, 1, 2, 4, 13] {
+        let mut state = vec![Fr::zero(); len];
+        assert_eq!(
+            permute(&params, &mut state),
+            Err(PoseidonError::InvalidStateLength { len, width: 3 })
+        );
+    }
+}
+
 /// Checks whether collision attempts with padding in big-endian inputs is
 /// prevented by enforcing the input size.
 #[test]
 fn test_poseidon_bn254_padding_collision_be() {
-    let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
 
     // Correct 32-byte inputs with explicit padding.
     let res = hasher.hash_bytes_be(&[
@@ -779,7 +877,7 @@ fn test_poseidon_bn254_padding_collision_be() {
 /// prevented by enforcing the input size.
 #[test]
 fn test_poseidon_bn254_padding_collision_le() {
-    let mut hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
 
     // Correct 32-byte inputs with explicit padding.
     let res = hasher.hash_bytes_le(&[
@@ -0,0 +1,158 @@
+use ark_bn254::Fr;
+use ark_ff::{Field, UniformRand, Zero};
+use light_poseidon::{
+    grain,
+    optimized::{self, OptimizedPoseidon, OptimizedPoseidonParameters},
+    parameters::bn254_inverse,
+    permute, rounds, sbox, Poseidon, PoseidonError, PoseidonHasher, PoseidonParameters, Sbox,
+};
+
+/// Textbook Poseidon permutation with *x^-1* S-boxes, inverting elements one
+/// by one.
+fn naive_permute(params: &PoseidonParameters<Fr>, state: &mut [Fr]) {
+    let half_rounds = params.full_rounds / 2;
+    let inverse = |a: Fr| a.inverse().unwrap_or(Fr::zero());
+    for round in 0..params.full_rounds + params.partial_rounds {
+        for (i, a) in state.iter_mut().enumerate() {
+            *a += params.ark[round * params.width + i];
+        }
+        if round < half_rounds || round >= half_rounds + params.partial_rounds {
+            state.iter_mut().for_each(|a| *a = inverse(*a));
+        } else {
+            state[0] = inverse(state[0]);
+        }
+        let result: Vec<Fr> = params
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(state.iter()).map(|(m, a)| *m * a).sum())
+            .collect();
+        state.copy_from_slice(&result);
+    }
+}
+
+/// Checks whether the parameters are reproducible with the Grain LFSR (with
+/// the S-box type `1`) and the round numbers calculator.
+#[test]
+fn test_bn254_inverse_generated() {
+    for t in 2..14u8 {
+        let width = t as usize;
+        let params = bn254_inverse::get_poseidon_parameters::<Fr>(t).unwrap();
+        assert_eq!(params.sbox, Sbox::Inverse);
+        assert_eq!(params.validate(), Ok(()));
+
+        let (full_rounds, partial_rounds) =
+            rounds::calc_round_numbers_for_field::<Fr>(width, Sbox::Inverse, 128).unwrap();
+        assert_eq!(full_rounds, bn254_inverse::FULL_ROUNDS);
+        assert_eq!(partial_rounds, bn254_inverse::PARTIAL_ROUNDS[width - 2]);
+
+        let generated =
+            grain::generate_parameters::<Fr>(width, full_rounds, partial_rounds, Sbox::Inverse);
+        assert_eq!(generated.ark, params.ark);
+        assert_eq!(generated.mds, params.mds);
+    }
+    assert_eq!(
+        bn254_inverse::get_poseidon_parameters::<Fr>(14).err(),
+        Some(PoseidonError::InvalidWidth {
+            width: 14,
+            max_limit: 13
+        })
+    );
+}
+
+#[test]
+fn test_sbox_grain_flag() {
+    assert_eq!(Sbox::Power(5).grain_flag(), 0);
+    assert_eq!(Sbox::Inverse.grain_flag(), 1);
+
+    // The S-box type is a part of the LFSR seed.
+    assert_ne!(
+        grain::generate_parameters::<Fr>(3, 8, 57, Sbox::Inverse).ark,
+        grain::generate_parameters::<Fr>(3, 8, 57, Sbox::Power(5)).ark
+    );
+}
+
+#[test]
+fn test_inverse_maps_zero_to_zero() {
+    let mut rng = rand::thread_rng();
+    assert_eq!(sbox::inverse(Fr::zero()), Fr::zero());
+    let x = Fr::rand(&mut rng);
+    assert_eq!(sbox::inverse(x) * x, Fr::from(1u64));
+
+    // With zero round constants and the identity matrix, full rounds invert
+    // all elements (with a batch inversion) and the partial round only the
+    // first one. Zeros have to stay zeros.
+    let identity: Vec<Vec<Fr>> = (0..4)
+        .map(|i| {
+            (0..4)
+                .map(|j| Fr::from(u64::from(i == j)))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let params = PoseidonParameters::new(vec![Fr::zero(); 12], identity, 2, 1, 4, Sbox::Inverse);
+    let (x, y) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+
+    let mut state = [x, Fr::zero(), y, Fr::zero()];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(state, [x.inverse().unwrap(), Fr::zero(), y, Fr::zero()]);
+
+    let mut state = [Fr::zero(); 4];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(state, [Fr::zero(); 4]);
+}
+
+#[test]
+fn test_bn254_inverse_permute() {
+    let mut rng = rand::thread_rng();
+    for t in 2..14u8 {
+        let params = bn254_inverse::get_poseidon_parameters::<Fr>(t).unwrap();
+        let optimized_params = OptimizedPoseidonParameters::new(&params).unwrap();
+
+        let state: Vec<Fr> = (0..t).map(|_| Fr::rand(&mut rng)).collect();
+        let mut expected = state.clone();
+        naive_permute(&params, &mut expected);
+
+        let mut s = state.clone();
+        permute(&params, &mut s).unwrap();
+        assert_eq!(s, expected);
+
+        let mut s = state.clone();
+        optimized::permute(&optimized_params, &mut s).unwrap();
+        assert_eq!(s, expected);
+    }
+}
+
+#[test]
+fn test_bn254_inverse_hash() {
+    let mut rng = rand::thread_rng();
+    for t in 2..14u8 {
+        let inputs: Vec<Fr> = (1..t).map(|_| Fr::rand(&mut rng)).collect();
+        let mut expected = [vec![Fr::zero()], inputs.clone()].concat();
+        naive_permute(
+            &bn254_inverse::get_poseidon_parameters::<Fr>(t).unwrap(),
+            &mut expected,
+        );
+
+        let poseidon =
+            Poseidon::try_new(bn254_inverse::get_poseidon_parameters::<Fr>(t).unwrap()).unwrap();
+        assert_eq!(poseidon.hash(&inputs).unwrap(), expected[0]);
+
+        let params = bn254_inverse::get_poseidon_parameters::<Fr>(t).unwrap();
+        let poseidon = OptimizedPoseidon::new(OptimizedPoseidonParameters::new(&params).unwrap());
+        assert_eq!(poseidon.hash(&inputs).unwrap(), expected[0]);
+    }
+}
+
+/// Round numbers of *x^-1* S-boxes, following the `alpha == -1` branch of
+/// `calc_round_numbers.py`.
+#[test]
+fn test_round_numbers_inverse() {
+    assert_eq!(
+        rounds::calc_round_numbers_for_field::<Fr>(3, Sbox::Inverse, 128),
+        Some((8, 63))
+    );
+    // Goldilocks.
+    assert_eq!(
+        rounds::calc_round_numbers(64.0, 12, Sbox::Inverse, 128, true),
+        Some((8, 18))
+    );
+}
@@ -0,0 +1,186 @@
+use ark_bn254::Fr;
+use ark_ff::{One, UniformRand};
+use light_poseidon::{
+    borrowed::{self, BorrowedPoseidon, BorrowedPoseidonParameters},
+    parameters::bn254_x5,
+    permute, Poseidon, PoseidonBytesHasher, PoseidonError, PoseidonHasher, PoseidonParameters,
+    Sbox,
+};
+
+/// Checks whether the `static` arrays contain the same parameters as the ones
+/// returned by `get_poseidon_parameters`.
+#[test]
+fn test_borrowed_parameters_match_parameters() {
+    for width in 2..14u8 {
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(width).unwrap();
+        let borrowed_params = bn254_x5::get_borrowed_poseidon_parameters(width).unwrap();
+
+        assert_eq!(borrowed_params.width, params.width);
+        assert_eq!(borrowed_params.full_rounds, params.full_rounds);
+        assert_eq!(borrowed_params.partial_rounds, params.partial_rounds);
+        assert_eq!(borrowed_params.sbox, params.sbox);
+
+        let owned: PoseidonParameters<Fr> = borrowed_params.into();
+        assert_eq!(owned.ark, params.ark);
+        assert_eq!(owned.mds, params.mds);
+    }
+}
+
+#[test]
+fn test_borrowed_permute_matches_permute() {
+    let mut rng = rand::thread_rng();
+    for width in 2..14u8 {
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(width).unwrap();
+        let borrowed_params = bn254_x5::get_borrowed_poseidon_parameters(width).unwrap();
+
+        let mut state: Vec<Fr> = (0..width).map(|_| Fr::rand(&mut rng)).collect();
+        let mut borrowed_state = state.clone();
+
+        permute(&params, &mut state).unwrap();
+        borrowed::permute(&borrowed_params, &mut borrowed_state).unwrap();
+
+        assert_eq!(state, borrowed_state);
+    }
+}
+
+#[test]
+fn test_borrowed_poseidon_matches_poseidon() {
+    let mut rng = rand::thread_rng();
+    for nr_inputs in 1..13 {
+        let inputs: Vec<Fr> = (0..nr_inputs).map(|_| Fr::rand(&mut rng)).collect();
+
+        let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+        let borrowed_hasher = BorrowedPoseidon::new_circom(nr_inputs).unwrap();
+
+        assert_eq!(
+            hasher.hash(&inputs).unwrap(),
+            borrowed_hasher.hash(&inputs).unwrap()
+        );
+        // Hashing doesn't modify the hasher.
+        assert_eq!(
+            hasher.hash(&inputs).unwrap(),
+            borrowed_hasher.hash(&inputs).unwrap()
+        );
+
+        let hasher = Poseidon::<Fr>::with_domain_tag_circom(nr_inputs, Fr::one()).unwrap();
+        let borrowed_hasher =
+            BorrowedPoseidon::with_domain_tag_circom(nr_inputs, Fr::one()).unwrap();
+
+        assert_eq!(
+            hasher.hash(&inputs).unwrap(),
+            borrowed_hasher.hash(&inputs).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_borrowed_poseidon_input_ones_twos() {
+    let hasher = BorrowedPoseidon::new_circom(2).unwrap();
+
+    let hash = hasher.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    assert_eq!(
+        hash,
+        [
+            13, 84, 225, 147, 143, 138, 140, 28, 125, 235, 94, 3, 85, 242, 99, 25, 32, 123, 132,
+            254, 156, 162, 206, 27, 38, 231, 53, 200, 41, 130, 25, 144
+        ]
+    );
+}
+
+/// Parameters borrowed from arrays defined outside of the library.
+#[test]
+fn test_borrowed_poseidon_custom_parameters() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let mds: Vec<Fr> = params.mds.iter().flatten().copied().collect();
+    let borrowed_params = BorrowedPoseidonParameters::new(
+        &params.ark,
+        &mds,
+        params.full_rounds,
+        params.partial_rounds,
+        params.width,
+        Sbox::Power(5),
+    )
+    .unwrap();
+
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+    let borrowed_hasher = BorrowedPoseidon::new(borrowed_params);
+    let hasher = Poseidon::<Fr>::new_circom(2).unwrap();
+    assert_eq!(
+        borrowed_hasher.hash(&inputs).unwrap(),
+        hasher.hash(&inputs).unwrap()
+    );
+}
+
+#[test]
+fn test_borrowed_poseidon_invalid_width() {
+    for nr_inputs in [0, 13, 20] {
+        assert!(matches!(
+            BorrowedPoseidon::new_circom(nr_inputs),
+            Err(PoseidonError::InvalidWidthCircom { .. })
+        ));
+    }
+    for width in [0u8, 1, 14] {
+        assert!(matches!(
+            bn254_x5::get_borrowed_poseidon_parameters(width),
+            Err(PoseidonError::InvalidWidthCircom { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_borrowed_poseidon_invalid_number_of_inputs() {
+    let hasher = BorrowedPoseidon::new_circom(2).unwrap();
+    assert!(matches!(
+        hasher.hash(&[Fr::one()]),
+        Err(PoseidonError::InvalidNumberOfInputs { .. })
+    ));
+
+    for nr_inputs in [0, 1, 3] {
+        let bytes = vec![&[1u8; 32][..]; nr_inputs];
+        assert_eq!(
+            hasher.hash_bytes_le(&bytes),
+            Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: nr_inputs,
+                max_limit: 2,
+                width: 3
+            })
+        );
+    }
+
+    let mut state = [Fr::one(); 2];
+    assert!(matches!(
+        hasher.permute(&mut state),
+        Err(PoseidonError::InvalidStateLength { len: 2, width: 3 })
+    ));
+}
+
+#[test]
+fn test_borrowed_parameters_invalid_lengths() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let mds: Vec<Fr> = params.mds.iter().flatten().copied().collect();
+
+    for len in [0, 3, params.ark.len() - 1, params.ark.len() + 1] {
+        let ark: Vec<Fr> = params.ark.iter().cycle().take(len).copied().collect();
+        assert_eq!(
+            BorrowedPoseidonParameters::new(&ark, &mds, 8, 57, 3, Sbox::Power(5)),
+            Err(PoseidonError::InvalidArkLength {
+                len,
+                expected: params.ark.len()
+            })
+        );
+    }
+
+    for len in [0, 3, 8, 10] {
+        let mds = vec![Fr::one(); len];
+        assert_eq!(
+            BorrowedPoseidonParameters::new(&params.ark, &mds, 8, 57, 3, Sbox::Power(5)),
+            Err(PoseidonError::InvalidMdsSize { width: 3 })
+        );
+    }
+
+    let ark = [Fr::one(); 65];
+    assert_eq!(
+        BorrowedPoseidonParameters::new(&ark, &[Fr::one()], 8, 57, 1, Sbox::Power(5)),
+        Err(PoseidonError::InvalidMdsSize { width: 1 })
+    );
+}
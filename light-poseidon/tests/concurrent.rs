@@ -0,0 +1,266 @@
+use ark_bn254::Fr;
+use ark_ff::{UniformRand, Zero};
+use light_poseidon::{
+    concurrent::ConcurrentMerkleTree, merkle::MerkleTree, Poseidon, PoseidonError, PoseidonHasher,
+};
+use proptest::{collection::vec, prelude::*};
+use rand::seq::SliceRandom;
+
+const HEIGHT: usize = 4;
+const CHANGELOG: usize = 8;
+const ROOTS: usize = 8;
+
+type Tree = ConcurrentMerkleTree<HEIGHT, CHANGELOG, ROOTS>;
+
+/// Naive Merkle tree, hashing all levels of the full tree.
+fn naive_layers(leaves: &[Fr]) -> Vec<Vec<Fr>> {
+    let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    let mut layer = leaves.to_vec();
+    layer.resize(1 << HEIGHT, Fr::zero());
+    let mut layers = vec![layer];
+    for level in 0..HEIGHT {
+        let layer = layers[level]
+            .chunks(2)
+            .map(|pair| poseidon.hash(pair).unwrap())
+            .collect();
+        layers.push(layer);
+    }
+    layers
+}
+
+fn naive_root(leaves: &[Fr]) -> Fr {
+    naive_layers(leaves)[HEIGHT][0]
+}
+
+fn naive_proof(leaves: &[Fr], index: usize) -> [Fr; HEIGHT] {
+    let layers = naive_layers(leaves);
+    let mut proof = [Fr::zero(); HEIGHT];
+    for (level, sibling) in proof.iter_mut().enumerate() {
+        *sibling = layers[level][(index >> level) ^ 1];
+    }
+    proof
+}
+
+#[test]
+fn test_append() {
+    let mut rng = rand::thread_rng();
+    let mut tree = Tree::new().unwrap();
+    let mut reference = MerkleTree::new(HEIGHT).unwrap();
+    assert_eq!(tree.root(), reference.root());
+    assert_eq!(tree.sequence_number(), 0);
+
+    for i in 0..tree.capacity() {
+        let leaf = Fr::rand(&mut rng);
+        assert_eq!(tree.append(leaf).unwrap(), i);
+        reference.append(leaf).unwrap();
+        assert_eq!(tree.root(), reference.root());
+        assert_eq!(tree.next_index(), i + 1);
+        assert_eq!(tree.sequence_number(), i + 1);
+
+        let entry = tree.changelog().last().unwrap();
+        assert_eq!(entry.root, tree.root());
+        assert_eq!(entry.index, i);
+        assert_eq!(entry.path[0], leaf);
+    }
+    assert_eq!(
+        tree.append(Fr::zero()),
+        Err(PoseidonError::MerkleTreeFull { capacity: 16 })
+    );
+
+    // Only the last `ROOTS` roots are kept.
+    assert_eq!(tree.roots().count(), ROOTS);
+    assert_eq!(tree.roots().last(), Some(&tree.root()));
+    assert_eq!(tree.changelog().count(), CHANGELOG);
+}
+
+/// Proofs created against the same root can all be used, in any order.
+#[test]
+fn test_replace_leaf_with_stale_proofs() {
+    let mut rng = rand::thread_rng();
+    let mut tree = Tree::new().unwrap();
+    let mut leaves: Vec<Fr> = (0..11).map(|_| Fr::rand(&mut rng)).collect();
+    for leaf in leaves.iter() {
+        tree.append(*leaf).unwrap();
+    }
+
+    let root = tree.root();
+    let mut updates: Vec<(usize, Fr, [Fr; HEIGHT])> = [0, 3, 4, 7, 10]
+        .into_iter()
+        .map(|index| (index, Fr::rand(&mut rng), naive_proof(&leaves, index)))
+        .collect();
+    updates.shuffle(&mut rng);
+
+    for (index, new_leaf, proof) in updates.iter() {
+        tree.replace_leaf(root, leaves[*index], *new_leaf, *index, proof)
+            .unwrap();
+    }
+    // Appends use the updated nodes.
+    let leaf = Fr::rand(&mut rng);
+    tree.append(leaf).unwrap();
+
+    for (index, new_leaf, _) in updates {
+        leaves[index] = new_leaf;
+    }
+    leaves.push(leaf);
+    assert_eq!(tree.root(), naive_root(&leaves));
+    assert!(tree.is_known_root(&root));
+}
+
+#[test]
+fn test_replace_leaf_errors() {
+    let mut tree = Tree::new().unwrap();
+    let leaves: Vec<Fr> = (1..=4u64).map(Fr::from).collect();
+    for leaf in leaves.iter() {
+        tree.append(*leaf).unwrap();
+    }
+    let root = tree.root();
+    let proof = naive_proof(&leaves, 1);
+
+    assert_eq!(
+        tree.replace_leaf(root, leaves[1], Fr::zero(), 4, &proof),
+        Err(PoseidonError::InvalidLeafIndex { index: 4, len: 4 })
+    );
+    assert_eq!(
+        tree.replace_leaf(root, Fr::from(42u64), Fr::zero(), 1, &proof),
+        Err(PoseidonError::InvalidMerkleProof)
+    );
+    assert_eq!(
+        tree.replace_leaf(Fr::from(42u64), leaves[1], Fr::zero(), 1, &proof),
+        Err(PoseidonError::MerkleRootNotFound)
+    );
+    // Failed replacements do not modify the tree.
+    assert_eq!(tree.root(), root);
+    assert_eq!(tree.sequence_number(), 4);
+
+    // The second replacement of the same leaf with a proof against the same
+    // root fails.
+    tree.replace_leaf(root, leaves[1], Fr::from(5u64), 1, &proof)
+        .unwrap();
+    assert_eq!(
+        tree.replace_leaf(root, leaves[1], Fr::from(6u64), 1, &proof),
+        Err(PoseidonError::MerkleLeafModified { index: 1 })
+    );
+
+    // Roots older than the changelog cannot be used.
+    for i in 0..CHANGELOG as u64 {
+        tree.append(Fr::from(10 + i)).unwrap();
+    }
+    assert_eq!(
+        tree.replace_leaf(root, leaves[2], Fr::zero(), 2, &naive_proof(&leaves, 2)),
+        Err(PoseidonError::MerkleRootNotFound)
+    );
+}
+
+#[test]
+fn test_invalid_parameters() {
+    assert!(matches!(
+        ConcurrentMerkleTree::<0, 8, 8>::new(),
+        Err(PoseidonError::InvalidMerkleTreeHeight {
+            height: 0,
+            max_limit: 31
+        })
+    ));
+    assert!(matches!(
+        ConcurrentMerkleTree::<4, 0, 8>::new(),
+        Err(PoseidonError::InvalidMerkleTreeBufferSize {
+            changelog: 0,
+            roots: 8
+        })
+    ));
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Append(u64),
+    /// Replaces the leaf selected by `index` (modulo the number of leaves)
+    /// with a proof against the root from `staleness` operations ago.
+    Replace {
+        index: usize,
+        value: u64,
+        staleness: usize,
+    },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        any::<u64>().prop_map(Op::Append),
+        (any::<usize>(), any::<u64>(), 0..CHANGELOG).prop_map(|(index, value, staleness)| {
+            Op::Replace {
+                index,
+                value,
+                staleness,
+            }
+        }),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    /// Random interleavings of appends and replacements with stale proofs
+    /// converge to the same root as the naive tree.
+    #[test]
+    fn prop_converges_to_naive_tree(ops in vec(op(), 1..40)) {
+        let mut tree = Tree::new().unwrap();
+        // Leaves after every successful operation.
+        let mut history: Vec<Vec<Fr>> = vec![Vec::new()];
+        // Position in the history where every leaf was last modified.
+        let mut modified_at: Vec<usize> = Vec::new();
+
+        for op in ops {
+            let mut leaves = history.last().unwrap().clone();
+            match op {
+                Op::Append(value) => {
+                    if leaves.len() == tree.capacity() {
+                        prop_assert_eq!(
+                            tree.append(Fr::from(value)),
+                            Err(PoseidonError::MerkleTreeFull { capacity: 16 })
+                        );
+                        continue;
+                    }
+                    prop_assert_eq!(tree.append(Fr::from(value)), Ok(leaves.len()));
+                    leaves.push(Fr::from(value));
+                    modified_at.push(history.len());
+                }
+                Op::Replace { index, value, staleness } => {
+                    if leaves.is_empty() {
+                        continue;
+                    }
+                    let index = index % leaves.len();
+                    let position = history.len() - 1 - staleness.min(history.len() - 1);
+                    let snapshot = &history[position];
+
+                    if modified_at[index] > position {
+                        // The snapshot does not contain the current leaf.
+                        let old_leaf = snapshot.get(index).copied().unwrap_or_default();
+                        prop_assert_eq!(
+                            tree.replace_leaf(
+                                naive_root(snapshot),
+                                old_leaf,
+                                Fr::from(value),
+                                index,
+                                &naive_proof(snapshot, index),
+                            ),
+                            Err(PoseidonError::MerkleLeafModified { index })
+                        );
+                        continue;
+                    }
+                    prop_assert_eq!(
+                        tree.replace_leaf(
+                            naive_root(snapshot),
+                            snapshot[index],
+                            Fr::from(value),
+                            index,
+                            &naive_proof(snapshot, index),
+                        ),
+                        Ok(())
+                    );
+                    leaves[index] = Fr::from(value);
+                    modified_at[index] = history.len();
+                }
+            }
+            prop_assert_eq!(tree.root(), naive_root(&leaves));
+            history.push(leaves);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use ark_bn254::Fr;
+use ark_ff::{One, UniformRand};
+use light_poseidon::{
+    borrowed::BorrowedPoseidon, fixed::FixedPoseidon, parameters::bn254_x5, Poseidon,
+    PoseidonBytesHasher, PoseidonError, PoseidonHasher,
+};
+
+/// Allocator counting the allocations of the current thread, so the tests
+/// running in parallel don't affect each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+macro_rules! test_fixed_poseidon_matches_poseidon {
+    ($($width:literal),+) => {
+        $(
+            {
+                let mut rng = rand::thread_rng();
+                let inputs: [Fr; $width - 1] = std::array::from_fn(|_| Fr::rand(&mut rng));
+
+                let fixed = FixedPoseidon::<Fr, $width>::new_circom().unwrap();
+                let poseidon = Poseidon::<Fr>::new_circom($width - 1).unwrap();
+                assert_eq!(fixed.hash(&inputs), poseidon.hash(&inputs).unwrap());
+
+                let fixed = FixedPoseidon::<Fr, $width>::with_domain_tag_circom(Fr::one()).unwrap();
+                let poseidon =
+                    Poseidon::<Fr>::with_domain_tag_circom($width - 1, Fr::one()).unwrap();
+                assert_eq!(fixed.hash(&inputs), poseidon.hash(&inputs).unwrap());
+            }
+        )+
+    };
+}
+
+#[test]
+fn test_fixed_poseidon_matches_poseidon() {
+    test_fixed_poseidon_matches_poseidon!(2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13);
+}
+
+#[test]
+fn test_fixed_poseidon_permute_matches_permute() {
+    let mut rng = rand::thread_rng();
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(5).unwrap();
+    let fixed = FixedPoseidon::<Fr, 5>::new_circom().unwrap();
+
+    let mut state: [Fr; 5] = std::array::from_fn(|_| Fr::rand(&mut rng));
+    let mut fixed_state = state;
+    light_poseidon::permute(&params, &mut state).unwrap();
+    fixed.permute(&mut fixed_state);
+
+    assert_eq!(state, fixed_state);
+}
+
+#[test]
+fn test_fixed_poseidon_input_ones_twos() {
+    let hasher = FixedPoseidon::<Fr, 3>::new_circom().unwrap();
+
+    let hash = hasher.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    assert_eq!(
+        hash,
+        [
+            13, 84, 225, 147, 143, 138, 140, 28, 125, 235, 94, 3, 85, 242, 99, 25, 32, 123, 132,
+            254, 156, 162, 206, 27, 38, 231, 53, 200, 41, 130, 25, 144
+        ]
+    );
+}
+
+#[test]
+fn test_fixed_poseidon_hasher_slice() {
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+    let fixed = FixedPoseidon::<Fr, 3>::new_circom().unwrap();
+    let hash = PoseidonHasher::hash(&fixed, &inputs[..]).unwrap();
+    assert_eq!(hash, fixed.hash(&inputs));
+
+    assert!(matches!(
+        PoseidonHasher::hash(&fixed, &inputs[..1]),
+        Err(PoseidonError::InvalidNumberOfInputs { .. })
+    ));
+    for nr_inputs in [0, 1, 3, 4] {
+        let bytes = vec![&[1u8; 32][..]; nr_inputs];
+        assert_eq!(
+            fixed.hash_bytes_be(&bytes),
+            Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: nr_inputs,
+                max_limit: 2,
+                width: 3
+            })
+        );
+    }
+}
+
+#[test]
+fn test_fixed_poseidon_no_allocations() {
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+
+    let bytes = [[1u8; 32], [2u8; 32]];
+
+    let before = allocations();
+    let fixed = FixedPoseidon::<Fr, 3>::new_circom().unwrap();
+    let hash = fixed.hash(&inputs);
+    let mut state = [Fr::one(); 3];
+    fixed.permute(&mut state);
+    let hash_be = fixed.hash_bytes_be(&[&bytes[0], &bytes[1]]).unwrap();
+    let hash_le = fixed.hash_bytes_le(&[&bytes[0], &bytes[1]]).unwrap();
+    // The borrowed hasher doesn't allocate for widths up to 16 either.
+    let borrowed = BorrowedPoseidon::new_circom(2).unwrap();
+    let borrowed_hash_be = borrowed.hash_bytes_be(&[&bytes[0], &bytes[1]]).unwrap();
+    assert_eq!(allocations(), before);
+
+    assert_eq!(hash_be, borrowed_hash_be);
+    let mut reversed = hash_le;
+    reversed.reverse();
+    assert_eq!(hash_be, reversed);
+
+    // The dynamic hasher allocates its parameters.
+    let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    assert!(allocations() > before);
+    assert_eq!(hash, poseidon.hash(&inputs).unwrap());
+}
+
+#[test]
+fn test_fixed_poseidon_invalid_width() {
+    assert!(matches!(
+        FixedPoseidon::<Fr, 1>::new_circom(),
+        Err(PoseidonError::InvalidWidthCircom { .. })
+    ));
+    assert!(matches!(
+        FixedPoseidon::<Fr, 14>::new_circom(),
+        Err(PoseidonError::InvalidWidthCircom { .. })
+    ));
+
+    let params = bn254_x5::get_borrowed_poseidon_parameters(4).unwrap();
+    assert!(matches!(
+        FixedPoseidon::<Fr, 3>::new(params),
+        Err(PoseidonError::UnexpectedWidth {
+            width: 4,
+            expected: 3
+        })
+    ));
+}
@@ -0,0 +1,149 @@
+use ark_ff::{PrimeField, UniformRand, Zero};
+use light_poseidon::{
+    goldilocks::Goldilocks, parameters::goldilocks_x7, permute, plonky2::Plonky2Poseidon, rounds,
+    PoseidonError, Sbox,
+};
+
+fn state(elements: [u64; 12]) -> [Goldilocks; 12] {
+    elements.map(Goldilocks::from)
+}
+
+#[test]
+fn test_goldilocks_modulus() {
+    assert_eq!(Goldilocks::MODULUS.0, [0xffff_ffff_0000_0001]);
+    assert_eq!(Goldilocks::from(u64::MAX), Goldilocks::from(0xffff_fffeu64));
+}
+
+/// Checks the permutation against the width 12 test vectors from Plonky2
+/// (`poseidon_goldilocks.rs`).
+#[test]
+fn test_goldilocks_x7_plonky2_test_vectors() {
+    let params = goldilocks_x7::get_poseidon_parameters::<Goldilocks>(12).unwrap();
+
+    let mut s = state([0; 12]);
+    permute(&params, &mut s).unwrap();
+    assert_eq!(
+        s,
+        state([
+            0x3c18a9786cb0b359,
+            0xc4055e3364a246c3,
+            0x7953db0ab48808f4,
+            0xc71603f33a1144ca,
+            0xd7709673896996dc,
+            0x46a84e87642f44ed,
+            0xd032648251ee0b3c,
+            0x1c687363b207df62,
+            0xdf8565563e8045fe,
+            0x40f5b37ff4254dae,
+            0xd070f637b431067c,
+            0x1792b1c4342109d7,
+        ])
+    );
+
+    let mut s = state([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+    permute(&params, &mut s).unwrap();
+    assert_eq!(
+        s,
+        state([
+            0xd64e1e3efc5b8e9e,
+            0x53666633020aaa47,
+            0xd40285597c6a8825,
+            0x613a4f81e81231d2,
+            0x414754bfebd051f0,
+            0xcb1f8980294a023f,
+            0x6eb2a9e4d54a9d0f,
+            0x1902bc3af467e056,
+            0xf045d5eafdc6021f,
+            0xe4150f77caaa3be5,
+            0xc9bfd01d39b50cce,
+            0x5c0a27fcb0e1459b,
+        ])
+    );
+}
+
+#[test]
+fn test_goldilocks_x7_parameters() {
+    let params = goldilocks_x7::get_poseidon_parameters::<Goldilocks>(12).unwrap();
+    assert_eq!(params.validate(), Ok(()));
+    assert_eq!(params.ark.len(), 360);
+    assert_eq!(params.mds[0][0], Goldilocks::from(25u64));
+    assert_eq!(params.mds[1][0], Goldilocks::from(20u64));
+    assert_eq!(params.mds[1][1], Goldilocks::from(17u64));
+
+    assert_eq!(
+        rounds::calc_round_numbers_for_field::<Goldilocks>(
+            12,
+            Sbox::Power(goldilocks_x7::ALPHA),
+            128
+        ),
+        Some((goldilocks_x7::FULL_ROUNDS, goldilocks_x7::PARTIAL_ROUNDS))
+    );
+
+    for t in [0, 2, 8, 11, 13, 16] {
+        assert_eq!(
+            goldilocks_x7::get_poseidon_parameters::<Goldilocks>(t).err(),
+            Some(PoseidonError::InvalidWidthGoldilocks { width: t as usize })
+        );
+    }
+}
+
+/// Up to 8 inputs are absorbed by overwriting the rate part of the zero state
+/// in one permutation.
+#[test]
+fn test_plonky2_poseidon_hash_no_pad() {
+    let mut rng = rand::thread_rng();
+    let params = goldilocks_x7::get_poseidon_parameters::<Goldilocks>(12).unwrap();
+    let hasher = Plonky2Poseidon::<Goldilocks>::new_goldilocks().unwrap();
+    assert_eq!(hasher.rate(), 8);
+
+    for nr_inputs in 1..=8 {
+        let inputs: Vec<Goldilocks> = (0..nr_inputs).map(|_| Goldilocks::rand(&mut rng)).collect();
+
+        let mut s = [Goldilocks::zero(); 12];
+        s[..nr_inputs].copy_from_slice(&inputs);
+        permute(&params, &mut s).unwrap();
+
+        assert_eq!(hasher.hash_no_pad(&inputs), s[..4]);
+    }
+
+    // No permutation for an empty input.
+    assert_eq!(hasher.hash_no_pad(&[]), [Goldilocks::zero(); 4]);
+}
+
+/// Chunks overwrite the rate part, so the capacity carries the state between
+/// permutations.
+#[test]
+fn test_plonky2_poseidon_hash_no_pad_multiple_chunks() {
+    let params = goldilocks_x7::get_poseidon_parameters::<Goldilocks>(12).unwrap();
+    let hasher = Plonky2Poseidon::<Goldilocks>::new_goldilocks().unwrap();
+    let inputs: Vec<Goldilocks> = (1..=20u64).map(Goldilocks::from).collect();
+
+    let mut s = [Goldilocks::zero(); 12];
+    for chunk in inputs.chunks(8) {
+        s[..chunk.len()].copy_from_slice(chunk);
+        permute(&params, &mut s).unwrap();
+    }
+    assert_eq!(hasher.hash_no_pad(&inputs), s[..4]);
+
+    // Squeezing more than the rate permutes the state again.
+    let outputs = hasher.hash_n_to_m_no_pad(&inputs, 10);
+    assert_eq!(outputs[..8], s[..8]);
+    permute(&params, &mut s).unwrap();
+    assert_eq!(outputs[8..], s[..2]);
+
+    assert!(hasher.hash_n_to_m_no_pad(&inputs, 0).is_empty());
+}
+
+#[test]
+fn test_plonky2_poseidon_invalid_width() {
+    let params =
+        light_poseidon::parameters::bn254_x5::get_poseidon_parameters::<ark_bn254::Fr>(4).unwrap();
+    assert!(matches!(
+        Plonky2Poseidon::new(params),
+        Err(PoseidonError::InvalidCapacity {
+            capacity: 4,
+            max_limit: 3,
+            width: 4
+        })
+    ));
+}
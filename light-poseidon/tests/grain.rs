@@ -0,0 +1,88 @@
+use ark_bn254::Fr;
+use light_poseidon::{
+    grain::{self, PoseidonGrainLfsr},
+    parameters::{bn254_x5, poseidon2_bn254_x5},
+    Sbox,
+};
+
+/// Checks whether the parameters generated with the Grain LFSR are identical
+/// to the BN254 parameters provided by the library.
+#[test]
+fn test_grain_bn254_x5() {
+    for t in 2..14u8 {
+        let expected = bn254_x5::get_poseidon_parameters::<Fr>(t).unwrap();
+        let params = grain::generate_parameters::<Fr>(
+            t as usize,
+            bn254_x5::FULL_ROUNDS,
+            bn254_x5::PARTIAL_ROUNDS[t as usize - 2],
+            Sbox::Power(bn254_x5::ALPHA),
+        );
+
+        assert_eq!(params.ark, expected.ark);
+        assert_eq!(params.mds, expected.mds);
+        assert_eq!(params.full_rounds, expected.full_rounds);
+        assert_eq!(params.partial_rounds, expected.partial_rounds);
+        assert_eq!(params.width, expected.width);
+        assert_eq!(params.sbox, expected.sbox);
+    }
+}
+
+/// Checks whether the Poseidon2 round constants generated with the Grain LFSR
+/// are identical to the BN254 Poseidon2 parameters provided by the library.
+#[test]
+fn test_grain_poseidon2_bn254_x5() {
+    for t in 2..5u8 {
+        let expected = poseidon2_bn254_x5::get_poseidon2_parameters::<Fr>(t).unwrap();
+        let ark = grain::generate_poseidon2_round_constants::<Fr>(
+            t as usize,
+            poseidon2_bn254_x5::FULL_ROUNDS,
+            poseidon2_bn254_x5::PARTIAL_ROUNDS,
+        );
+
+        assert_eq!(ark, expected.ark);
+    }
+}
+
+/// Checks the first output bits of the LFSR seeded for BN254, *t = 3*, 8 full
+/// and 57 partial rounds. The first sampled field element is the first round
+/// constant.
+#[test]
+fn test_grain_lfsr_bits() {
+    let mut lfsr = PoseidonGrainLfsr::new(0, 254, 3, 8, 57);
+    let bits = lfsr.get_bits(254);
+    let mut lfsr = PoseidonGrainLfsr::new(0, 254, 3, 8, 57);
+    let elements = lfsr.get_field_elements_rejection_sampling::<Fr>(1);
+
+    let expected = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap().ark[0];
+    assert_eq!(elements[0], expected);
+    assert_eq!(
+        bits[..8],
+        [false, false, true, true, true, false, true, true]
+    );
+
+    // Different instances give different output.
+    let mut lfsr = PoseidonGrainLfsr::new(0, 254, 3, 8, 56);
+    assert_ne!(lfsr.get_bits(254), bits);
+    let mut lfsr = PoseidonGrainLfsr::new(1, 254, 3, 8, 57);
+    assert_ne!(lfsr.get_bits(254), bits);
+}
+
+/// Checks whether the generated MDS matrices are Cauchy matrices, i.e.
+/// `1 / M[i][j] = x_i + y_j`.
+#[test]
+fn test_grain_cauchy_matrix() {
+    use ark_ff::Field;
+
+    let mut lfsr = PoseidonGrainLfsr::new(0, 254, 5, 8, 60);
+    let mds = grain::generate_cauchy_matrix::<Fr>(&mut lfsr, 5);
+
+    let inv: Vec<Vec<Fr>> = mds
+        .iter()
+        .map(|row| row.iter().map(|m| m.inverse().unwrap()).collect())
+        .collect();
+    for i in 0..5 {
+        for j in 0..5 {
+            assert_eq!(inv[i][j] - inv[0][j], inv[i][0] - inv[0][0]);
+        }
+    }
+}
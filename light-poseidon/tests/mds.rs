@@ -0,0 +1,169 @@
+use ark_bn254::Fr;
+use ark_ff::{Field, One};
+use light_poseidon::{mds, parameters::bn254_x5, PoseidonError, PoseidonParameters, Sbox};
+
+fn matrix<const N: usize>(m: [[i64; N]; N]) -> Vec<Vec<Fr>> {
+    m.iter()
+        .map(|row| row.iter().map(|a| Fr::from(*a)).collect())
+        .collect()
+}
+
+fn cauchy(xs: &[u64], ys: &[u64]) -> Vec<Vec<Fr>> {
+    xs.iter()
+        .map(|x| {
+            ys.iter()
+                .map(|y| Fr::from(x + y).inverse().unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_mds_security_bn254_x5() {
+    for t in 2..14u8 {
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(t).unwrap();
+        assert_eq!(params.check_mds_security(), Ok(()));
+        assert_eq!(params.validate(), Ok(()));
+    }
+}
+
+#[test]
+fn test_mds_invalid_size() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    assert_eq!(
+        mds::check_mds_security(&params.mds, 4),
+        Err(PoseidonError::InvalidMdsSize { width: 4 })
+    );
+
+    let mut mds = params.mds.clone();
+    mds[1].pop();
+    let params = PoseidonParameters::new(params.ark, mds, 8, 57, 3, Sbox::Power(5));
+    assert_eq!(
+        params.validate(),
+        Err(PoseidonError::InvalidMdsSize { width: 3 })
+    );
+}
+
+#[test]
+fn test_mds_not_invertible() {
+    assert_eq!(
+        mds::check_mds_security(&vec![vec![Fr::one(); 3]; 3], 3),
+        Err(PoseidonError::MdsNotInvertible)
+    );
+}
+
+#[test]
+fn test_mds_not_mds() {
+    // Invertible, but with zero elements.
+    assert_eq!(
+        mds::check_mds_security(&matrix([[1, 0], [0, 1]]), 2),
+        Err(PoseidonError::NotMds)
+    );
+    // Invertible, but `[[1, 2], [2, 4]]` is singular.
+    assert_eq!(
+        mds::check_mds_security(&matrix([[1, 2, 3], [2, 4, 5], [3, 5, 6]]), 3),
+        Err(PoseidonError::NotMds)
+    );
+}
+
+#[test]
+fn test_is_mds() {
+    assert_eq!(mds::is_mds(&cauchy(&[1, 2, 3, 4], &[5, 6, 7, 8])), Ok(true));
+    // Duplicate `x_i`.
+    assert_eq!(mds::is_mds(&cauchy(&[1, 2, 1], &[5, 6, 7])), Ok(false));
+    // Not a Cauchy matrix.
+    assert_eq!(
+        mds::is_mds(&matrix([[5, 7, 7], [5, 8, 2], [3, 3, 9]])),
+        Ok(true)
+    );
+    assert_eq!(
+        mds::is_mds(&matrix([[1, 2, 3], [2, 4, 5], [3, 5, 6]])),
+        Ok(false)
+    );
+}
+
+/// Large matrices other than Cauchy matrices are rejected instead of checking
+/// exponentially many submatrices.
+#[test]
+fn test_is_mds_too_large() {
+    // Cauchy matrices of any size are checked.
+    let xs: Vec<u64> = (0..64).collect();
+    let ys: Vec<u64> = (64..128).collect();
+    assert_eq!(mds::is_mds(&cauchy(&xs, &ys)), Ok(true));
+
+    for width in [13, 16, 64, 100] {
+        // `M[i][j] = i + j + 1` isn't a Cauchy matrix.
+        let m: Vec<Vec<Fr>> = (0..width)
+            .map(|i| (0..width).map(|j| Fr::from((i + j + 1) as u64)).collect())
+            .collect();
+        assert_eq!(
+            mds::is_mds(&m),
+            Err(PoseidonError::MdsTooLarge {
+                width,
+                max_limit: mds::MAX_EXHAUSTIVE_MDS_WIDTH,
+            })
+        );
+    }
+}
+
+/// Parameters with a large matrix other than a Cauchy matrix are rejected by
+/// the validation instead of exhausting memory or panicking.
+#[test]
+fn test_validate_large_non_cauchy_mds() {
+    let width = 64;
+    // A matrix of ones plus `2 * I` is invertible, but not a Cauchy matrix.
+    let m: Vec<Vec<Fr>> = (0..width)
+        .map(|i| {
+            (0..width)
+                .map(|j| if i == j { Fr::from(3u64) } else { Fr::one() })
+                .collect()
+        })
+        .collect();
+    let params =
+        PoseidonParameters::new(vec![Fr::one(); 66 * width], m, 8, 58, width, Sbox::Power(5));
+
+    assert_eq!(
+        params.validate(),
+        Err(PoseidonError::MdsTooLarge {
+            width,
+            max_limit: mds::MAX_EXHAUSTIVE_MDS_WIDTH,
+        })
+    );
+}
+
+/// `(0, 1, -1)` is an eigenvector of the matrix, so the S-box of partial
+/// rounds is never active for states in its span.
+#[test]
+fn test_mds_invariant_subspace_trail_algorithm_1() {
+    assert_eq!(
+        mds::check_mds_security(&matrix([[5, 7, 7], [5, 8, 2], [3, 3, 9]]), 3),
+        Err(PoseidonError::InvariantSubspaceTrail {
+            algorithm: 1,
+            rounds: 1,
+        })
+    );
+}
+
+/// `e_0`, `M * e_0` and `M^2 * e_0` are linearly dependent.
+#[test]
+fn test_mds_invariant_subspace_trail_algorithm_2() {
+    assert_eq!(
+        mds::check_mds_security(&matrix([[2, 6, 6], [3, 2, 5], [3, 1, 6]]), 3),
+        Err(PoseidonError::InvariantSubspaceTrail {
+            algorithm: 2,
+            rounds: 1,
+        })
+    );
+}
+
+/// The matrix is an involution (`M^2 = I`).
+#[test]
+fn test_mds_invariant_subspace_trail_algorithm_3() {
+    assert_eq!(
+        mds::check_mds_security(&matrix([[2, 3], [-1, -2]]), 2),
+        Err(PoseidonError::InvariantSubspaceTrail {
+            algorithm: 3,
+            rounds: 2,
+        })
+    );
+}
@@ -0,0 +1,176 @@
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField, UniformRand, Zero};
+use light_poseidon::{
+    merkle::{self, MerkleProof, MerkleTree},
+    Poseidon, PoseidonError, PoseidonHasher,
+};
+
+/// Computes the root by hashing all levels of the full tree.
+fn naive_root(height: usize, leaves: &[Fr]) -> Fr {
+    let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    let mut layer = leaves.to_vec();
+    layer.resize(1 << height, Fr::zero());
+    for _ in 0..height {
+        layer = layer
+            .chunks(2)
+            .map(|pair| poseidon.hash(pair).unwrap())
+            .collect();
+    }
+    layer[0]
+}
+
+#[test]
+fn test_zero_hashes() {
+    let zero_hashes = merkle::zero_hashes(3, Fr::zero()).unwrap();
+    assert_eq!(zero_hashes.len(), 4);
+    assert_eq!(zero_hashes[0], Fr::zero());
+    // Poseidon(0, 0), the first zero hash of Light Protocol trees.
+    assert_eq!(
+        hex::encode(zero_hashes[1].into_bigint().to_bytes_be()),
+        "2098f5fb9e239eab3ceac3f27b81e481dc3124d55ffed523a839ee8446b64864"
+    );
+
+    let tree = MerkleTree::new(3).unwrap();
+    assert_eq!(tree.zero_hashes(), zero_hashes);
+    assert_eq!(tree.root(), zero_hashes[3]);
+    assert_eq!(tree.root(), naive_root(3, &[]));
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn test_append_root() {
+    let mut rng = rand::thread_rng();
+    let height = 4;
+    let mut tree = MerkleTree::new(height).unwrap();
+    let mut leaves = Vec::new();
+    for i in 0..tree.capacity() {
+        let leaf = Fr::rand(&mut rng);
+        leaves.push(leaf);
+        assert_eq!(tree.append(leaf).unwrap(), i);
+        assert_eq!(tree.len(), i + 1);
+        assert_eq!(tree.leaf(i), Some(leaf));
+        assert_eq!(tree.root(), naive_root(height, &leaves));
+    }
+
+    assert_eq!(
+        tree.append(Fr::zero()),
+        Err(PoseidonError::MerkleTreeFull { capacity: 16 })
+    );
+}
+
+#[test]
+fn test_custom_zero_leaf() {
+    let zero_leaf = Fr::from(42u64);
+    let mut tree = MerkleTree::with_zero_leaf(2, zero_leaf).unwrap();
+    let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    let zero_node = poseidon.hash(&[zero_leaf, zero_leaf]).unwrap();
+    assert_eq!(tree.root(), poseidon.hash(&[zero_node, zero_node]).unwrap());
+
+    let leaf = Fr::from(1u64);
+    tree.append(leaf).unwrap();
+    let node = poseidon.hash(&[leaf, zero_leaf]).unwrap();
+    assert_eq!(tree.root(), poseidon.hash(&[node, zero_node]).unwrap());
+}
+
+#[test]
+fn test_proofs() {
+    let mut rng = rand::thread_rng();
+    let mut tree = MerkleTree::new(5).unwrap();
+    let leaves: Vec<Fr> = (0..11).map(|_| Fr::rand(&mut rng)).collect();
+    for leaf in leaves.iter() {
+        tree.append(*leaf).unwrap();
+    }
+
+    let root = tree.root();
+    for (i, leaf) in leaves.iter().enumerate() {
+        let proof = tree.proof(i).unwrap();
+        assert_eq!(proof.path_elements.len(), 5);
+        assert!(proof.verify(*leaf, root).unwrap());
+        assert!(!proof.verify(Fr::rand(&mut rng), root).unwrap());
+    }
+
+    // Index bits, least significant bit first.
+    let proof = tree.proof(10).unwrap();
+    assert_eq!(proof.path_indices(), [false, true, false, true, false]);
+    // A proof with a wrong index does not verify.
+    let wrong_index = MerkleProof {
+        leaf_index: 11,
+        ..proof
+    };
+    assert!(!wrong_index.verify(leaves[10], root).unwrap());
+
+    assert_eq!(
+        tree.proof(11),
+        Err(PoseidonError::InvalidLeafIndex { index: 11, len: 11 })
+    );
+}
+
+/// Proofs longer than the maximum height or with index bits above their
+/// height are rejected instead of panicking or ignoring the bits.
+#[test]
+fn test_invalid_proof() {
+    let mut tree = MerkleTree::new(3).unwrap();
+    tree.append(Fr::from(1u64)).unwrap();
+    let root = tree.root();
+    let proof = tree.proof(0).unwrap();
+
+    for leaf_index in [8, 9, usize::MAX] {
+        let proof = MerkleProof {
+            leaf_index,
+            ..proof.clone()
+        };
+        assert_eq!(
+            proof.verify(Fr::from(1u64), root),
+            Err(PoseidonError::LeafIndexOutOfRange {
+                index: leaf_index,
+                height: 3
+            })
+        );
+    }
+
+    for height in [merkle::MAX_HEIGHT + 1, 64, 100] {
+        let proof = MerkleProof {
+            leaf_index: 0,
+            path_elements: vec![Fr::zero(); height],
+        };
+        assert_eq!(proof.path_indices(), vec![false; height]);
+        assert_eq!(
+            proof.compute_root(Fr::from(1u64)),
+            Err(PoseidonError::InvalidMerkleTreeHeight {
+                height,
+                max_limit: merkle::MAX_HEIGHT
+            })
+        );
+    }
+}
+
+/// Proofs are valid only against the root at the time they were created.
+#[test]
+fn test_proof_after_append() {
+    let mut tree = MerkleTree::new(3).unwrap();
+    tree.append(Fr::from(1u64)).unwrap();
+    let proof = tree.proof(0).unwrap();
+    let old_root = tree.root();
+
+    tree.append(Fr::from(2u64)).unwrap();
+    assert!(proof.verify(Fr::from(1u64), old_root).unwrap());
+    assert!(!proof.verify(Fr::from(1u64), tree.root()).unwrap());
+    assert!(tree
+        .proof(0)
+        .unwrap()
+        .verify(Fr::from(1u64), tree.root())
+        .unwrap());
+}
+
+#[test]
+fn test_invalid_height() {
+    for height in [0, 32] {
+        assert!(matches!(
+            MerkleTree::new(height),
+            Err(PoseidonError::InvalidMerkleTreeHeight {
+                height: h,
+                max_limit: 31
+            }) if h == height
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, One, PrimeField, UniformRand, Zero};
+use light_poseidon::{
+    optimized::{self, OptimizedPoseidon, OptimizedPoseidonParameters},
+    parameters::bn254_x5,
+    permute, Poseidon, PoseidonBytesHasher, PoseidonError, PoseidonHasher, PoseidonParameters,
+    Sbox,
+};
+
+/// Checks whether the optimized permutation produces the same output state as
+/// the regular one for all widths of the BN254 parameters and random states.
+#[test]
+fn test_optimized_permute_matches_permute() {
+    let mut rng = rand::thread_rng();
+    for width in 2..14u8 {
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(width).unwrap();
+        let optimized_params = OptimizedPoseidonParameters::new(&params).unwrap();
+
+        for _ in 0..5 {
+            let mut state: Vec<Fr> = (0..width).map(|_| Fr::rand(&mut rng)).collect();
+            let mut optimized_state = state.clone();
+
+            permute(&params, &mut state).unwrap();
+            optimized::permute(&optimized_params, &mut optimized_state).unwrap();
+
+            assert_eq!(state, optimized_state);
+        }
+    }
+}
+
+#[test]
+fn test_optimized_poseidon_matches_poseidon() {
+    let mut rng = rand::thread_rng();
+    for nr_inputs in 1..13 {
+        let inputs: Vec<Fr> = (0..nr_inputs).map(|_| Fr::rand(&mut rng)).collect();
+
+        let hasher = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+        let optimized_hasher = OptimizedPoseidon::<Fr>::new_circom(nr_inputs).unwrap();
+
+        assert_eq!(
+            hasher.hash(&inputs).unwrap(),
+            optimized_hasher.hash(&inputs).unwrap()
+        );
+
+        let hasher = Poseidon::<Fr>::with_domain_tag_circom(nr_inputs, Fr::one()).unwrap();
+        let optimized_hasher =
+            OptimizedPoseidon::<Fr>::with_domain_tag_circom(nr_inputs, Fr::one()).unwrap();
+
+        assert_eq!(
+            hasher.hash(&inputs).unwrap(),
+            optimized_hasher.hash(&inputs).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_optimized_poseidon_input_ones_twos() {
+    let hasher = OptimizedPoseidon::<Fr>::new_circom(2).unwrap();
+
+    let hash = hasher.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    assert_eq!(
+        hash,
+        [
+            13, 84, 225, 147, 143, 138, 140, 28, 125, 235, 94, 3, 85, 242, 99, 25, 32, 123, 132,
+            254, 156, 162, 206, 27, 38, 231, 53, 200, 41, 130, 25, 144
+        ]
+    );
+
+    let hash = hasher.hash_bytes_le(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    assert_eq!(
+        hash,
+        [
+            144, 25, 130, 41, 200, 53, 231, 38, 27, 206, 162, 156, 254, 132, 123, 32, 25, 99, 242,
+            85, 3, 94, 235, 125, 28, 140, 138, 143, 147, 225, 84, 13
+        ]
+    );
+
+    let hash = hasher
+        .hash(&[Fr::from(1u64), Fr::from(2u64)])
+        .unwrap()
+        .into_bigint()
+        .to_bytes_le();
+    assert_eq!(
+        hash,
+        [
+            154, 24, 23, 68, 122, 96, 25, 158, 81, 69, 50, 116, 242, 23, 54, 42, 207, 233, 98, 150,
+            107, 76, 246, 61, 65, 144, 214, 231, 245, 192, 92, 17
+        ]
+    );
+}
+
+/// Checks the equivalence for parameters without partial rounds and with
+/// more than 8 full rounds.
+#[test]
+fn test_optimized_permute_custom_rounds() {
+    let mut rng = rand::thread_rng();
+    let base = bn254_x5::get_poseidon_parameters::<Fr>(4).unwrap();
+
+    for (full_rounds, partial_rounds) in [(2, 0), (2, 1), (4, 0), (10, 20)] {
+        let ark = base.ark[..(full_rounds + partial_rounds) * 4].to_vec();
+        let params = PoseidonParameters::new(
+            ark,
+            base.mds.clone(),
+            full_rounds,
+            partial_rounds,
+            4,
+            Sbox::Power(5),
+        );
+        let optimized_params = OptimizedPoseidonParameters::new(&params).unwrap();
+
+        let mut state: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let mut optimized_state = state.clone();
+
+        permute(&params, &mut state).unwrap();
+        optimized::permute(&optimized_params, &mut optimized_state).unwrap();
+
+        assert_eq!(state, optimized_state);
+    }
+}
+
+#[test]
+fn test_optimized_invalid_full_rounds() {
+    let base = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+
+    for full_rounds in [0, 3, 7] {
+        let params = PoseidonParameters::new(
+            base.ark.clone(),
+            base.mds.clone(),
+            full_rounds,
+            base.partial_rounds,
+            3,
+            Sbox::Power(5),
+        );
+        assert_eq!(
+            OptimizedPoseidonParameters::new(&params),
+            Err(PoseidonError::InvalidFullRounds { full_rounds })
+        );
+    }
+}
+
+#[test]
+fn test_optimized_invalid_ark_length() {
+    let base = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+
+    for len in [0, 3, base.ark.len() - 1, base.ark.len() + 1] {
+        let ark = base.ark.iter().cycle().take(len).copied().collect();
+        let params = PoseidonParameters::new(ark, base.mds.clone(), 8, 57, 3, Sbox::Power(5));
+        assert_eq!(
+            OptimizedPoseidonParameters::new(&params),
+            Err(PoseidonError::InvalidArkLength {
+                len,
+                expected: base.ark.len()
+            })
+        );
+    }
+}
+
+#[test]
+fn test_optimized_invalid_mds_size() {
+    let base = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+
+    let mut short_row = base.mds.clone();
+    short_row[2].pop();
+    for mds in [vec![], base.mds[..2].to_vec(), short_row] {
+        let params = PoseidonParameters::new(base.ark.clone(), mds, 8, 57, 3, Sbox::Power(5));
+        assert_eq!(
+            OptimizedPoseidonParameters::new(&params),
+            Err(PoseidonError::InvalidMdsSize { width: 3 })
+        );
+    }
+
+    let params = PoseidonParameters::new(
+        vec![Fr::one(); 65],
+        vec![vec![Fr::one()]],
+        8,
+        57,
+        1,
+        Sbox::Power(5),
+    );
+    assert_eq!(
+        OptimizedPoseidonParameters::new(&params),
+        Err(PoseidonError::InvalidMdsSize { width: 1 })
+    );
+}
+
+#[test]
+fn test_optimized_singular_mds() {
+    let base = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let mds = vec![vec![Fr::one(); 3]; 3];
+    let params = PoseidonParameters::new(base.ark.clone(), mds, 8, 57, 3, Sbox::Power(5));
+
+    assert_eq!(
+        OptimizedPoseidonParameters::new(&params),
+        Err(PoseidonError::MdsNotInvertible)
+    );
+}
+
+#[test]
+fn test_optimized_invalid_state_length() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let params = OptimizedPoseidonParameters::new(&params).unwrap();
+
+    let mut state = vec![Fr::zero(); 4];
+    assert_eq!(
+        optimized::permute(&params, &mut state),
+        Err(PoseidonError::InvalidStateLength { len: 4, width: 3 })
+    );
+}
@@ -0,0 +1,129 @@
+use ark_bn254::Fr;
+use light_poseidon::{
+    parameters::bn254_x5, Poseidon, PoseidonError, PoseidonHasher, PoseidonParameters, Sbox,
+};
+
+#[test]
+fn test_try_new_bn254_x5() {
+    for t in 2..14u8 {
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(t).unwrap();
+        let params = PoseidonParameters::try_new(
+            params.ark,
+            params.mds,
+            params.full_rounds,
+            params.partial_rounds,
+            params.width,
+            params.sbox,
+        )
+        .unwrap();
+
+        let hasher = Poseidon::try_new(params).unwrap();
+        let inputs = vec![Fr::from(1u64); t as usize - 1];
+        assert_eq!(
+            hasher.hash(&inputs),
+            Poseidon::<Fr>::new_circom(t as usize - 1)
+                .unwrap()
+                .hash(&inputs)
+        );
+    }
+}
+
+#[test]
+fn test_try_new_invalid_ark_length() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+
+    for len in [0, 3 * 65 - 1, 3 * 65 + 1] {
+        let mut ark = params.ark.clone();
+        ark.resize(len, Fr::from(1u64));
+        assert_eq!(
+            PoseidonParameters::try_new(ark, params.mds.clone(), 8, 57, 3, Sbox::Power(5)).err(),
+            Some(PoseidonError::InvalidArkLength { len, expected: 195 })
+        );
+    }
+
+    // Full and partial rounds mismatching the constants.
+    assert_eq!(
+        PoseidonParameters::try_new(
+            params.ark.clone(),
+            params.mds.clone(),
+            8,
+            56,
+            3,
+            Sbox::Power(5)
+        )
+        .err(),
+        Some(PoseidonError::InvalidArkLength {
+            len: 195,
+            expected: 192
+        })
+    );
+}
+
+#[test]
+fn test_try_new_invalid_full_rounds() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+
+    for (full_rounds, partial_rounds) in [(0, 65), (7, 58), (9, 56)] {
+        assert_eq!(
+            PoseidonParameters::try_new(
+                params.ark.clone(),
+                params.mds.clone(),
+                full_rounds,
+                partial_rounds,
+                3,
+                Sbox::Power(5)
+            )
+            .err(),
+            Some(PoseidonError::InvalidFullRounds { full_rounds })
+        );
+    }
+}
+
+#[test]
+fn test_try_new_invalid_alpha() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+
+    // `p - 1` is divisible by 2 and 3 for BN254.
+    for alpha in [0, 1, 2, 3, 4, 6, 9] {
+        assert_eq!(
+            PoseidonParameters::try_new(
+                params.ark.clone(),
+                params.mds.clone(),
+                8,
+                57,
+                3,
+                Sbox::Power(alpha)
+            )
+            .err(),
+            Some(PoseidonError::InvalidAlpha { alpha })
+        );
+    }
+
+    for alpha in [5, 7, 11, 17] {
+        assert!(PoseidonParameters::try_new(
+            params.ark.clone(),
+            params.mds.clone(),
+            8,
+            57,
+            3,
+            Sbox::Power(alpha)
+        )
+        .is_ok());
+    }
+}
+
+#[test]
+fn test_poseidon_try_new_invalid_params() {
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let mut ark = params.ark;
+    ark.pop();
+    let params = PoseidonParameters::new(ark, params.mds, 8, 57, 3, Sbox::Power(5));
+
+    assert_eq!(
+        Poseidon::try_new(params).err(),
+        Some(PoseidonError::InvalidArkLength {
+            len: 194,
+            expected: 195
+        })
+    );
+}
@@ -0,0 +1,215 @@
+use ark_ff::{PrimeField, UniformRand};
+use light_poseidon::{
+    grain,
+    halo2::Halo2Poseidon,
+    parameters::{pallas_x5, vesta_x5},
+    pasta::{Fp, Fq},
+    permute, rounds, PoseidonBytesHasher, PoseidonError, PoseidonHasher, PoseidonParameters, Sbox,
+};
+
+fn fe<F: PrimeField>(hex_str: &str) -> F {
+    F::from_be_bytes_mod_order(&hex::decode(hex_str).unwrap())
+}
+
+/// Checks the permutation of `[0, 1, 2]` against the test vectors of
+/// `P128Pow5T3` from halo2_gadgets (`poseidon::primitives::test_vectors`).
+#[test]
+fn test_pasta_x5_halo2_permute_test_vectors() {
+    let params = pallas_x5::get_poseidon_parameters::<Fp>(3).unwrap();
+    let mut state = [Fp::from(0u64), Fp::from(1u64), Fp::from(2u64)];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(
+        state,
+        [
+            fe("2a526acd0b64b45394efb364f966240ff7e69a71d0b642a0aeb1bc024aeca456"),
+            fe("13c5d1568b4aa43076ff7dae343d5512dcd42e7fbed9dafe012a3e9628e5b82a"),
+            fe("0a49c868c6976544256fcd597984561af7cfdfe1bda42c7b359029a1d34e9ddd"),
+        ]
+    );
+
+    let params = vesta_x5::get_poseidon_parameters::<Fq>(3).unwrap();
+    let mut state = [Fq::from(0u64), Fq::from(1u64), Fq::from(2u64)];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(
+        state,
+        [
+            fe("315a1f4cdb942f7ceddd74f22f8f2ff74d43d1973dd336c60eb08ea813bebe59"),
+            fe("3be475f2d7642bde642adee0dd13aa48413ee0eb7bbd2198f9f126e61ea165f1"),
+            fe("25ab8aece9537168117fdb2420d8ea605019bfd4e0423fa014d542372a7ba0d9"),
+        ]
+    );
+}
+
+/// Checks the permutation of `[0, 1, 2]` with parameters generated with the
+/// S-box type `1` in the LFSR seed against the test vectors from zkhash
+/// (HorizenLabs/poseidon2).
+#[test]
+fn test_pasta_x5_zkhash_kat() {
+    let params = grain::generate_parameters_with_sbox_flag::<Fp>(1, 3, 8, 56, Sbox::Power(5));
+    let mut state = [Fp::from(0u64), Fp::from(1u64), Fp::from(2u64)];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(
+        state,
+        [
+            fe("08fd69dd1602112194d1fefd8c2b20242e371879feba6683a4bdeebd6e8f121c"),
+            fe("2a17023cc2483bf305661df2580c3b29444f8b954de7f2166091592ba7728591"),
+            fe("1495649c6632dd6202315e468aa08b1392b750dfe0d2b3bbc902e230355e9615"),
+        ]
+    );
+
+    let params = grain::generate_parameters_with_sbox_flag::<Fq>(1, 3, 8, 56, Sbox::Power(5));
+    let mut state = [Fq::from(0u64), Fq::from(1u64), Fq::from(2u64)];
+    permute(&params, &mut state).unwrap();
+    assert_eq!(
+        state,
+        [
+            fe("32e8b71fc2963b1c2371a5a9e191671079b3e059d9683027b146bd5d34cea133"),
+            fe("005e6cd1461b0470c03f045e8fba078846bbdbb0992c37fc6f4764ebdb92a1d6"),
+            fe("162f4406f334d8600c569b3172e75abf00f6c201871d4fff9834cedd0c8aa5d3"),
+        ]
+    );
+}
+
+fn check_generated<F: PrimeField + From<ark_ff::BigInteger256>>(
+    get_poseidon_parameters: fn(u8) -> Result<PoseidonParameters<F>, PoseidonError>,
+    partial_rounds: &[usize],
+) {
+    for t in 2..14u8 {
+        let width = t as usize;
+        let params = get_poseidon_parameters(t).unwrap();
+        assert_eq!(params.validate(), Ok(()));
+
+        let (full_rounds, rp) =
+            rounds::calc_round_numbers_for_field::<F>(width, Sbox::Power(5), 128).unwrap();
+        assert_eq!(full_rounds, 8);
+        assert_eq!(rp, partial_rounds[width - 2]);
+
+        let generated = grain::generate_parameters::<F>(width, full_rounds, rp, Sbox::Power(5));
+        assert_eq!(generated.ark, params.ark);
+        assert_eq!(generated.mds, params.mds);
+    }
+    assert_eq!(
+        get_poseidon_parameters(14).err(),
+        Some(PoseidonError::InvalidWidth {
+            width: 14,
+            max_limit: 13
+        })
+    );
+}
+
+/// Checks whether the parameters are reproducible with the Grain LFSR and the
+/// round numbers calculator.
+#[test]
+fn test_pasta_x5_generated() {
+    check_generated::<Fp>(
+        pallas_x5::get_poseidon_parameters::<Fp>,
+        &pallas_x5::PARTIAL_ROUNDS,
+    );
+    check_generated::<Fq>(
+        vesta_x5::get_poseidon_parameters::<Fq>,
+        &vesta_x5::PARTIAL_ROUNDS,
+    );
+    // `P128Pow5T3`
+    assert_eq!(pallas_x5::PARTIAL_ROUNDS[1], 56);
+    assert_eq!(vesta_x5::PARTIAL_ROUNDS[1], 56);
+}
+
+/// `ConstantLength<2>` hashing absorbs both inputs in one permutation of
+/// `[a, b, 2 * 2^64]`.
+#[test]
+fn test_halo2_poseidon_constant_length_2() {
+    let mut rng = rand::thread_rng();
+    let capacity = Fp::from(2u64) * Fp::from(1u128 << 64);
+
+    let hasher = Halo2Poseidon::<Fp>::new_pallas(2).unwrap();
+    let params = pallas_x5::get_poseidon_parameters::<Fp>(3).unwrap();
+    for _ in 0..10 {
+        let inputs = [Fp::rand(&mut rng), Fp::rand(&mut rng)];
+        let mut state = [inputs[0], inputs[1], capacity];
+        permute(&params, &mut state).unwrap();
+        assert_eq!(hasher.hash(&inputs).unwrap(), state[0]);
+    }
+
+    let hasher = Halo2Poseidon::<Fq>::new_vesta(2).unwrap();
+    let params = vesta_x5::get_poseidon_parameters::<Fq>(3).unwrap();
+    let capacity = Fq::from(2u64) * Fq::from(1u128 << 64);
+    for _ in 0..10 {
+        let inputs = [Fq::rand(&mut rng), Fq::rand(&mut rng)];
+        let mut state = [inputs[0], inputs[1], capacity];
+        permute(&params, &mut state).unwrap();
+        assert_eq!(hasher.hash(&inputs).unwrap(), state[0]);
+    }
+}
+
+/// Inputs which don't fill the rate part are padded with zeros and every
+/// chunk is followed by a permutation.
+#[test]
+fn test_halo2_poseidon_constant_length_padding() {
+    let params = pallas_x5::get_poseidon_parameters::<Fp>(3).unwrap();
+    let inputs: Vec<Fp> = (1..=5u64).map(Fp::from).collect();
+
+    for nr_inputs in 1..=5 {
+        let mut state = [Fp::from(0u64), Fp::from(0u64), Fp::from(1u128 << 64)];
+        state[2] *= Fp::from(nr_inputs as u64);
+        for chunk in inputs[..nr_inputs].chunks(2) {
+            state[0] += chunk[0];
+            if let Some(input) = chunk.get(1) {
+                state[1] += input;
+            }
+            permute(&params, &mut state).unwrap();
+        }
+
+        let hasher = Halo2Poseidon::<Fp>::new_pallas(nr_inputs).unwrap();
+        assert_eq!(hasher.hash(&inputs[..nr_inputs]).unwrap(), state[0]);
+    }
+
+    // The length is a part of the domain.
+    let hasher_1 = Halo2Poseidon::<Fp>::new_pallas(1).unwrap();
+    let hasher_2 = Halo2Poseidon::<Fp>::new_pallas(2).unwrap();
+    assert_ne!(
+        hasher_1.hash(&[Fp::from(1u64)]).unwrap(),
+        hasher_2.hash(&[Fp::from(1u64), Fp::from(0u64)]).unwrap()
+    );
+}
+
+#[test]
+fn test_halo2_poseidon_hash_bytes() {
+    let hasher = Halo2Poseidon::<Fp>::new_pallas(2).unwrap();
+
+    let hash_be = hasher.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    let hash_le = hasher.hash_bytes_le(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    let hash = hasher
+        .hash(&[
+            Fp::from_be_bytes_mod_order(&[1u8; 32]),
+            Fp::from_be_bytes_mod_order(&[2u8; 32]),
+        ])
+        .unwrap();
+
+    assert_eq!(Fp::from_be_bytes_mod_order(&hash_be), hash);
+    assert_eq!(Fp::from_le_bytes_mod_order(&hash_le), hash);
+
+    // Larger than the modulus.
+    assert_eq!(
+        hasher.hash_bytes_be(&[&[0xffu8; 32], &[2u8; 32]]),
+        Err(PoseidonError::InputLargerThanModulus)
+    );
+}
+
+#[test]
+fn test_halo2_poseidon_invalid_inputs() {
+    assert!(matches!(
+        Halo2Poseidon::<Fp>::new_pallas(0),
+        Err(PoseidonError::EmptyInput)
+    ));
+
+    let hasher = Halo2Poseidon::<Fq>::new_vesta(2).unwrap();
+    for nr_inputs in [0, 1, 3] {
+        assert_eq!(
+            hasher.hash(&vec![Fq::from(1u64); nr_inputs]),
+            Err(PoseidonError::UnexpectedNumberOfInputs {
+                inputs: nr_inputs,
+                expected: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use ark_bn254::Fr;
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use light_poseidon::{
+    parameters::poseidon2_bn254_x5::get_poseidon2_parameters,
+    poseidon2::{self, Poseidon2, Poseidon2Parameters},
+    PoseidonBytesHasher, PoseidonError, PoseidonHasher, Sbox,
+};
+
+fn fr(hex_str: &str) -> Fr {
+    Fr::from_be_bytes_mod_order(&hex::decode(hex_str).unwrap())
+}
+
+/// Checks the permutation of `[0, 1, 2]` against the test vector from the
+/// reference implementation (HorizenLabs/poseidon2).
+#[test]
+fn test_poseidon2_bn254_x5_t3_kat() {
+    let params = get_poseidon2_parameters::<Fr>(3).unwrap();
+    let mut state = [Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)];
+    poseidon2::permute(&params, &mut state).unwrap();
+
+    assert_eq!(
+        state,
+        [
+            fr("0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033"),
+            fr("303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570"),
+            fr("1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8"),
+        ]
+    );
+}
+
+/// Checks the permutation of `[0, 1, 2, 3]` against the test vector from
+/// Barretenberg.
+#[test]
+fn test_poseidon2_bn254_x5_t4_kat() {
+    let params = get_poseidon2_parameters::<Fr>(4).unwrap();
+    let mut state = [
+        Fr::from(0u64),
+        Fr::from(1u64),
+        Fr::from(2u64),
+        Fr::from(3u64),
+    ];
+    poseidon2::permute(&params, &mut state).unwrap();
+
+    assert_eq!(
+        state,
+        [
+            fr("01bd538c2ee014ed5141b29e9ae240bf8db3fe5b9a38629a9647cf8d76c01737"),
+            fr("239b62e7db98aa3a2a8f6a0d2fa1709e7a35959aa6c7034814d9daa90cbac662"),
+            fr("04cbb44c61d928ed06808456bf758cbf0c18d1e15a7b6dbc8245fa7515d5e3cb"),
+            fr("2e11c5cff2a22c64d01304b778d78f6998eff1ab73163a35603f54794c30847a"),
+        ]
+    );
+}
+
+/// Checks the permutation of `[0, 1]` against a snapshot of the output of
+/// this library, to catch regressions. There is no published test vector for
+/// this width, the constants come from the same generator as for the widths
+/// above.
+#[test]
+fn test_poseidon2_bn254_x5_t2_regression() {
+    let params = get_poseidon2_parameters::<Fr>(2).unwrap();
+    let mut state = [Fr::from(0u64), Fr::from(1u64)];
+    poseidon2::permute(&params, &mut state).unwrap();
+
+    assert_eq!(
+        state,
+        [
+            fr("1d01e56f49579cec72319e145f06f6177f6c5253206e78c2689781452a31878b"),
+            fr("0d189ec589c41b8cffa88cfc523618a055abe8192c70f75aa72fc514560f6c61"),
+        ]
+    );
+}
+
+/// Checks whether the hash is the first element of the permuted state with
+/// the domain tag followed by the inputs, like in `Poseidon`.
+#[test]
+fn test_poseidon2_bn254_x5_hash() {
+    let mut rng = rand::thread_rng();
+    for nr_inputs in 1..4 {
+        let inputs: Vec<Fr> = (0..nr_inputs).map(|_| Fr::rand(&mut rng)).collect();
+
+        let hasher = Poseidon2::<Fr>::new_bn254(nr_inputs).unwrap();
+        let hash = hasher.hash(&inputs).unwrap();
+
+        let mut state = [vec![Fr::zero()], inputs.clone()].concat();
+        hasher.permute(&mut state).unwrap();
+        assert_eq!(hash, state[0]);
+
+        let hasher = Poseidon2::<Fr>::with_domain_tag_bn254(nr_inputs, Fr::one()).unwrap();
+        assert_ne!(hasher.hash(&inputs).unwrap(), hash);
+    }
+}
+
+#[test]
+fn test_poseidon2_bn254_x5_hash_bytes() {
+    let hasher = Poseidon2::<Fr>::new_bn254(2).unwrap();
+
+    let hash_be = hasher.hash_bytes_be(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    let hash_le = hasher.hash_bytes_le(&[&[1u8; 32], &[2u8; 32]]).unwrap();
+    let hash = hasher
+        .hash(&[
+            Fr::from_be_bytes_mod_order(&[1u8; 32]),
+            Fr::from_be_bytes_mod_order(&[2u8; 32]),
+        ])
+        .unwrap();
+
+    assert_eq!(Fr::from_be_bytes_mod_order(&hash_be), hash);
+    assert_eq!(Fr::from_le_bytes_mod_order(&hash_le), hash);
+
+    assert_eq!(
+        hasher.hash_bytes_be(&[&[1u8; 31], &[2u8; 32]]),
+        Err(PoseidonError::InvalidInputLength {
+            len: 31,
+            modulus_bytes_len: 32,
+        })
+    );
+}
+
+/// Checks the dense representations of the external and internal matrices.
+#[test]
+fn test_poseidon2_matrices() {
+    let params = get_poseidon2_parameters::<Fr>(3).unwrap();
+    assert_eq!(
+        params.mat_external(),
+        [
+            [Fr::from(2u64), Fr::from(1u64), Fr::from(1u64)],
+            [Fr::from(1u64), Fr::from(2u64), Fr::from(1u64)],
+            [Fr::from(1u64), Fr::from(1u64), Fr::from(2u64)],
+        ]
+    );
+    assert_eq!(
+        params.mat_internal(),
+        [
+            [Fr::from(2u64), Fr::from(1u64), Fr::from(1u64)],
+            [Fr::from(1u64), Fr::from(2u64), Fr::from(1u64)],
+            [Fr::from(1u64), Fr::from(1u64), Fr::from(3u64)],
+        ]
+    );
+
+    let params = get_poseidon2_parameters::<Fr>(4).unwrap();
+    let m4 = [[5u64, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+    assert_eq!(
+        params.mat_external(),
+        m4.map(|row| row.map(Fr::from).to_vec()).to_vec()
+    );
+
+    // For 8 elements, the external matrix is `circ(2 * M4, M4)`.
+    let params =
+        Poseidon2Parameters::<Fr>::new(vec![], vec![Fr::zero(); 8], 0, 0, 8, Sbox::Power(5))
+            .unwrap();
+    let mat_external = params.mat_external();
+    for i in 0..8 {
+        for j in 0..8 {
+            let factor = if i / 4 == j / 4 { 2 } else { 1 };
+            assert_eq!(mat_external[i][j], Fr::from(factor * m4[i % 4][j % 4]));
+        }
+    }
+}
+
+#[test]
+fn test_poseidon2_invalid_width() {
+    for width in [0, 1, 5, 6, 7, 9, 25] {
+        assert_eq!(
+            Poseidon2Parameters::<Fr>::new(vec![], vec![], 8, 56, width, Sbox::Power(5)),
+            Err(PoseidonError::InvalidWidthPoseidon2 { width })
+        );
+    }
+
+    for nr_inputs in [0, 4, 5] {
+        assert!(matches!(
+            Poseidon2::<Fr>::new_bn254(nr_inputs),
+            Err(PoseidonError::InvalidWidth { max_limit: 4, .. })
+        ));
+    }
+}
+
+#[test]
+fn test_poseidon2_invalid_ark_length() {
+    let params = get_poseidon2_parameters::<Fr>(3).unwrap();
+    let expected = params.ark.len();
+
+    for len in [0, expected - 1, expected + 1] {
+        let ark = params.ark.iter().cycle().take(len).copied().collect();
+        assert_eq!(
+            Poseidon2Parameters::new(
+                ark,
+                params.mat_internal_diag_m_1.clone(),
+                params.full_rounds,
+                params.partial_rounds,
+                3,
+                Sbox::Power(5)
+            ),
+            Err(PoseidonError::InvalidArkLength { len, expected })
+        );
+    }
+}
+
+#[test]
+fn test_poseidon2_invalid_mat_internal_diag_length() {
+    let params = get_poseidon2_parameters::<Fr>(3).unwrap();
+
+    for len in [0, 2, 4] {
+        assert_eq!(
+            Poseidon2Parameters::new(
+                params.ark.clone(),
+                vec![Fr::one(); len],
+                params.full_rounds,
+                params.partial_rounds,
+                3,
+                Sbox::Power(5)
+            ),
+            Err(PoseidonError::InvalidMatInternalDiagLength { len, width: 3 })
+        );
+    }
+}
+
+#[test]
+fn test_poseidon2_invalid_alpha() {
+    let params = get_poseidon2_parameters::<Fr>(3).unwrap();
+
+    // `p - 1` is divisible by 2 and 3 for BN254.
+    for alpha in [0, 1, 2, 3, 4, 6, 9] {
+        assert_eq!(
+            Poseidon2Parameters::new(
+                params.ark.clone(),
+                params.mat_internal_diag_m_1.clone(),
+                params.full_rounds,
+                params.partial_rounds,
+                3,
+                Sbox::Power(alpha)
+            ),
+            Err(PoseidonError::InvalidAlpha { alpha })
+        );
+    }
+}
+
+#[test]
+fn test_poseidon2_invalid_number_of_inputs() {
+    let hasher = Poseidon2::<Fr>::new_bn254(2).unwrap();
+    for nr_inputs in [0, 1, 3] {
+        let inputs = vec![Fr::one(); nr_inputs];
+        assert_eq!(
+            hasher.hash(&inputs),
+            Err(PoseidonError::InvalidNumberOfInputs {
+                inputs: nr_inputs,
+                max_limit: 2,
+                width: 3,
+            })
+        );
+    }
+
+    let mut state = [Fr::zero(); 2];
+    assert_eq!(
+        hasher.permute(&mut state),
+        Err(PoseidonError::InvalidStateLength { len: 2, width: 3 })
+    );
+}
@@ -0,0 +1,202 @@
+// The `MontConfig` derive emits code gated on the `asm` feature of ark-ff.
+#![allow(unexpected_cfgs)]
+
+use ark_bn254::Fr;
+use ark_ff::{
+    fields::{Fp64, MontBackend, MontConfig},
+    PrimeField, UniformRand, Zero,
+};
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef};
+use light_poseidon::{
+    grain,
+    parameters::{bn254_inverse, bn254_x5},
+    r1cs::PoseidonGadget,
+    Poseidon, PoseidonError, PoseidonHasher, Sbox,
+};
+
+/// A 63-bit field with a safe prime modulus (*p = 2q + 1*), where *x^3* is a
+/// permutation.
+#[derive(MontConfig)]
+#[modulus = "9223372036854771239"]
+#[generator = "11"]
+pub struct SafePrimeConfig;
+type SafePrimeField = Fp64<MontBackend<SafePrimeConfig, 1>>;
+
+fn witnesses<F: PrimeField>(cs: &ConstraintSystemRef<F>, values: &[F]) -> Vec<FpVar<F>> {
+    values
+        .iter()
+        .map(|value| FpVar::new_witness(cs.clone(), || Ok(*value)).unwrap())
+        .collect()
+}
+
+/// Number of constraints of circomlib's `Poseidon(nInputs)` template: 3 per
+/// S-box, except the S-box of the constant first element in the first round.
+fn circomlib_constraints(nr_inputs: usize) -> usize {
+    let width = nr_inputs + 1;
+    3 * (bn254_x5::FULL_ROUNDS * width + bn254_x5::PARTIAL_ROUNDS[width - 2]) - 3
+}
+
+#[test]
+fn test_gadget_matches_hasher() {
+    let mut rng = rand::thread_rng();
+    for nr_inputs in 1..13 {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let inputs: Vec<Fr> = (0..nr_inputs).map(|_| Fr::rand(&mut rng)).collect();
+
+        let gadget = PoseidonGadget::<Fr>::new_circom(nr_inputs).unwrap();
+        let hash = gadget.hash(&witnesses(&cs, &inputs)).unwrap();
+
+        let poseidon = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+        assert_eq!(hash.value().unwrap(), poseidon.hash(&inputs).unwrap());
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_constraints(), circomlib_constraints(nr_inputs));
+    }
+}
+
+/// Known constraint counts of circomlib's `Poseidon` template.
+#[test]
+fn test_circomlib_constraint_counts() {
+    for (nr_inputs, constraints) in [(1, 213), (2, 240)] {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let inputs = witnesses(&cs, &vec![Fr::zero(); nr_inputs]);
+        let _hash = PoseidonGadget::<Fr>::new_circom(nr_inputs)
+            .unwrap()
+            .hash(&inputs)
+            .unwrap();
+        assert_eq!(cs.num_constraints(), constraints);
+    }
+}
+
+#[test]
+fn test_gadget_permute() {
+    let mut rng = rand::thread_rng();
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let state: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+
+    let gadget = PoseidonGadget::<Fr>::new_circom(2).unwrap();
+    let mut state_vars = witnesses(&cs, &state);
+    gadget.permute(&mut state_vars).unwrap();
+
+    let mut expected = state.clone();
+    Poseidon::<Fr>::new_circom(2)
+        .unwrap()
+        .permute(&mut expected)
+        .unwrap();
+    assert_eq!(state_vars.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+    // No constant elements, so 3 constraints per every S-box.
+    assert_eq!(cs.num_constraints(), 3 * (8 * 3 + 57));
+
+    assert_eq!(
+        gadget.permute(&mut state_vars[..2]),
+        Err(PoseidonError::InvalidStateLength { len: 2, width: 3 })
+    );
+}
+
+#[test]
+fn test_gadget_domain_tag() {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+    let tag = Fr::from(42u64);
+
+    let gadget = PoseidonGadget::<Fr>::with_domain_tag_circom(2, tag).unwrap();
+    let hash = gadget.hash(&witnesses(&cs, &inputs)).unwrap();
+
+    let poseidon = Poseidon::<Fr>::with_domain_tag_circom(2, tag).unwrap();
+    assert_eq!(hash.value().unwrap(), poseidon.hash(&inputs).unwrap());
+    assert!(cs.is_satisfied().unwrap());
+}
+
+/// Permutes a random state with the gadget using *x^alpha* S-boxes and checks
+/// that every S-box takes `sbox_constraints` constraints.
+fn check_gadget_power_sbox<F: PrimeField>(alpha: u64, sbox_constraints: usize) {
+    let mut rng = rand::thread_rng();
+    let cs = ConstraintSystem::<F>::new_ref();
+    let state: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+    let params = grain::generate_parameters::<F>(3, 8, 57, Sbox::Power(alpha));
+
+    let mut expected = state.clone();
+    Poseidon::try_new(grain::generate_parameters::<F>(
+        3,
+        8,
+        57,
+        Sbox::Power(alpha),
+    ))
+    .unwrap()
+    .permute(&mut expected)
+    .unwrap();
+
+    let mut state_vars = witnesses(&cs, &state);
+    PoseidonGadget::new(params)
+        .permute(&mut state_vars)
+        .unwrap();
+    assert_eq!(state_vars.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.num_constraints(), sbox_constraints * (8 * 3 + 57));
+}
+
+#[test]
+fn test_gadget_sboxes() {
+    let mut rng = rand::thread_rng();
+    // Square-and-multiply chains: x^3 - 2, x^7 - 4, x^17 - 5 constraints.
+    // x^3 isn't a permutation of the BN254 scalar field.
+    check_gadget_power_sbox::<SafePrimeField>(3, 2);
+    check_gadget_power_sbox::<Fr>(7, 4);
+    check_gadget_power_sbox::<Fr>(17, 5);
+
+    // x^-1, including a zero element.
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let state = [Fr::zero(), Fr::rand(&mut rng), Fr::rand(&mut rng)];
+    let params = bn254_inverse::get_poseidon_parameters::<Fr>(3).unwrap();
+    let partial_rounds = params.partial_rounds;
+
+    let mut expected = state;
+    Poseidon::new(bn254_inverse::get_poseidon_parameters::<Fr>(3).unwrap())
+        .permute(&mut expected)
+        .unwrap();
+
+    let mut state_vars = witnesses(&cs, &state);
+    PoseidonGadget::new(params)
+        .permute(&mut state_vars)
+        .unwrap();
+    assert_eq!(state_vars.value().unwrap(), expected);
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.num_constraints(), 3 * (8 * 3 + partial_rounds));
+}
+
+/// A wrong witness makes the constraint system unsatisfied.
+#[test]
+fn test_gadget_unsatisfied() {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let inputs = witnesses(&cs, &[Fr::from(1u64), Fr::from(2u64)]);
+    let hash = PoseidonGadget::<Fr>::new_circom(2)
+        .unwrap()
+        .hash(&inputs)
+        .unwrap();
+
+    let claimed = FpVar::new_input(cs.clone(), || Ok(Fr::from(3u64))).unwrap();
+    ark_r1cs_std::eq::EqGadget::enforce_equal(&hash, &claimed).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_gadget_invalid_inputs() {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let gadget = PoseidonGadget::<Fr>::new_circom(2).unwrap();
+    assert_eq!(
+        gadget.hash(&witnesses(&cs, &[Fr::zero()])).err(),
+        Some(PoseidonError::InvalidNumberOfInputs {
+            inputs: 1,
+            max_limit: 2,
+            width: 3
+        })
+    );
+    assert!(matches!(
+        PoseidonGadget::<Fr>::new_circom(13),
+        Err(PoseidonError::InvalidWidthCircom {
+            width: 14,
+            max_limit: 13
+        })
+    ));
+}
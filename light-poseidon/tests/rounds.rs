@@ -0,0 +1,108 @@
+use ark_bn254::Fr;
+use light_poseidon::{
+    parameters::{bn254_x5, poseidon2_bn254_x5},
+    rounds, Sbox,
+};
+
+/// Checks whether the calculated round numbers reproduce the BN254 x^5
+/// parameters provided by the library.
+#[test]
+fn test_round_numbers_bn254_x5() {
+    for (i, expected) in bn254_x5::PARTIAL_ROUNDS.iter().enumerate() {
+        let width = i + 2;
+        assert_eq!(
+            rounds::calc_round_numbers_for_field_circom::<Fr>(
+                width,
+                Sbox::Power(bn254_x5::ALPHA),
+                128
+            ),
+            Some((bn254_x5::FULL_ROUNDS, *expected))
+        );
+    }
+}
+
+#[test]
+fn test_round_numbers_poseidon2_bn254_x5() {
+    for width in 2..5 {
+        assert_eq!(
+            rounds::calc_round_numbers_for_field::<Fr>(
+                width,
+                Sbox::Power(poseidon2_bn254_x5::ALPHA),
+                128
+            ),
+            Some((
+                poseidon2_bn254_x5::FULL_ROUNDS,
+                poseidon2_bn254_x5::PARTIAL_ROUNDS
+            ))
+        );
+    }
+}
+
+/// Checks the round numbers of other fields against the output of
+/// `calc_round_numbers.py`.
+#[test]
+fn test_round_numbers_other_fields() {
+    // Goldilocks (p = 2^64 - 2^32 + 1), as used by Plonky2.
+    assert_eq!(
+        rounds::calc_round_numbers(64.0, 12, Sbox::Power(7), 128, true),
+        Some((8, 22))
+    );
+    // A field of the size of BN254 with x^3 S-boxes, which aren't a
+    // permutation of BN254 itself.
+    assert_eq!(
+        rounds::calc_round_numbers(rounds::log2_modulus::<Fr>(), 3, Sbox::Power(3), 128, true),
+        Some((8, 83))
+    );
+}
+
+#[test]
+fn test_round_numbers_without_margin() {
+    let log2_p = rounds::log2_modulus::<Fr>();
+    assert!((log2_p - 253.5967).abs() < 0.0001);
+
+    let (full_rounds, partial_rounds) =
+        rounds::calc_round_numbers(log2_p, 3, Sbox::Power(5), 128, false).unwrap();
+    assert!(rounds::is_secure(
+        log2_p,
+        3,
+        full_rounds,
+        partial_rounds,
+        Sbox::Power(5),
+        128
+    ));
+    assert!(!rounds::is_secure(
+        log2_p,
+        3,
+        full_rounds,
+        partial_rounds - 1,
+        Sbox::Power(5),
+        128
+    ));
+    assert!(!rounds::is_secure(
+        log2_p,
+        3,
+        full_rounds - 2,
+        partial_rounds,
+        Sbox::Power(5),
+        128
+    ));
+
+    // The margin adds 2 full rounds and 7.5% of partial rounds.
+    assert_eq!(
+        rounds::calc_round_numbers(log2_p, 3, Sbox::Power(5), 128, true),
+        Some((
+            full_rounds + 2,
+            (partial_rounds as f64 * 1.075).ceil() as usize
+        ))
+    );
+}
+
+#[test]
+fn test_round_numbers_invalid_alpha() {
+    for alpha in [0, 1, 2] {
+        assert_eq!(
+            rounds::calc_round_numbers_for_field::<Fr>(3, Sbox::Power(alpha), 128),
+            None
+        );
+    }
+}
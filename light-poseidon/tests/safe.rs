@@ -0,0 +1,225 @@
+use ark_bn254::Fr;
+use ark_ff::{UniformRand, Zero};
+use light_poseidon::{
+    parameters::bn254_x5,
+    safe::{IoPattern, SafeSponge, SpongeOp, MAX_OP_LEN},
+    sponge::PoseidonSponge,
+    PoseidonError,
+};
+
+/// Tag values from the SAFE implementation of neptune (`sponge/api.rs`).
+#[test]
+fn test_io_pattern_tag_neptune() {
+    assert_eq!(IoPattern::new(vec![]).tag(0).unwrap(), 0);
+    assert_eq!(
+        IoPattern::new(vec![]).tag(123).unwrap(),
+        340282366920938463463374607431768191899
+    );
+
+    let pattern = IoPattern::new(vec![SpongeOp::Absorb(2), SpongeOp::Squeeze(2)]);
+    assert_eq!(
+        pattern.tag(0).unwrap(),
+        340282366920938463463374607090318361668
+    );
+    assert_eq!(
+        pattern.tag(1).unwrap(),
+        340282366920938463463374607090314341989
+    );
+
+    // Consecutive operations of the same kind are aggregated.
+    let split_absorb = IoPattern::new(vec![
+        SpongeOp::Absorb(1),
+        SpongeOp::Absorb(1),
+        SpongeOp::Squeeze(2),
+    ]);
+    let split_both = IoPattern::new(vec![
+        SpongeOp::Absorb(1),
+        SpongeOp::Absorb(1),
+        SpongeOp::Squeeze(1),
+        SpongeOp::Squeeze(1),
+    ]);
+    assert_eq!(split_absorb.tag(0).unwrap(), pattern.tag(0).unwrap());
+    assert_eq!(split_both.tag(0).unwrap(), pattern.tag(0).unwrap());
+}
+
+/// The tag is put in the capacity element, the rest of the computation is the
+/// same as with the plain sponge.
+#[test]
+fn test_safe_sponge_matches_sponge() {
+    let mut rng = rand::thread_rng();
+    let pattern = IoPattern::new(vec![
+        SpongeOp::Absorb(3),
+        SpongeOp::Squeeze(1),
+        SpongeOp::Absorb(1),
+        SpongeOp::Squeeze(3),
+    ]);
+    let tag = Fr::from(pattern.tag(7).unwrap());
+    let inputs: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+
+    let mut sponge = PoseidonSponge::<Fr>::with_domain_tag_circom(2, tag).unwrap();
+    sponge.absorb(&inputs[..3]);
+    let mut expected = sponge.squeeze(1);
+    sponge.absorb(&inputs[3..]);
+    expected.extend(sponge.squeeze(3));
+
+    let mut safe = SafeSponge::<Fr>::new_circom(2, pattern.clone(), 7).unwrap();
+    safe.absorb(&inputs[..3]).unwrap();
+    let mut outputs = safe.squeeze(1).unwrap();
+    safe.absorb(&inputs[3..]).unwrap();
+    outputs.extend(safe.squeeze(3).unwrap());
+    assert_eq!(safe.state(), sponge.state());
+    safe.finish().unwrap();
+    assert_eq!(outputs, expected);
+
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let mut safe = SafeSponge::new(params, 1, pattern, 7).unwrap();
+    safe.absorb(&inputs[..3]).unwrap();
+    assert_eq!(safe.squeeze(1).unwrap(), expected[..1]);
+}
+
+#[test]
+fn test_safe_sponge_domain_separation() {
+    let pattern = IoPattern::new(vec![SpongeOp::Absorb(2), SpongeOp::Squeeze(1)]);
+    let other_pattern = IoPattern::new(vec![
+        SpongeOp::Absorb(2),
+        SpongeOp::Squeeze(1),
+        SpongeOp::Squeeze(1),
+    ]);
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+
+    let hash = |pattern: IoPattern, domain_separator: u32| {
+        let mut sponge = SafeSponge::<Fr>::new_circom(2, pattern, domain_separator).unwrap();
+        assert_eq!(
+            sponge.state()[0],
+            Fr::from(sponge.pattern().tag(domain_separator).unwrap())
+        );
+        assert!(sponge.state()[1..].iter().all(|e| e.is_zero()));
+        sponge.absorb(&inputs).unwrap();
+        sponge.squeeze(1).unwrap()[0]
+    };
+
+    assert_ne!(hash(pattern.clone(), 0), hash(pattern.clone(), 1));
+    assert_ne!(hash(pattern, 0), hash(other_pattern, 0));
+}
+
+#[test]
+fn test_safe_sponge_pattern_violation() {
+    let pattern = IoPattern::new(vec![SpongeOp::Absorb(2), SpongeOp::Squeeze(1)]);
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+    let mut sponge = SafeSponge::<Fr>::new_circom(2, pattern, 0).unwrap();
+    let initial_state = sponge.state().to_vec();
+
+    // Squeezing before absorbing.
+    assert_eq!(
+        sponge.squeeze(1),
+        Err(PoseidonError::IoPatternViolation {
+            index: 0,
+            expected: "Absorb(2)".to_string(),
+            op: "Squeeze(1)".to_string(),
+        })
+    );
+    // Absorbing a different number of elements.
+    assert_eq!(
+        sponge.absorb(&inputs[..1]),
+        Err(PoseidonError::IoPatternViolation {
+            index: 0,
+            expected: "Absorb(2)".to_string(),
+            op: "Absorb(1)".to_string(),
+        })
+    );
+    // Failed calls do not modify the state.
+    assert_eq!(sponge.state(), initial_state);
+
+    sponge.absorb(&inputs).unwrap();
+    assert_eq!(
+        sponge.squeeze(2),
+        Err(PoseidonError::IoPatternViolation {
+            index: 1,
+            expected: "Squeeze(1)".to_string(),
+            op: "Squeeze(2)".to_string(),
+        })
+    );
+    sponge.squeeze(1).unwrap();
+
+    // Calls beyond the declared pattern.
+    assert_eq!(
+        sponge.squeeze(1),
+        Err(PoseidonError::IoPatternViolation {
+            index: 2,
+            expected: "end of the pattern".to_string(),
+            op: "Squeeze(1)".to_string(),
+        })
+    );
+    assert_eq!(sponge.finish(), Ok(()));
+}
+
+#[test]
+fn test_safe_sponge_not_finished() {
+    let pattern = IoPattern::new(vec![SpongeOp::Absorb(2), SpongeOp::Squeeze(1)]);
+    let mut sponge = SafeSponge::<Fr>::new_circom(2, pattern, 0).unwrap();
+    sponge.absorb(&[Fr::from(1u64), Fr::from(2u64)]).unwrap();
+    assert_eq!(
+        sponge.finish(),
+        Err(PoseidonError::IoPatternNotFinished {
+            performed: 1,
+            expected: 2
+        })
+    );
+}
+
+#[test]
+fn test_invalid_io_pattern() {
+    for (ops, index) in [
+        (vec![SpongeOp::Absorb(0)], 0),
+        (vec![SpongeOp::Absorb(1), SpongeOp::Squeeze(0)], 1),
+        (vec![SpongeOp::Absorb(1), SpongeOp::Squeeze(1 << 31)], 1),
+    ] {
+        let op = format!("{:?}", ops[index]);
+        assert_eq!(
+            SafeSponge::<Fr>::new_circom(2, IoPattern::new(ops), 0).err(),
+            Some(PoseidonError::InvalidIoPattern { index, op })
+        );
+    }
+}
+
+/// Aggregated lengths are checked as well. Three `ABSORB(MAX_OP_LEN)`
+/// overflow `u32`, while an aggregated `ABSORB(2^31)` would clash with the
+/// absorb flag in the encoding (and have the same tag as `ABSORB(0)`).
+#[test]
+fn test_invalid_io_pattern_aggregate() {
+    for (ops, index) in [
+        (vec![SpongeOp::Absorb(MAX_OP_LEN); 3], 1),
+        (vec![SpongeOp::Absorb(MAX_OP_LEN), SpongeOp::Absorb(1)], 1),
+        (
+            vec![
+                SpongeOp::Absorb(1),
+                SpongeOp::Squeeze(1 << 30),
+                SpongeOp::Squeeze(1 << 30),
+            ],
+            2,
+        ),
+    ] {
+        let pattern = IoPattern::new(ops);
+        assert_eq!(
+            pattern.validate(),
+            Err(PoseidonError::InvalidIoPatternAggregate {
+                index,
+                max_limit: MAX_OP_LEN,
+            })
+        );
+        assert_eq!(pattern.tag(0).err(), pattern.validate().err());
+        assert!(SafeSponge::<Fr>::new_circom(2, pattern, 0).is_err());
+    }
+
+    // The largest aggregated operation is still valid.
+    let pattern = IoPattern::new(vec![
+        SpongeOp::Absorb(MAX_OP_LEN - 1),
+        SpongeOp::Absorb(1),
+        SpongeOp::Squeeze(1),
+    ]);
+    assert_eq!(pattern.validate(), Ok(()));
+    assert_eq!(
+        pattern.tag(0),
+        IoPattern::new(vec![SpongeOp::Absorb(MAX_OP_LEN), SpongeOp::Squeeze(1)]).tag(0)
+    );
+}
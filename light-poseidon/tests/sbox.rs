@@ -0,0 +1,236 @@
+// The `MontConfig` derive emits code gated on the `asm` feature of ark-ff.
+#![allow(unexpected_cfgs)]
+
+use ark_bn254::Fr;
+use ark_ff::{
+    fields::{Fp64, MontBackend, MontConfig},
+    Field, PrimeField, UniformRand,
+};
+use light_poseidon::{
+    goldilocks::Goldilocks,
+    grain,
+    optimized::{self, OptimizedPoseidonParameters},
+    permute,
+    poseidon2::{self, Poseidon2, Poseidon2Parameters},
+    sbox, Poseidon, PoseidonHasher, PoseidonParameters, Sbox,
+};
+
+/// Exponents covering all specialized S-boxes and the generic fallback.
+const ALPHAS: [u64; 5] = [3, 5, 7, 11, 17];
+
+/// Exponents from [`ALPHAS`] which are permutations of the BN254 scalar field
+/// (`p - 1` is divisible by 3).
+const BN254_ALPHAS: [u64; 4] = [5, 7, 11, 17];
+
+/// A 63-bit field with a safe prime modulus (*p = 2q + 1*), where all the
+/// odd exponents from [`ALPHAS`] are permutations.
+#[derive(MontConfig)]
+#[modulus = "9223372036854771239"]
+#[generator = "11"]
+pub struct SafePrimeConfig;
+type SafePrimeField = Fp64<MontBackend<SafePrimeConfig, 1>>;
+
+fn mat_mul<F: PrimeField>(mat: &[Vec<F>], state: &[F]) -> Vec<F> {
+    mat.iter()
+        .map(|row| row.iter().zip(state).map(|(m, s)| *m * s).sum())
+        .collect()
+}
+
+fn naive_sbox<F: PrimeField>(x: F, sbox: Sbox) -> F {
+    match sbox {
+        Sbox::Power(alpha) => x.pow([alpha]),
+        Sbox::Inverse => x.inverse().unwrap_or_default(),
+    }
+}
+
+/// Textbook Poseidon permutation, computing the S-box with `Field::pow` and
+/// `Field::inverse`.
+fn naive_permute<F: PrimeField>(params: &PoseidonParameters<F>, state: &mut Vec<F>) {
+    let half_rounds = params.full_rounds / 2;
+    let all_rounds = params.full_rounds + params.partial_rounds;
+    for round in 0..all_rounds {
+        for (i, a) in state.iter_mut().enumerate() {
+            *a += params.ark[round * params.width + i];
+        }
+        if round < half_rounds || round >= half_rounds + params.partial_rounds {
+            for a in state.iter_mut() {
+                *a = naive_sbox(*a, params.sbox);
+            }
+        } else {
+            state[0] = naive_sbox(state[0], params.sbox);
+        }
+        *state = mat_mul(&params.mds, state);
+    }
+}
+
+/// Textbook Poseidon2 permutation, computing the S-box with `Field::pow` and
+/// `Field::inverse` and the linear layers with dense matrices.
+fn naive_permute_poseidon2<F: PrimeField>(params: &Poseidon2Parameters<F>, state: &mut Vec<F>) {
+    let mat_external = params.mat_external();
+    let mat_internal = params.mat_internal();
+    let width = params.width;
+    let half_rounds = params.full_rounds / 2;
+
+    *state = mat_mul(&mat_external, state);
+    let mut ark = params.ark.iter();
+    for round in 0..params.full_rounds + params.partial_rounds {
+        if round < half_rounds || round >= half_rounds + params.partial_rounds {
+            for a in state.iter_mut() {
+                *a = naive_sbox(*a + ark.next().unwrap(), params.sbox);
+            }
+            *state = mat_mul(&mat_external, state);
+        } else {
+            state[0] = naive_sbox(state[0] + ark.next().unwrap(), params.sbox);
+            *state = mat_mul(&mat_internal, state);
+        }
+    }
+    assert!(ark.next().is_none());
+    assert_eq!(state.len(), width);
+}
+
+#[test]
+fn test_specialized_power_maps() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let x = Fr::rand(&mut rng);
+        assert_eq!(sbox::pow3(x), x.pow([3]));
+        assert_eq!(sbox::pow5(x), x.pow([5]));
+        assert_eq!(sbox::pow7(x), x.pow([7]));
+        assert_eq!(sbox::pow17(x), x.pow([17]));
+
+        let x = Goldilocks::rand(&mut rng);
+        assert_eq!(sbox::pow7(x), x.pow([7]));
+    }
+    assert_eq!(sbox::SPECIALIZED_ALPHAS, [3, 5, 7, 17]);
+}
+
+fn check_permute_with_alphas<F: PrimeField>(alphas: &[u64]) {
+    let mut rng = rand::thread_rng();
+    for &alpha in alphas {
+        for width in [2, 3, 5] {
+            let params = grain::generate_parameters::<F>(width, 8, 57, Sbox::Power(alpha));
+            let optimized_params = OptimizedPoseidonParameters::new(&params).unwrap();
+            let poseidon = Poseidon::try_new(grain::generate_parameters::<F>(
+                width,
+                8,
+                57,
+                Sbox::Power(alpha),
+            ))
+            .unwrap();
+
+            let state: Vec<F> = (0..width).map(|_| F::rand(&mut rng)).collect();
+            let mut expected = state.clone();
+            naive_permute(&params, &mut expected);
+
+            let mut s = state.clone();
+            permute(&params, &mut s).unwrap();
+            assert_eq!(s, expected, "alpha {alpha}, width {width}");
+
+            let mut s = state.clone();
+            poseidon.permute(&mut s).unwrap();
+            assert_eq!(s, expected, "alpha {alpha}, width {width}");
+
+            let mut s = state.clone();
+            optimized::permute(&optimized_params, &mut s).unwrap();
+            assert_eq!(s, expected, "alpha {alpha}, width {width}");
+        }
+    }
+}
+
+#[test]
+fn test_permute_with_alphas() {
+    check_permute_with_alphas::<Fr>(&BN254_ALPHAS);
+    check_permute_with_alphas::<SafePrimeField>(&ALPHAS);
+}
+
+fn check_hash_with_alphas<F: PrimeField>(alphas: &[u64]) {
+    let mut rng = rand::thread_rng();
+    for &alpha in alphas {
+        let params = grain::generate_parameters::<F>(4, 8, 57, Sbox::Power(alpha));
+        let inputs: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+
+        let mut expected = vec![F::zero()];
+        expected.extend_from_slice(&inputs);
+        naive_permute(&params, &mut expected);
+
+        let poseidon = Poseidon::try_new(grain::generate_parameters::<F>(
+            4,
+            8,
+            57,
+            Sbox::Power(alpha),
+        ))
+        .unwrap();
+        assert_eq!(poseidon.hash(&inputs).unwrap(), expected[0]);
+
+        let optimized =
+            optimized::OptimizedPoseidon::new(OptimizedPoseidonParameters::new(&params).unwrap());
+        assert_eq!(optimized.hash(&inputs).unwrap(), expected[0]);
+    }
+}
+
+#[test]
+fn test_hash_with_alphas() {
+    check_hash_with_alphas::<Fr>(&BN254_ALPHAS);
+    check_hash_with_alphas::<SafePrimeField>(&ALPHAS);
+}
+
+fn check_poseidon2_with_alphas<F: PrimeField>(alphas: &[u64]) {
+    let mut rng = rand::thread_rng();
+    for &alpha in alphas {
+        for width in [2, 3, 4, 8] {
+            let ark = grain::generate_poseidon2_round_constants::<F>(width, 8, 56);
+            let diag: Vec<F> = (0..width).map(|_| F::rand(&mut rng)).collect();
+            let params =
+                Poseidon2Parameters::new(ark, diag, 8, 56, width, Sbox::Power(alpha)).unwrap();
+
+            let state: Vec<F> = (0..width).map(|_| F::rand(&mut rng)).collect();
+            let mut expected = state.clone();
+            naive_permute_poseidon2(&params, &mut expected);
+
+            let mut s = state.clone();
+            poseidon2::permute(&params, &mut s).unwrap();
+            assert_eq!(s, expected, "alpha {alpha}, width {width}");
+
+            let mut s = state.clone();
+            Poseidon2::new(params).permute(&mut s).unwrap();
+            assert_eq!(s, expected, "alpha {alpha}, width {width}");
+        }
+    }
+}
+
+#[test]
+fn test_poseidon2_with_alphas() {
+    check_poseidon2_with_alphas::<Fr>(&BN254_ALPHAS);
+    check_poseidon2_with_alphas::<SafePrimeField>(&ALPHAS);
+}
+
+/// Known answer test for the *x^7* S-box, generated with zkhash
+/// (`poseidon_instance_goldilocks.rs`, which seeds the Grain LFSR with the
+/// S-box type `1`) for the input `[0, 1, ..., 11]`.
+#[test]
+fn test_goldilocks_x7_zkhash() {
+    let params =
+        grain::generate_parameters_with_sbox_flag::<Goldilocks>(1, 12, 8, 22, Sbox::Power(7));
+
+    let mut state: Vec<Goldilocks> = (0..12u64).map(Goldilocks::from).collect();
+    Poseidon::new(params).permute(&mut state).unwrap();
+
+    let expected: Vec<Goldilocks> = [
+        0xe9ad770762f48ef5u64,
+        0xc12796961ddc7859,
+        0xa61b71de9595e016,
+        0xead9e6aa583aafa3,
+        0x93e297beff76e95b,
+        0x53abd3c5c2a0e924,
+        0xf3bc50e655c74f51,
+        0x246cac41b9a45d84,
+        0xcc7f9314b2341f4f,
+        0xf5f071587c83415c,
+        0x09486cf35116fba3,
+        0x9d82aaf136b5c38a,
+    ]
+    .into_iter()
+    .map(Goldilocks::from)
+    .collect();
+    assert_eq!(state, expected);
+}
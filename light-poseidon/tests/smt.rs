@@ -0,0 +1,269 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use ark_bn254::Fr;
+use ark_ff::{UniformRand, Zero};
+use light_poseidon::{
+    smt::{SparseMerkleProof, SparseMerkleTree},
+    Poseidon, PoseidonError, PoseidonHasher,
+};
+use rand::seq::SliceRandom;
+
+fn leaf(key: u64, value: u64) -> Fr {
+    Poseidon::<Fr>::new_circom(3)
+        .unwrap()
+        .hash(&[Fr::from(key), Fr::from(value), Fr::from(1u64)])
+        .unwrap()
+}
+
+fn node(left: Fr, right: Fr) -> Fr {
+    Poseidon::<Fr>::new_circom(2)
+        .unwrap()
+        .hash(&[left, right])
+        .unwrap()
+}
+
+/// Checks the layout of iden3 sparse Merkle trees: leaves are stored on the
+/// shallowest level with a unique path, empty subtrees are zero.
+#[test]
+fn test_smt_layout() {
+    let mut tree = SparseMerkleTree::new(10).unwrap();
+    assert_eq!(tree.root(), Fr::zero());
+
+    tree.insert(Fr::from(1u64), Fr::from(10u64)).unwrap();
+    assert_eq!(tree.root(), leaf(1, 10));
+
+    // The key 2 (0b10) goes left on the first level, the key 1 (0b01) right.
+    tree.insert(Fr::from(2u64), Fr::from(20u64)).unwrap();
+    assert_eq!(tree.root(), node(leaf(2, 20), leaf(1, 10)));
+
+    // The keys 1 (0b001) and 5 (0b101) share the first two bits.
+    tree.insert(Fr::from(5u64), Fr::from(50u64)).unwrap();
+    assert_eq!(
+        tree.root(),
+        node(
+            leaf(2, 20),
+            node(node(leaf(1, 10), leaf(5, 50)), Fr::zero())
+        )
+    );
+
+    tree.update(Fr::from(5u64), Fr::from(51u64)).unwrap();
+    assert_eq!(
+        tree.root(),
+        node(
+            leaf(2, 20),
+            node(node(leaf(1, 10), leaf(5, 51)), Fr::zero())
+        )
+    );
+}
+
+/// Checks the roots against the test vectors of iden3's go-merkletree-sql
+/// (`TestNewTree` in `merkletree_test.go`, generated with circomlib's
+/// `smt.js`).
+#[test]
+fn test_smt_go_merkletree_vectors() {
+    let mut tree = SparseMerkleTree::new(10).unwrap();
+    for (key, value, root) in [
+        (
+            1u64,
+            2u64,
+            "13578938674299138072471463694055224830892726234048532520316387704878000008795",
+        ),
+        (
+            33,
+            44,
+            "5412393676474193513566895793055462193090331607895808993925969873307089394741",
+        ),
+        (
+            1234,
+            9876,
+            "14204494359367183802864593755198662203838502594566452929175967972147978322084",
+        ),
+    ] {
+        tree.insert(Fr::from(key), Fr::from(value)).unwrap();
+        let root = Fr::from_str(root).unwrap();
+        assert_eq!(tree.root(), root);
+        assert!(tree
+            .proof(&Fr::from(key))
+            .unwrap()
+            .verify_inclusion(root, Fr::from(key), Fr::from(value))
+            .unwrap());
+    }
+}
+
+#[test]
+fn test_smt_matches_map() {
+    let mut rng = rand::thread_rng();
+    let mut tree = SparseMerkleTree::new(254).unwrap();
+    let mut map = BTreeMap::new();
+
+    for _ in 0..50 {
+        let key = Fr::rand(&mut rng);
+        let value = Fr::rand(&mut rng);
+        tree.insert(key, value).unwrap();
+        map.insert(key, value);
+    }
+    let keys: Vec<Fr> = map.keys().copied().collect();
+    for key in keys.iter().step_by(3) {
+        let value = Fr::rand(&mut rng);
+        tree.update(*key, value).unwrap();
+        map.insert(*key, value);
+    }
+
+    for (key, value) in map.iter() {
+        assert_eq!(tree.get(key).unwrap(), Some(*value));
+    }
+    assert_eq!(tree.get(&Fr::rand(&mut rng)).unwrap(), None);
+
+    // The root depends only on the contents, not on the order of insertion.
+    let mut entries: Vec<(Fr, Fr)> = map.into_iter().collect();
+    entries.shuffle(&mut rng);
+    let mut shuffled = SparseMerkleTree::new(254).unwrap();
+    for (key, value) in entries {
+        shuffled.insert(key, value).unwrap();
+    }
+    assert_eq!(shuffled.root(), tree.root());
+    // The nodes replaced by updates were pruned.
+    assert_eq!(shuffled.nr_nodes(), tree.nr_nodes());
+}
+
+#[test]
+fn test_smt_proofs() {
+    let mut rng = rand::thread_rng();
+    let mut tree = SparseMerkleTree::new(32).unwrap();
+    let entries: Vec<(Fr, Fr)> = (0..20u64)
+        .map(|key| (Fr::from(key * 8), Fr::rand(&mut rng)))
+        .collect();
+    for (key, value) in entries.iter() {
+        tree.insert(*key, *value).unwrap();
+    }
+    let root = tree.root();
+
+    for (key, value) in entries.iter() {
+        let proof = tree.proof(key).unwrap();
+        assert!(proof.existence);
+        assert!(proof.verify_inclusion(root, *key, *value).unwrap());
+        assert!(!proof.verify_inclusion(root, *key, Fr::zero()).unwrap());
+        assert!(!proof.verify_non_inclusion(root, *key).unwrap());
+    }
+
+    let (mut with_node_aux, mut without_node_aux) = (0, 0);
+    for key in (0..240u64)
+        .filter(|key| key % 8 != 0 || *key >= 160)
+        .map(Fr::from)
+    {
+        let proof = tree.proof(&key).unwrap();
+        assert!(!proof.existence);
+        assert!(proof.verify_non_inclusion(root, key).unwrap());
+        assert!(!proof.verify_inclusion(root, key, Fr::zero()).unwrap());
+        if proof.node_aux.is_some() {
+            with_node_aux += 1;
+        } else {
+            without_node_aux += 1;
+        }
+
+        // The auxiliary node cannot be replaced with the key itself.
+        let forged = SparseMerkleProof {
+            node_aux: Some((key, Fr::zero())),
+            ..proof
+        };
+        assert!(!forged.verify_non_inclusion(root, key).unwrap());
+    }
+    // Both kinds of non-inclusion proofs are covered.
+    assert!(with_node_aux > 0 && without_node_aux > 0);
+}
+
+#[test]
+fn test_smt_proof_serialization() {
+    let mut rng = rand::thread_rng();
+    let mut tree = SparseMerkleTree::new(254).unwrap();
+    for _ in 0..30 {
+        tree.insert(Fr::rand(&mut rng), Fr::rand(&mut rng)).unwrap();
+    }
+    tree.insert(Fr::from(0u64), Fr::from(1u64)).unwrap();
+
+    for key in [Fr::from(0u64), Fr::from(2u64), Fr::rand(&mut rng)] {
+        let proof = tree.proof(&key).unwrap();
+        let bytes = proof.to_bytes();
+        // Flags, length, bitmap, non-empty siblings and the auxiliary node.
+        let nr_elements = proof.siblings.iter().filter(|s| !s.is_zero()).count()
+            + 2 * proof.node_aux.is_some() as usize;
+        assert_eq!(
+            bytes.len(),
+            2 + proof.siblings.len().div_ceil(8) + 32 * nr_elements
+        );
+        assert_eq!(SparseMerkleProof::from_bytes(&bytes).unwrap(), proof);
+    }
+
+    // A deep path with mostly empty siblings.
+    let mut tree = SparseMerkleTree::new(254).unwrap();
+    tree.insert(Fr::from(1u64), Fr::from(1u64)).unwrap();
+    tree.insert(Fr::from(1u64 + (1 << 40)), Fr::from(1u64))
+        .unwrap();
+    let proof = tree.proof(&Fr::from(1u64)).unwrap();
+    assert_eq!(proof.siblings.len(), 41);
+    let bytes = proof.to_bytes();
+    assert_eq!(bytes.len(), 2 + 6 + 32);
+    assert_eq!(SparseMerkleProof::from_bytes(&bytes).unwrap(), proof);
+
+    // Invalid encodings.
+    let invalid = Err(PoseidonError::InvalidSparseMerkleProof);
+    assert_eq!(SparseMerkleProof::from_bytes(&[]), invalid);
+    assert_eq!(
+        SparseMerkleProof::from_bytes(&bytes[..bytes.len() - 1]),
+        invalid
+    );
+    assert_eq!(
+        SparseMerkleProof::from_bytes(&[bytes.clone(), vec![0]].concat()),
+        invalid
+    );
+    let mut unknown_flags = bytes.clone();
+    unknown_flags[0] |= 1 << 2;
+    assert_eq!(SparseMerkleProof::from_bytes(&unknown_flags), invalid);
+    let mut padding_bit = bytes.clone();
+    padding_bit[7] |= 1 << 7;
+    assert_eq!(SparseMerkleProof::from_bytes(&padding_bit), invalid);
+    let mut above_modulus = bytes.clone();
+    above_modulus[8..].fill(0xff);
+    assert_eq!(
+        SparseMerkleProof::from_bytes(&above_modulus),
+        Err(PoseidonError::InputLargerThanModulus)
+    );
+}
+
+#[test]
+fn test_smt_errors() {
+    assert!(matches!(
+        SparseMerkleTree::new(0),
+        Err(PoseidonError::InvalidMerkleTreeHeight {
+            height: 0,
+            max_limit: 254
+        })
+    ));
+    assert!(matches!(
+        SparseMerkleTree::new(255),
+        Err(PoseidonError::InvalidMerkleTreeHeight {
+            height: 255,
+            max_limit: 254
+        })
+    ));
+
+    let mut tree = SparseMerkleTree::new(2).unwrap();
+    tree.insert(Fr::from(1u64), Fr::from(1u64)).unwrap();
+    let root = tree.root();
+    assert_eq!(
+        tree.insert(Fr::from(1u64), Fr::from(2u64)),
+        Err(PoseidonError::SparseMerkleKeyExists)
+    );
+    assert_eq!(
+        tree.update(Fr::from(2u64), Fr::from(2u64)),
+        Err(PoseidonError::SparseMerkleKeyNotFound)
+    );
+    // The keys 1 (0b001) and 5 (0b101) share the path of depth 2.
+    assert_eq!(
+        tree.insert(Fr::from(5u64), Fr::from(5u64)),
+        Err(PoseidonError::SparseMerkleMaxDepth { depth: 2 })
+    );
+    // Failed operations do not modify the tree.
+    assert_eq!(tree.root(), root);
+    assert_eq!(tree.get(&Fr::from(1u64)).unwrap(), Some(Fr::from(1u64)));
+}
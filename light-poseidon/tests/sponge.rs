@@ -0,0 +1,383 @@
+// The `MontConfig` derive emits code gated on the `asm` feature of ark-ff.
+#![allow(unexpected_cfgs)]
+
+use ark_bn254::Fr;
+use ark_ff::{
+    fields::{Fp64, MontBackend, MontConfig},
+    BigInteger, One, PrimeField, UniformRand, Zero,
+};
+use light_poseidon::{
+    goldilocks::Goldilocks,
+    parameters::{bn254_x5, goldilocks_x7},
+    sponge::{self, PoseidonSponge},
+    Poseidon, PoseidonError, PoseidonHasher,
+};
+use rand::Rng;
+
+/// Checks that absorbing `width - 1` inputs into a sponge with capacity 1
+/// permutes the same state as the circom-compatible hash, so the first
+/// element of the state after squeezing is equal to the hash.
+#[test]
+fn test_sponge_state_matches_hash() {
+    let mut rng = rand::thread_rng();
+    for rate in 1..13 {
+        let inputs: Vec<Fr> = (0..rate).map(|_| Fr::rand(&mut rng)).collect();
+
+        let hasher = Poseidon::<Fr>::new_circom(rate).unwrap();
+        let hash = hasher.hash(&inputs).unwrap();
+
+        let mut sponge = PoseidonSponge::<Fr>::new_circom(rate).unwrap();
+        sponge.absorb(&inputs);
+        let output = sponge.squeeze(1);
+
+        assert_eq!(sponge.state()[0], hash);
+        assert_eq!(output[0], sponge.state()[1]);
+    }
+}
+
+/// Checks the output of the sponge for inputs `1` and `2`, where the first
+/// element of the state matches the circom hash of the same inputs.
+#[test]
+fn test_sponge_input_one_two() {
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    sponge.absorb(&[Fr::from(1u64), Fr::from(2u64)]);
+    sponge.squeeze(1);
+
+    assert_eq!(
+        sponge.state()[0].into_bigint().to_bytes_le(),
+        [
+            154, 24, 23, 68, 122, 96, 25, 158, 81, 69, 50, 116, 242, 23, 54, 42, 207, 233, 98, 150,
+            107, 76, 246, 61, 65, 144, 214, 231, 245, 192, 92, 17
+        ]
+    );
+}
+
+#[test]
+fn test_sponge_absorb_in_chunks() {
+    let mut rng = rand::thread_rng();
+    let inputs: Vec<Fr> = (0..17).map(|_| Fr::rand(&mut rng)).collect();
+
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(4).unwrap();
+    sponge.absorb(&inputs);
+    let expected = sponge.squeeze(3);
+
+    for split in [0, 1, 3, 4, 5, 8, 16, 17] {
+        let mut sponge = PoseidonSponge::<Fr>::new_circom(4).unwrap();
+        sponge.absorb(&inputs[..split]);
+        sponge.absorb(&[]);
+        sponge.absorb(&inputs[split..]);
+        assert_eq!(sponge.squeeze(3), expected);
+    }
+}
+
+#[test]
+fn test_sponge_squeeze_in_chunks() {
+    let inputs = [Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)];
+
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    sponge.absorb(&inputs);
+    let expected = sponge.squeeze(7);
+    assert_eq!(expected.len(), 7);
+
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    sponge.absorb(&inputs);
+    let mut output = sponge.squeeze(1);
+    output.extend(sponge.squeeze(0));
+    output.extend(sponge.squeeze(3));
+    output.extend(sponge.squeeze(3));
+    assert_eq!(output, expected);
+
+    // Every output element within a rate part has to be different.
+    for i in 0..expected.len() {
+        for j in i + 1..expected.len() {
+            assert_ne!(expected[i], expected[j]);
+        }
+    }
+}
+
+/// Checks that squeezing in between absorbs (duplexing) produces different
+/// outputs than absorbing all the inputs at once.
+#[test]
+fn test_sponge_duplex() {
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    sponge.absorb(&[Fr::from(1u64)]);
+    let first = sponge.squeeze(1);
+    sponge.absorb(&[Fr::from(2u64)]);
+    let second = sponge.squeeze(1);
+
+    let mut other = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    other.absorb(&[Fr::from(1u64), Fr::from(2u64)]);
+    let joined = other.squeeze(2);
+
+    assert_ne!(first[0], second[0]);
+    assert_ne!(second[0], joined[1]);
+    assert_ne!(first[0], joined[0]);
+}
+
+#[test]
+fn test_sponge_permute() {
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    sponge.absorb(&inputs);
+    sponge.permute();
+    sponge.absorb(&inputs);
+    let output = sponge.squeeze(1);
+
+    let mut other = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    other.absorb(&inputs);
+    other.absorb(&inputs);
+    assert_eq!(other.squeeze(1), output);
+
+    let mut other = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    other.absorb(&inputs);
+    other.permute();
+    assert_ne!(other.squeeze(1), output);
+}
+
+/// An explicit permutation is not repeated by the following squeeze, which
+/// returns the rate part of the permuted state.
+#[test]
+fn test_sponge_permute_then_squeeze() {
+    let inputs = [Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)];
+
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(3).unwrap();
+    sponge.absorb(&inputs);
+    let mut expected = sponge.state().to_vec();
+    Poseidon::<Fr>::new_circom(3)
+        .unwrap()
+        .permute(&mut expected)
+        .unwrap();
+
+    sponge.permute();
+    assert_eq!(sponge.state(), expected);
+    assert_eq!(sponge.squeeze(3), expected[1..]);
+}
+
+#[test]
+fn test_sponge_with_domain_tag() {
+    let inputs = [Fr::from(1u64), Fr::from(2u64)];
+
+    let mut sponge = PoseidonSponge::<Fr>::with_domain_tag_circom(2, Fr::zero()).unwrap();
+    sponge.absorb(&inputs);
+    let expected_tag_zero = sponge.squeeze(1);
+
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    sponge.absorb(&inputs);
+    assert_eq!(sponge.squeeze(1), expected_tag_zero);
+
+    let mut sponge = PoseidonSponge::<Fr>::with_domain_tag_circom(2, Fr::one()).unwrap();
+    sponge.absorb(&inputs);
+    assert_ne!(sponge.squeeze(1), expected_tag_zero);
+}
+
+#[test]
+fn test_sponge_capacity() {
+    let mut rng = rand::thread_rng();
+    let inputs: Vec<Fr> = (0..10).map(|_| Fr::rand(&mut rng)).collect();
+
+    for capacity in 1..5 {
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(5).unwrap();
+        let mut sponge = PoseidonSponge::new(params, capacity).unwrap();
+        assert_eq!(sponge.capacity(), capacity);
+        assert_eq!(sponge.rate(), 5 - capacity);
+
+        sponge.absorb(&inputs);
+        let output = sponge.squeeze(4);
+        assert_eq!(output.len(), 4);
+    }
+}
+
+#[test]
+fn test_sponge_invalid_capacity() {
+    for capacity in [0, 3, 4] {
+        let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+        assert!(matches!(
+            PoseidonSponge::new(params, capacity),
+            Err(PoseidonError::InvalidCapacity {
+                capacity: c,
+                max_limit: 2,
+                width: 3,
+            }) if c == capacity
+        ));
+    }
+}
+
+#[test]
+fn test_sponge_circom_invalid_rate() {
+    assert!(matches!(
+        PoseidonSponge::<Fr>::new_circom(0),
+        Err(PoseidonError::InvalidWidthCircom { .. })
+    ));
+    assert!(matches!(
+        PoseidonSponge::<Fr>::new_circom(13),
+        Err(PoseidonError::InvalidWidthCircom {
+            width: 14,
+            max_limit: 13,
+        })
+    ));
+}
+
+#[test]
+fn test_bytes_to_limbs() {
+    assert_eq!(sponge::limb_bytes_len::<Fr>(), 31);
+    assert_eq!(sponge::limb_bytes_len::<ark_bls12_381::Fr>(), 31);
+
+    // Only the padding.
+    let mut padding = [0u8; 31];
+    padding[0] = 1;
+    assert_eq!(
+        sponge::bytes_to_limbs::<Fr>(&[]).unwrap(),
+        [Fr::from_be_bytes_mod_order(&padding)]
+    );
+
+    // The padding of an input filling the whole limb is a new limb.
+    let limbs = sponge::bytes_to_limbs::<Fr>(&[0xff; 31]).unwrap();
+    assert_eq!(
+        limbs,
+        [
+            Fr::from_be_bytes_mod_order(&[0xff; 31]),
+            Fr::from_be_bytes_mod_order(&padding)
+        ]
+    );
+
+    for len in 0..100 {
+        let limbs = sponge::bytes_to_limbs::<Fr>(&vec![0xff; len]).unwrap();
+        assert_eq!(limbs.len(), len / 31 + 1);
+        assert!(!limbs.last().unwrap().is_zero());
+    }
+}
+
+/// Checks whether the hash is the same as absorbing the padded limbs and
+/// squeezing one element.
+#[test]
+fn test_hash_bytes_var_matches_sponge() {
+    let mut rng = rand::thread_rng();
+    for rate in 1..13 {
+        for len in [0, 1, 30, 31, 32, 100, 31 * rate, 31 * rate + 1] {
+            let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+
+            let mut sponge = PoseidonSponge::<Fr>::new_circom(rate).unwrap();
+            sponge.absorb(&sponge::bytes_to_limbs::<Fr>(&input).unwrap());
+            let expected = sponge.squeeze(1)[0].into_bigint().to_bytes_be();
+
+            let sponge = PoseidonSponge::<Fr>::new_circom(rate).unwrap();
+            assert_eq!(sponge.hash_bytes_var(&input).unwrap().to_vec(), expected);
+        }
+    }
+}
+
+/// Inputs differing only in trailing (or leading) zeros, which would collide
+/// with zero padding, have different hashes.
+#[test]
+fn test_hash_bytes_var_no_trailing_zeros_collision() {
+    let sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+
+    let mut inputs: Vec<Vec<u8>> = Vec::new();
+    for len in 0..=93 {
+        inputs.push(vec![0; len]);
+        inputs.push([vec![1], vec![0; len]].concat());
+        inputs.push([vec![0; len], vec![1]].concat());
+    }
+    inputs.sort();
+    inputs.dedup();
+
+    let mut hashes: Vec<[u8; 32]> = inputs
+        .iter()
+        .map(|input| sponge.hash_bytes_var(input).unwrap())
+        .collect();
+    hashes.sort();
+    hashes.dedup();
+    assert_eq!(hashes.len(), inputs.len());
+
+    // The padding byte itself is not a collision either.
+    assert_ne!(
+        sponge.hash_bytes_var(&[]).unwrap(),
+        sponge.hash_bytes_var(&[1]).unwrap()
+    );
+    assert_ne!(
+        sponge.hash_bytes_var(&[0xab]).unwrap(),
+        sponge.hash_bytes_var(&[0xab, 1]).unwrap()
+    );
+}
+
+/// The hash doesn't depend on what was absorbed before, but it depends on the
+/// domain tag.
+#[test]
+fn test_hash_bytes_var_fresh_state() {
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    let hash = sponge.hash_bytes_var(b"light-poseidon").unwrap();
+
+    sponge.absorb(&[Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)]);
+    assert_eq!(sponge.hash_bytes_var(b"light-poseidon").unwrap(), hash);
+    assert_eq!(sponge.hash_bytes_var(b"light-poseidon").unwrap(), hash);
+
+    let sponge = PoseidonSponge::<Fr>::with_domain_tag_circom(2, Fr::one()).unwrap();
+    assert_ne!(sponge.hash_bytes_var(b"light-poseidon").unwrap(), hash);
+}
+
+/// Hashing bytes in the middle of absorbing and squeezing doesn't change the
+/// state of the sponge.
+#[test]
+fn test_hash_bytes_var_interleaved() {
+    let inputs = [Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)];
+
+    let mut expected = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    expected.absorb(&inputs);
+    let expected_output = expected.squeeze(3);
+
+    let mut sponge = PoseidonSponge::<Fr>::new_circom(2).unwrap();
+    sponge.absorb(&inputs);
+    let state = sponge.state().to_vec();
+    sponge.hash_bytes_var(b"light-poseidon").unwrap();
+    assert_eq!(sponge.state(), state);
+    assert_eq!(sponge.squeeze(1), expected_output[..1]);
+    sponge.hash_bytes_var(b"light-poseidon").unwrap();
+    assert_eq!(sponge.squeeze(2), expected_output[1..]);
+    assert_eq!(sponge.state(), expected.state());
+}
+
+#[derive(MontConfig)]
+#[modulus = "251"]
+#[generator = "6"]
+struct F251Config;
+type F251 = Fp64<MontBackend<F251Config, 1>>;
+
+/// Fields with a modulus of at most 8 bits can't hold a whole byte.
+#[test]
+fn test_bytes_to_limbs_small_field() {
+    assert_eq!(sponge::limb_bytes_len::<F251>(), 0);
+    assert_eq!(
+        sponge::bytes_to_limbs::<F251>(b"light-poseidon"),
+        Err(PoseidonError::UnsupportedPrimeField {
+            modulus_bit_size: 8
+        })
+    );
+
+    assert_eq!(sponge::limb_bytes_len::<Goldilocks>(), 7);
+    assert_eq!(
+        sponge::bytes_to_limbs::<Goldilocks>(&[0xff; 7]).unwrap(),
+        [
+            Goldilocks::from(0xff_ffff_ffff_ffffu64),
+            Goldilocks::from(0x01_0000_0000_0000u64)
+        ]
+    );
+}
+
+/// The hash of fields which are not serialized into 32 bytes can't be
+/// returned as an array, so they're rejected.
+#[test]
+fn test_hash_bytes_var_unsupported_field() {
+    let params = goldilocks_x7::get_poseidon_parameters::<Goldilocks>(12).unwrap();
+    let mut sponge = PoseidonSponge::new(params, 4).unwrap();
+    sponge.absorb(&[Goldilocks::one()]);
+    let state = sponge.state().to_vec();
+
+    assert_eq!(
+        sponge.hash_bytes_var(b"light-poseidon"),
+        Err(PoseidonError::UnsupportedPrimeField {
+            modulus_bit_size: 64
+        })
+    );
+    assert_eq!(sponge.state(), state);
+}
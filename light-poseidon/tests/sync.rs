@@ -0,0 +1,74 @@
+use std::{sync::OnceLock, thread};
+
+use ark_bn254::Fr;
+use ark_ff::UniformRand;
+use light_poseidon::{
+    borrowed::BorrowedPoseidon, fixed::FixedPoseidon, grain, halo2::Halo2Poseidon,
+    optimized::OptimizedPoseidon, pasta, permute, plonky2::Plonky2Poseidon, poseidon2::Poseidon2,
+    Poseidon, PoseidonHasher, Sbox,
+};
+
+static POSEIDON: OnceLock<Poseidon<Fr>> = OnceLock::new();
+
+fn poseidon() -> &'static Poseidon<Fr> {
+    POSEIDON.get_or_init(|| Poseidon::<Fr>::new_circom(2).unwrap())
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_hashers_send_sync() {
+    assert_send_sync::<Poseidon<Fr>>();
+    assert_send_sync::<OptimizedPoseidon<Fr>>();
+    assert_send_sync::<BorrowedPoseidon<'static, Fr>>();
+    assert_send_sync::<FixedPoseidon<'static, Fr, 3>>();
+    assert_send_sync::<Poseidon2<Fr>>();
+    assert_send_sync::<Halo2Poseidon<pasta::Fp>>();
+    assert_send_sync::<Plonky2Poseidon<Fr>>();
+}
+
+/// Hashes with one `static` hasher from multiple threads at once.
+#[test]
+fn test_poseidon_shared_between_threads() {
+    let mut rng = rand::thread_rng();
+    let inputs: Vec<[Fr; 2]> = (0..64)
+        .map(|_| [Fr::rand(&mut rng), Fr::rand(&mut rng)])
+        .collect();
+    let expected: Vec<Fr> = inputs
+        .iter()
+        .map(|input| Poseidon::<Fr>::new_circom(2).unwrap().hash(input).unwrap())
+        .collect();
+
+    let hashes: Vec<Fr> = thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .chunks(8)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|input| poseidon().hash(input).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(hashes, expected);
+}
+
+/// States wider than the stack buffer are allocated.
+#[test]
+fn test_poseidon_wide_state() {
+    let params = grain::generate_parameters::<Fr>(20, 8, 60, Sbox::Power(5));
+    let poseidon = Poseidon::new(grain::generate_parameters::<Fr>(20, 8, 60, Sbox::Power(5)));
+
+    let inputs: Vec<Fr> = (0..19u64).map(Fr::from).collect();
+    let mut state = [vec![Fr::from(0u64)], inputs.clone()].concat();
+    permute(&params, &mut state).unwrap();
+
+    assert_eq!(poseidon.hash(&inputs).unwrap(), state[0]);
+}
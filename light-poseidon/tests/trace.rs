@@ -0,0 +1,125 @@
+use ark_bn254::Fr;
+use ark_ff::{Field, UniformRand, Zero};
+use light_poseidon::{
+    parameters::{bn254_inverse, bn254_x5},
+    Poseidon, PoseidonError, PoseidonHasher,
+};
+
+#[test]
+fn test_trace_matches_permutation() {
+    let mut rng = rand::thread_rng();
+    for nr_inputs in 1..13 {
+        let poseidon = Poseidon::<Fr>::new_circom(nr_inputs).unwrap();
+        let width = nr_inputs + 1;
+        let state: Vec<Fr> = (0..width).map(|_| Fr::rand(&mut rng)).collect();
+
+        let mut expected = state.clone();
+        poseidon.permute(&mut expected).unwrap();
+
+        let mut traced = state.clone();
+        let trace = poseidon.permute_with_trace(&mut traced).unwrap();
+        assert_eq!(traced, expected);
+        assert_eq!(trace.input, state);
+        assert_eq!(trace.output(), expected);
+        assert_eq!(
+            trace.rounds.len(),
+            bn254_x5::FULL_ROUNDS + bn254_x5::PARTIAL_ROUNDS[width - 2]
+        );
+    }
+}
+
+/// Checks every step of every round against the parameters.
+#[test]
+fn test_trace_steps() {
+    let mut rng = rand::thread_rng();
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(3).unwrap();
+    let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    let mut state: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+    let trace = poseidon.permute_with_trace(&mut state).unwrap();
+
+    let mut previous = trace.input.clone();
+    for (i, round) in trace.rounds.iter().enumerate() {
+        assert_eq!(round.round, i);
+        assert_eq!(round.full, !(4..61).contains(&i));
+
+        for (j, previous) in previous.iter().enumerate() {
+            assert_eq!(round.after_ark[j], *previous + params.ark[i * 3 + j]);
+            let sbox = if round.full || j == 0 {
+                round.after_ark[j].pow([5])
+            } else {
+                round.after_ark[j]
+            };
+            assert_eq!(round.after_sbox[j], sbox);
+            let mds: Fr = (0..3).map(|k| params.mds[j][k] * round.after_sbox[k]).sum();
+            assert_eq!(round.after_mds[j], mds);
+        }
+        previous = round.after_mds.clone();
+    }
+}
+
+#[test]
+fn test_hash_with_trace() {
+    let mut rng = rand::thread_rng();
+    let inputs: Vec<Fr> = (0..2).map(|_| Fr::rand(&mut rng)).collect();
+    let tag = Fr::from(7u64);
+
+    let poseidon = Poseidon::<Fr>::with_domain_tag_circom(2, tag).unwrap();
+    let (hash, trace) = poseidon.hash_with_trace(&inputs).unwrap();
+    assert_eq!(hash, poseidon.hash(&inputs).unwrap());
+    assert_eq!(trace.input, [tag, inputs[0], inputs[1]]);
+    assert_eq!(trace.output()[0], hash);
+
+    // The x^-1 S-box.
+    let poseidon = Poseidon::new(bn254_inverse::get_poseidon_parameters::<Fr>(3).unwrap());
+    let (hash, _) = poseidon.hash_with_trace(&inputs).unwrap();
+    assert_eq!(hash, poseidon.hash(&inputs).unwrap());
+
+    assert_eq!(
+        poseidon.hash_with_trace(&inputs[..1]).err(),
+        Some(PoseidonError::InvalidNumberOfInputs {
+            inputs: 1,
+            max_limit: 2,
+            width: 3
+        })
+    );
+    assert_eq!(
+        poseidon.permute_with_trace(&mut [Fr::zero(); 4]).err(),
+        Some(PoseidonError::InvalidStateLength { len: 4, width: 3 })
+    );
+}
+
+#[test]
+fn test_trace_json() {
+    let poseidon = Poseidon::<Fr>::new_circom(2).unwrap();
+    let (hash, trace) = poseidon
+        .hash_with_trace(&[Fr::from(1u64), Fr::from(2u64)])
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&trace.to_json()).unwrap();
+    let decimal = |element: &Fr| element.to_string();
+
+    assert_eq!(json["input"], serde_json::json!(["0", "1", "2"]));
+    let rounds = json["rounds"].as_array().unwrap();
+    assert_eq!(rounds.len(), trace.rounds.len());
+    for (round, expected) in rounds.iter().zip(trace.rounds.iter()) {
+        assert_eq!(round["round"], expected.round);
+        assert_eq!(
+            round["type"],
+            if expected.full { "full" } else { "partial" }
+        );
+        for (key, states) in [
+            ("after_ark", &expected.after_ark),
+            ("after_sbox", &expected.after_sbox),
+            ("after_mds", &expected.after_mds),
+        ] {
+            let states: Vec<String> = states.iter().map(decimal).collect();
+            assert_eq!(round[key], serde_json::json!(states));
+        }
+    }
+    // Poseidon([1, 2]) from circomlibjs.
+    assert_eq!(
+        json["output"][0],
+        "7853200120776062878684798364095072458815029376092732009249414926327459813530"
+    );
+    assert_eq!(json["output"][0], decimal(&hash));
+}
@@ -0,0 +1,24 @@
+//! Uses the hashers of light-poseidon which don't allocate, so CI can check
+//! that they build on `no_std` targets without the `alloc` feature.
+#![no_std]
+
+use ark_bn254::Fr;
+use light_poseidon::{
+    borrowed::BorrowedPoseidon, fixed::FixedPoseidon, PoseidonBytesHasher, PoseidonError,
+    PoseidonHasher, HASH_LEN,
+};
+
+/// Hashes two big-endian inputs with [`FixedPoseidon`].
+pub fn hash_fixed(left: &[u8], right: &[u8]) -> Result<[u8; HASH_LEN], PoseidonError> {
+    FixedPoseidon::<Fr, 3>::new_circom()?.hash_bytes_be(&[left, right])
+}
+
+/// Hashes two big-endian inputs with [`BorrowedPoseidon`].
+pub fn hash_borrowed(left: &[u8], right: &[u8]) -> Result<[u8; HASH_LEN], PoseidonError> {
+    BorrowedPoseidon::new_circom(2)?.hash_bytes_be(&[left, right])
+}
+
+/// Hashes two prime field elements with [`BorrowedPoseidon`].
+pub fn hash_elements(left: Fr, right: Fr) -> Result<Fr, PoseidonError> {
+    BorrowedPoseidon::new_circom(2)?.hash(&[left, right])
+}
@@ -0,0 +1,26 @@
+use std::str::FromStr;
+
+use ark_bn254::Fr;
+use no_std_check::{hash_borrowed, hash_elements, hash_fixed};
+
+/// The same hash as `Poseidon::<Fr>::new_circom(2)` (see the `bn254_x5`
+/// tests), computed without the `alloc` feature of light-poseidon when this
+/// crate is tested on its own.
+#[test]
+fn test_hash_without_alloc() {
+    let expected = [
+        13, 84, 225, 147, 143, 138, 140, 28, 125, 235, 94, 3, 85, 242, 99, 25, 32, 123, 132, 254,
+        156, 162, 206, 27, 38, 231, 53, 200, 41, 130, 25, 144,
+    ];
+    assert_eq!(hash_fixed(&[1u8; 32], &[2u8; 32]).unwrap(), expected);
+    assert_eq!(hash_borrowed(&[1u8; 32], &[2u8; 32]).unwrap(), expected);
+
+    assert!(hash_fixed(&[1u8; 31], &[2u8; 32]).is_err());
+    assert_eq!(
+        hash_elements(Fr::from(1u64), Fr::from(2u64)).unwrap(),
+        Fr::from_str(
+            "7853200120776062878684798364095072458815029376092732009249414926327459813530"
+        )
+        .unwrap()
+    );
+}
@@ -42,67 +42,105 @@ fn rustfmt(code: String) -> Result<Vec<u8>, anyhow::Error> {
 
     Ok(formatted_code)
 }
-use std::path::Path;
-
-const FIELD: &str = "1";
-const SBOX: &str = "0";
-const FIELD_ELEMENT_BIT_SIZE: &str = "254";
-const FULL_ROUNDS: &str = "8";
-const PARTIAL_ROUNDS: [u8; 12] = [56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65];
-const MODULUS_HEX: &str = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
-#[allow(clippy::needless_return)]
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, BigInteger256, PrimeField};
+use light_poseidon::{goldilocks::Goldilocks, grain, pasta, rounds, Sbox};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const FULL_ROUNDS: usize = 8;
+const ALPHA: u64 = 5;
+const SECURITY_LEVEL: u32 = 128;
+/// Number of partial rounds published in the generated file. Constants are
+/// generated only for *t ≤ 13*.
+const PARTIAL_ROUNDS_LEN: usize = 15;
+const POSEIDON2_PARTIAL_ROUNDS: usize = 56;
+const POSEIDON2_WIDTHS: [usize; 3] = [2, 3, 4];
+/// Plonky2 uses a single width for Goldilocks.
+const GOLDILOCKS_WIDTH: usize = 12;
+const GOLDILOCKS_FULL_ROUNDS: usize = 8;
+const GOLDILOCKS_PARTIAL_ROUNDS: usize = 22;
+const GOLDILOCKS_ALPHA: u64 = 7;
+/// First row of the circulant part of the Plonky2 MDS matrix.
+const GOLDILOCKS_MDS_CIRC: [u64; GOLDILOCKS_WIDTH] =
+    [17, 15, 41, 16, 2, 28, 13, 13, 39, 18, 34, 20];
+/// Diagonal part of the Plonky2 MDS matrix.
+const GOLDILOCKS_MDS_DIAG: [u64; GOLDILOCKS_WIDTH] = [8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+const HALO2_SBOX_FLAG_NOTE: &str = "
+    //!
+    //! The parameters for *t = 3* are the same as the `P128Pow5T3`
+    //! parameters of [halo2_gadgets](https://github.com/zcash/halo2), which
+    //! use the S-box type `0` in the LFSR seed. Note that
+    //! [zkhash](https://github.com/HorizenLabs/poseidon2) uses the S-box type
+    //! `1`, so its round constants are different.";
+
 pub fn generate_parameters(_opts: Options) -> Result<(), anyhow::Error> {
-    // git clone hadehash into target
-    // create params to compute files in target
-    // loop over files in dir target/params/
-    // the line after Round constants for GF(p):
-    // remove [ ], split at , parse
-    if !Path::new("./target/hadeshash").exists() {
-        let _git_result = std::process::Command::new("git")
-            .arg("clone")
-            .arg("https://extgit.iaik.tugraz.at/krypto/hadeshash.git")
-            .arg("./target/hadeshash")
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .output()
-            .map_err(|e| anyhow::format_err!("git clone failed: {}", e.to_string()))?;
-    }
-    if !Path::new("./target/params").exists() {
-        let _mkdir_result = std::process::Command::new("mkdir")
-            .arg("./target/params")
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .output()
-            .map_err(|e| anyhow::format_err!("mkdir failed: {}", e.to_string()))?;
-    }
-    for i in 2..14 {
-        let path = format!("./target/params/poseidon_params_bn254_x5_{}", i);
-
-        if !Path::new(&path).exists() {
-            println!(
-                "Generating Parameters partial rounds {} t = {}",
-                PARTIAL_ROUNDS[i - 2],
-                i
-            );
-            let arg = "./target/hadeshash/code/generate_parameters_grain.sage".to_string();
-
-            let output = std::process::Command::new("sage")
-                .args([
-                    arg,
-                    FIELD.to_string(),
-                    SBOX.to_string(),
-                    FIELD_ELEMENT_BIT_SIZE.to_string(),
-                    format!("{}", i),
-                    FULL_ROUNDS.to_string(),
-                    format!("{}", PARTIAL_ROUNDS[i - 2]),
-                    MODULUS_HEX.to_string(),
-                ])
-                .output()?;
-            let mut file = File::create(&path)?;
-            file.write_all(&output.stdout)?;
-        }
+    generate_bn254_x5()?;
+    generate_poseidon2_bn254_x5()?;
+    generate_for_field::<Fr>(&FieldParameters {
+        path: "./light-poseidon/src/parameters/bn254_inverse.rs",
+        field_name: "the BN254 scalar field",
+        sbox: Sbox::Inverse,
+        sbox_flag: Sbox::Inverse.grain_flag(),
+        sbox_flag_note: "",
+        max_width: 13,
+    })?;
+    generate_for_field::<ark_bls12_381::Fr>(&FieldParameters {
+        path: "./light-poseidon/src/parameters/bls12_381_x5.rs",
+        field_name: "the BLS12-381 scalar field",
+        sbox: Sbox::Power(ALPHA),
+        sbox_flag: 1,
+        sbox_flag_note: "
+    //!
+    //! Note that the S-box type in the LFSR seed is `1`, not `0`, like in
+    //! [neptune](https://github.com/argumentcomputer/neptune). The round
+    //! constants for *t = 9* are checked against the ones published by neptune,
+    //! the permutation itself is not checked against other implementations.",
+        max_width: 13,
+    })?;
+    generate_for_field::<pasta::Fp>(&FieldParameters {
+        path: "./light-poseidon/src/parameters/pallas_x5.rs",
+        field_name: "the base field of the Pallas curve",
+        sbox: Sbox::Power(ALPHA),
+        sbox_flag: 0,
+        sbox_flag_note: HALO2_SBOX_FLAG_NOTE,
+        max_width: 13,
+    })?;
+    generate_for_field::<pasta::Fq>(&FieldParameters {
+        path: "./light-poseidon/src/parameters/vesta_x5.rs",
+        field_name: "the base field of the Vesta curve",
+        sbox: Sbox::Power(ALPHA),
+        sbox_flag: 0,
+        sbox_flag_note: HALO2_SBOX_FLAG_NOTE,
+        max_width: 13,
+    })?;
+    generate_goldilocks_x7()?;
+
+    std::process::Command::new("cargo")
+        .arg("fmt")
+        .output()
+        .map_err(|e| anyhow::format_err!("cargo fmt failed: {}", e))?;
+    Ok(())
+}
+
+/// Returns the number of partial rounds for the BN254 x^5 parameters of the
+/// given width, rounded up to a multiple of the width like in the reference
+/// implementation and circomlib.
+fn partial_rounds(t: usize) -> Result<usize, anyhow::Error> {
+    let (full_rounds, partial_rounds) =
+        rounds::calc_round_numbers_for_field_circom::<Fr>(t, Sbox::Power(ALPHA), SECURITY_LEVEL)
+            .ok_or_else(|| anyhow::format_err!("no secure round numbers for t = {}", t))?;
+    if full_rounds != FULL_ROUNDS {
+        anyhow::bail!(
+            "unexpected number of full rounds for t = {}: {}",
+            t,
+            full_rounds
+        );
     }
+    Ok(partial_rounds)
+}
 
+fn generate_bn254_x5() -> Result<(), anyhow::Error> {
     let mut code = String::new();
     code += "
     //! Constants and MDS matrix for the BN254 curve with the following properties:
@@ -113,7 +151,9 @@ pub fn generate_parameters(_opts: Options) -> Result<(), anyhow::Error> {
     //!
     //! Those parameters are used for our Poseidon hash implementation.
     //!
-    //! They were generated using the official script from the Poseidon paper:
+    //! They were generated with the Grain LFSR (see the
+    //! [`grain`](crate::grain) module), which produces the same output as
+    //! the official script from the Poseidon paper:
     //! [generate_parameters_grain.sage](https://extgit.iaik.tugraz.at/krypto/hadeshash/-/blob/master/code/generate_parameters_grain.sage)
     //! with the following parameters:
     //!
@@ -121,9 +161,27 @@ pub fn generate_parameters(_opts: Options) -> Result<(), anyhow::Error> {
     //! sage generate_parameters_grain.sage 1 0 254 3 8 57 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
     //! ```
     pub const FULL_ROUNDS: usize = 8;
-    pub const PARTIAL_ROUNDS: [usize; 15] = [56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64];
+    ";
+    code += &format!(
+        "pub const PARTIAL_ROUNDS: [usize; {}] = {:?};",
+        PARTIAL_ROUNDS_LEN,
+        (2..PARTIAL_ROUNDS_LEN + 2)
+            .map(partial_rounds)
+            .collect::<Result<Vec<_>, _>>()?
+    );
+    code += "
     pub const ALPHA: u64 = 5;
 
+    use ark_bn254::Fr;
+    use ark_ff::BigInt;
+    use crate::{borrowed::BorrowedPoseidonParameters, PoseidonError, Sbox};
+    #[cfg(feature = \"alloc\")]
+    use alloc::vec;
+    #[cfg(feature = \"alloc\")]
+    use ark_ff::PrimeField;
+    #[cfg(feature = \"alloc\")]
+    use crate::PoseidonParameters;
+
     /// Returns Poseidon parameters for the BN254 curve with the following
     /// properties:
     ///
@@ -133,82 +191,61 @@ pub fn generate_parameters(_opts: Options) -> Result<(), anyhow::Error> {
     ///
     /// The argument of this macro is a type which implements
     /// [`ark_ff::PrimeField`](ark_ff::PrimeField).
-    use ark_ff::PrimeField;
-    use crate::{PoseidonParameters, PoseidonError};
     // to avoid warnings when width_limit_13 feature is used
     #[allow(unused_variables)]
-    pub fn get_poseidon_parameters<F: PrimeField + std::convert::From<ark_ff::BigInteger256>>(t: u8) -> Result<PoseidonParameters<F>, PoseidonError> {
+    #[cfg(feature = \"alloc\")]
+    pub fn get_poseidon_parameters<F: PrimeField + core::convert::From<ark_ff::BigInteger256>>(t: u8) -> Result<PoseidonParameters<F>, PoseidonError> {
     if t == 0_u8 {
         Err(PoseidonError::InvalidWidthCircom {
             width: t as usize,
             max_limit: 13usize,
         })\n
     }\n";
+    let mut statics = String::new();
     for t in 2..14 {
-        let path = format!("./target/params/poseidon_params_bn254_x5_{}", t);
-        let mut file = File::open(path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        let lines = contents.lines();
-
-        for line in lines {
-            if line.starts_with("['") {
-                code += &[
-                    String::from("\telse if "),
-                    t.to_string(),
-                    String::from(
-                        " == t {
-                        let ark = vec![\n",
-                    ),
-                ]
-                .concat();
-
-                let line_processed = line
-                    .strip_prefix('[')
-                    .unwrap()
-                    .strip_suffix(']')
-                    .unwrap()
-                    .trim()
-                    .split(", ")
-                    .collect::<Vec<&str>>();
-                let _x: Vec<&str> = line_processed
-                    .iter()
-                    .map(|elem| {
-                        let str = String::from(
-                            elem.strip_prefix('\'').unwrap().strip_suffix('\'').unwrap(),
-                        );
-                        code += &get_fr_string(&str);
-
-                        return "1";
-                    })
-                    .collect();
-                code += "\t\t\t\t];\n";
-            } else if line.starts_with(" [['") {
-                code += &String::from("\t\t\t\tlet mds = vec![\n");
-                let line_processed = line.split('[').collect::<Vec<&str>>();
-
-                let _x: Vec<&str> = line_processed
-                    .iter()
-                    .map(|e| {
-                        if e.starts_with('\'') {
-                            code += &String::from("\t\t\t\t\tvec![\n");
-                        }
-
-                        for elem in e.split('\'') {
-                            if elem.starts_with("0x") {
-                                code += &get_fr_string(&String::from(elem));
-                            }
-                        }
-                        if e.starts_with('\'') {
-                            code += &String::from("\t\t\t\t\t],\n");
-                        }
-
-                        return "1";
-                    })
-                    .collect();
-                code += &String::from("\t\t];\n");
+        let partial_rounds = partial_rounds(t)?;
+        println!(
+            "Generating Parameters partial rounds {} t = {}",
+            partial_rounds, t
+        );
+        let params =
+            grain::generate_parameters::<Fr>(t, FULL_ROUNDS, partial_rounds, Sbox::Power(ALPHA));
+
+        code += &format!("\telse if {} == t {{\n\t\t\t\tlet ark = vec![\n", t);
+        for c in params.ark.iter() {
+            code += &get_fr_string(c);
+        }
+        code += "\t\t\t\t];\n";
+        statics += &format!(
+            "\n/// Round constants for *t = {t}*, in Montgomery form.\npub static ARK_{t}: [Fr; {}] = [\n",
+            params.ark.len()
+        );
+        for c in params.ark.iter() {
+            statics += &get_mont_fr_string(c);
+        }
+        statics += "];\n";
+        statics += &format!(
+            "\n/// MDS matrix for *t = {t}*, in Montgomery form.\npub static MDS_{t}: [[Fr; {t}]; {t}] = [\n"
+        );
+        for row in params.mds.iter() {
+            statics += "[\n";
+            for m in row.iter() {
+                statics += &get_mont_fr_string(m);
+            }
+            statics += "],\n";
+        }
+        statics += "];\n";
+
+        code += "\t\t\t\tlet mds = vec![\n";
+        for row in params.mds.iter() {
+            code += "\t\t\t\t\tvec![\n";
+            for m in row.iter() {
+                code += &get_fr_string(m);
             }
+            code += "\t\t\t\t\t],\n";
         }
+        code += "\t\t];\n";
+
         code += &format!(
             "Ok(crate::PoseidonParameters::new(
             ark,
@@ -216,7 +253,7 @@ pub fn generate_parameters(_opts: Options) -> Result<(), anyhow::Error> {
             FULL_ROUNDS,
             PARTIAL_ROUNDS[{}],
             t.into(),
-            ALPHA,
+            crate::Sbox::Power(ALPHA),
             ))\n",
             t - 2
         );
@@ -231,28 +268,427 @@ pub fn generate_parameters(_opts: Options) -> Result<(), anyhow::Error> {
     }";
     code += "}\n";
 
-    let path = "./light-poseidon/src/parameters/bn254_x5.rs";
+    code += "
+    /// Returns Poseidon parameters for the BN254 curve like
+    /// [`get_poseidon_parameters`], but borrowed from the `static` arrays,
+    /// which are already in Montgomery form. Nothing is converted or
+    /// allocated.
+    pub fn get_borrowed_poseidon_parameters(t: u8) -> Result<BorrowedPoseidonParameters<'static, Fr>, PoseidonError> {
+        let (ark, mds): (&'static [Fr], &'static [Fr]) = match t {
+    ";
+    for t in 2..14 {
+        code += &format!("{t} => (&ARK_{t}, MDS_{t}.as_flattened()),\n");
+    }
+    code += "_ => {
+                return Err(PoseidonError::InvalidWidthCircom {
+                    width: t as usize,
+                    max_limit: 13usize,
+                })
+            }
+        };
+        BorrowedPoseidonParameters::new(
+            ark,
+            mds,
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS[t as usize - 2],
+            t.into(),
+            Sbox::Power(ALPHA),
+        )
+    }
+    ";
+    code += &statics;
+
+    write_generated_file("./light-poseidon/src/parameters/bn254_x5.rs", &code)
+}
+
+fn generate_poseidon2_bn254_x5() -> Result<(), anyhow::Error> {
+    let max_width = POSEIDON2_WIDTHS[POSEIDON2_WIDTHS.len() - 1];
+    let mut code = String::new();
+    code += "
+    //! Constants and internal matrices of Poseidon2 for the BN254 curve with the
+    //! following properties:
+    //!
+    //! * x^5 S-boxes
+    //! * width - *2 ≤ t ≤ 4*
+    //! * 8 full rounds and 56 partial rounds
+    //!
+    //! Round constants were generated with the Grain LFSR (see the
+    //! [`grain`](crate::grain) module), which produces the same output as the
+    //! [poseidon2_rust_params.sage](https://github.com/HorizenLabs/poseidon2/blob/main/poseidon2_rust_params.sage)
+    //! script from the reference implementation with the following parameters:
+    //!
+    //! ```bash
+    //! sage poseidon2_rust_params.sage 1 0 254 <t> 8 56 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
+    //! ```
+    //!
+    //! The internal matrices for *t = 2* and *t = 3* are the fixed ones
+    //! (`[[2, 1], [1, 3]]` and `[[2, 1, 1], [1, 2, 1], [1, 1, 3]]`). The diagonal
+    //! of the internal matrix for *t = 4* is the one used by the reference
+    //! implementation and Barretenberg.
+    pub const FULL_ROUNDS: usize = 8;
+    pub const PARTIAL_ROUNDS: usize = 56;
+    pub const ALPHA: u64 = 5;
+
+    use alloc::vec;
+    use crate::{poseidon2::Poseidon2Parameters, PoseidonError, Sbox};
+    use ark_ff::PrimeField;
+
+    /// Returns Poseidon2 parameters for the BN254 curve with the following
+    /// properties:
+    ///
+    /// * x^5 S-boxes
+    /// * width - *2 ≤ t ≤ 4*
+    /// * 8 full rounds and 56 partial rounds
+    ///
+    /// The type argument is a type which implements
+    /// [`ark_ff::PrimeField`](ark_ff::PrimeField).
+    pub fn get_poseidon2_parameters<F: PrimeField + core::convert::From<ark_ff::BigInteger256>>(t: u8) -> Result<Poseidon2Parameters<F>, PoseidonError> {
+    if t == 0_u8 {
+        Err(PoseidonError::InvalidWidth {
+            width: t as usize,
+            max_limit: ";
+    code += &format!("{}usize,\n}})\n}}", max_width);
+    for t in POSEIDON2_WIDTHS {
+        println!("Generating Poseidon2 parameters t = {}", t);
+        let ark = grain::generate_poseidon2_round_constants::<Fr>(
+            t,
+            FULL_ROUNDS,
+            POSEIDON2_PARTIAL_ROUNDS,
+        );
+
+        code += &format!(" else if {} == t {{\nlet ark = vec![\n", t);
+        for c in ark.iter() {
+            code += &get_fr_string(c);
+        }
+        code += "];\nlet mat_internal_diag_m_1 = vec![\n";
+        for d in poseidon2_mat_internal_diag_m_1(t)? {
+            code += &get_fr_string(&d);
+        }
+        code += "];\n";
+        code += "Poseidon2Parameters::new(
+            ark,
+            mat_internal_diag_m_1,
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            t.into(),
+            Sbox::Power(ALPHA),
+            )\n}";
+    }
+    code += &format!(
+        " else {{
+        Err(PoseidonError::InvalidWidth {{
+            width: t as usize,
+            max_limit: {}usize,
+        }})
+    }}
+    }}\n",
+        max_width
+    );
+
+    write_generated_file(
+        "./light-poseidon/src/parameters/poseidon2_bn254_x5.rs",
+        &code,
+    )
+}
+
+/// Poseidon parameters for a prime field, generated for widths from 2 to
+/// `max_width`, with the minimal secure numbers of rounds.
+struct FieldParameters<'a> {
+    path: &'a str,
+    field_name: &'a str,
+    sbox: Sbox,
+    /// S-box type used in the seed of the Grain LFSR.
+    sbox_flag: u64,
+    /// Documentation appended to the module docs, explaining the S-box type.
+    sbox_flag_note: &'a str,
+    max_width: usize,
+}
+
+fn generate_for_field<F: PrimeField<BigInt = BigInteger256>>(
+    params: &FieldParameters,
+) -> Result<(), anyhow::Error> {
+    let (sbox_name, sbox_const, sbox_expr) = match params.sbox {
+        Sbox::Power(alpha) => (
+            format!("x^{}", alpha),
+            format!("pub const ALPHA: u64 = {};", alpha),
+            "Sbox::Power(ALPHA)",
+        ),
+        Sbox::Inverse => ("x^-1".to_string(), String::new(), "Sbox::Inverse"),
+    };
+    let widths = 2..params.max_width + 1;
+    let mut partial_rounds = Vec::new();
+    for t in widths.clone() {
+        let (full_rounds, rp) =
+            rounds::calc_round_numbers_for_field::<F>(t, params.sbox, SECURITY_LEVEL)
+                .ok_or_else(|| anyhow::format_err!("no secure round numbers for t = {}", t))?;
+        if full_rounds != FULL_ROUNDS {
+            anyhow::bail!(
+                "unexpected number of full rounds for t = {}: {}",
+                t,
+                full_rounds
+            );
+        }
+        partial_rounds.push(rp);
+    }
+
+    let mut code = format!(
+        "
+    //! Constants and MDS matrices for {field_name} with the following
+    //! properties:
+    //!
+    //! * {sbox_name} S-boxes
+    //! * width - *2 ≤ t ≤ {max_width}*
+    //! * inputs - *1 ≤ n ≤ {max_inputs}*
+    //! * 8 full rounds and partial rounds depending on *t*: *{partial_rounds:?}*
+    //!
+    //! The numbers of rounds were calculated with the [`rounds`](crate::rounds)
+    //! module. The parameters were generated with the Grain LFSR (see the
+    //! [`grain`](crate::grain) module), which produces the same output as the
+    //! official script from the Poseidon paper:
+    //! [generate_parameters_grain.sage](https://extgit.iaik.tugraz.at/krypto/hadeshash/-/blob/master/code/generate_parameters_grain.sage)
+    //! with the following parameters:
+    //!
+    //! ```bash
+    //! sage generate_parameters_grain.sage 1 {sbox_flag} {field_size} <t> 8 <partial rounds> 0x{modulus}
+    //! ```{sbox_flag_note}
+    pub const FULL_ROUNDS: usize = {FULL_ROUNDS};
+    pub const PARTIAL_ROUNDS: [usize; {len}] = {partial_rounds:?};
+    {sbox_const}
+
+    use alloc::vec;
+    use ark_ff::PrimeField;
+    use crate::{{PoseidonParameters, PoseidonError, Sbox}};
+
+    /// Returns Poseidon parameters for {field_name} with {sbox_name}
+    /// S-boxes and the given width (*2 ≤ t ≤ {max_width}*).
+    ///
+    /// The type argument is a type which implements
+    /// [`ark_ff::PrimeField`](ark_ff::PrimeField).
+    pub fn get_poseidon_parameters<F: PrimeField + core::convert::From<ark_ff::BigInteger256>>(t: u8) -> Result<PoseidonParameters<F>, PoseidonError> {{
+    ",
+        field_name = params.field_name,
+        max_width = params.max_width,
+        max_inputs = params.max_width - 1,
+        partial_rounds = partial_rounds,
+        sbox_flag = params.sbox_flag,
+        field_size = F::MODULUS_BIT_SIZE,
+        modulus = hex::encode(F::MODULUS.to_bytes_be()),
+        sbox_flag_note = params.sbox_flag_note,
+        len = partial_rounds.len(),
+        sbox_name = sbox_name,
+        sbox_const = sbox_const,
+    );
+
+    for (i, t) in widths.enumerate() {
+        println!(
+            "Generating parameters for {} partial rounds {} t = {}",
+            params.field_name, partial_rounds[i], t
+        );
+        let poseidon_params = grain::generate_parameters_with_sbox_flag::<F>(
+            params.sbox_flag,
+            t,
+            FULL_ROUNDS,
+            partial_rounds[i],
+            params.sbox,
+        );
+
+        code += &format!(
+            "if {} == t {{
+let ark = vec![
+",
+            t
+        );
+        for c in poseidon_params.ark.iter() {
+            code += &get_fr_string(c);
+        }
+        code += "];
+let mds = vec![
+";
+        for row in poseidon_params.mds.iter() {
+            code += "vec![
+";
+            for m in row.iter() {
+                code += &get_fr_string(m);
+            }
+            code += "],
+";
+        }
+        code += &format!(
+            "];
+Ok(PoseidonParameters::new(
+            ark,
+            mds,
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS[{}],
+            t.into(),
+            {},
+            ))
+}} else ",
+            i, sbox_expr
+        );
+    }
+    code += &format!(
+        "{{
+        Err(PoseidonError::InvalidWidth {{
+            width: t as usize,
+            max_limit: {}usize,
+        }})
+    }}
+    }}
+",
+        params.max_width
+    );
+
+    write_generated_file(params.path, &code)
+}
+
+/// Generates the Plonky2 parameters for Goldilocks. Unlike other parameters,
+/// the round constants are sampled with ChaCha8 seeded with zero, like in the
+/// `generate_constants` binary of Plonky2.
+fn generate_goldilocks_x7() -> Result<(), anyhow::Error> {
+    let (full_rounds, partial_rounds) = rounds::calc_round_numbers_for_field::<Goldilocks>(
+        GOLDILOCKS_WIDTH,
+        Sbox::Power(GOLDILOCKS_ALPHA),
+        SECURITY_LEVEL,
+    )
+    .ok_or_else(|| anyhow::format_err!("no secure round numbers for Goldilocks"))?;
+    if (full_rounds, partial_rounds) != (GOLDILOCKS_FULL_ROUNDS, GOLDILOCKS_PARTIAL_ROUNDS) {
+        anyhow::bail!(
+            "unexpected numbers of rounds for Goldilocks: {} {}",
+            full_rounds,
+            partial_rounds
+        );
+    }
+
+    println!("Generating Goldilocks parameters t = {}", GOLDILOCKS_WIDTH);
+    let modulus = Goldilocks::MODULUS.0[0];
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let ark: Vec<u64> = (0..GOLDILOCKS_WIDTH * (full_rounds + partial_rounds))
+        .map(|_| rng.gen_range(0..modulus))
+        .collect();
+
+    let code = format!(
+        "
+    //! Constants and MDS matrix of [Plonky2](https://github.com/0xPolygonZero/plonky2)
+    //! Poseidon for the Goldilocks field (see the
+    //! [`goldilocks`](crate::goldilocks) module) with the following
+    //! properties:
+    //!
+    //! * x^7 S-boxes
+    //! * width - *t = 12* (8 rate and 4 capacity elements)
+    //! * 8 full rounds and 22 partial rounds
+    //!
+    //! The numbers of rounds match the ones calculated with the
+    //! [`rounds`](crate::rounds) module. The round constants were sampled
+    //! uniformly from the field with ChaCha8 seeded with zero, like in
+    //! Plonky2. The MDS matrix is the sum of a circulant matrix and a
+    //! diagonal matrix: `M[i][j] = MDS_MATRIX_CIRC[(j - i) mod t]`, plus
+    //! `MDS_MATRIX_DIAG[i]` on the diagonal.
+    pub const FULL_ROUNDS: usize = {full_rounds};
+    pub const PARTIAL_ROUNDS: usize = {partial_rounds};
+    pub const ALPHA: u64 = {alpha};
+    pub const WIDTH: usize = {width};
+
+    pub const MDS_MATRIX_CIRC: [u64; WIDTH] = {circ:?};
+    pub const MDS_MATRIX_DIAG: [u64; WIDTH] = {diag:?};
+    pub const ROUND_CONSTANTS: [u64; {ark_len}] = [{ark}];
+
+    use ark_ff::PrimeField;
+    use crate::{{PoseidonParameters, PoseidonError, Sbox}};
+
+    /// Returns Plonky2 Poseidon parameters for the Goldilocks field. The only
+    /// supported width is *t = 12*.
+    ///
+    /// The type argument is a type which implements
+    /// [`ark_ff::PrimeField`](ark_ff::PrimeField).
+    pub fn get_poseidon_parameters<F: PrimeField>(t: u8) -> Result<PoseidonParameters<F>, PoseidonError> {{
+        if t as usize != WIDTH {{
+            return Err(PoseidonError::InvalidWidthGoldilocks {{ width: t as usize }});
+        }}
+
+        let ark = ROUND_CONSTANTS.iter().map(|c| F::from(*c)).collect();
+        let mds = (0..WIDTH)
+            .map(|i| {{
+                (0..WIDTH)
+                    .map(|j| {{
+                        let a = F::from(MDS_MATRIX_CIRC[(j + WIDTH - i) % WIDTH]);
+                        if i == j {{
+                            a + F::from(MDS_MATRIX_DIAG[i])
+                        }} else {{
+                            a
+                        }}
+                    }})
+                    .collect()
+            }})
+            .collect();
+
+        Ok(PoseidonParameters::new(
+            ark,
+            mds,
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            WIDTH,
+            Sbox::Power(ALPHA),
+        ))
+    }}
+",
+        full_rounds = full_rounds,
+        partial_rounds = partial_rounds,
+        alpha = GOLDILOCKS_ALPHA,
+        width = GOLDILOCKS_WIDTH,
+        circ = GOLDILOCKS_MDS_CIRC,
+        diag = GOLDILOCKS_MDS_DIAG,
+        ark_len = ark.len(),
+        ark = ark
+            .iter()
+            .map(|c| format!("{:#018x}", c))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+
+    write_generated_file("./light-poseidon/src/parameters/goldilocks_x7.rs", &code)
+}
+
+/// Returns the diagonal (minus one) of the Poseidon2 internal matrix for the
+/// given width. Fails for widths without a published diagonal.
+fn poseidon2_mat_internal_diag_m_1(t: usize) -> Result<Vec<Fr>, anyhow::Error> {
+    let diag = match t {
+        2 => vec![Fr::from(1u64), Fr::from(2u64)],
+        3 => vec![Fr::from(1u64), Fr::from(1u64), Fr::from(2u64)],
+        // Diagonal used by the reference implementation and Barretenberg.
+        4 => [
+            "10dc6e9c006ea38b04b1e03b4bd9490c0d03f98929ca1d7fb56821fd19d3b6e7",
+            "0c28145b6a44df3e0149b3d0a30b3bb599df9756d4dd9b84a86b38cfb45a740b",
+            "00544b8338791518b2c7645a50392798b21f75bb60e3596170067d00141cac15",
+            "222c01175718386f2e2e82eb122789e352e105a3b8fa852613bc534433ee428b",
+        ]
+        .iter()
+        .map(|d| hex::decode(d).map(|d| Fr::from_be_bytes_mod_order(&d)))
+        .collect::<Result<_, _>>()?,
+        _ => anyhow::bail!("no published Poseidon2 internal matrix for t = {}", t),
+    };
+    Ok(diag)
+}
+
+fn write_generated_file(path: &str, code: &str) -> Result<(), anyhow::Error> {
     let mut file = File::create(path)?;
     file.write_all(b"// This file is generated by xtask. Do not edit it manually.\n\n")?;
     // file.write_all(&rustfmt(code.to_string())?)?;
     write!(file, "{}", code)?;
     println!("Poseidon Parameters written to {:?}", path);
-    std::process::Command::new("cargo")
-        .arg("fmt")
-        .output()
-        .map_err(|e| anyhow::format_err!("cargo fmt failed: {}", e.to_string()))?;
     Ok(())
 }
 
-fn get_fr_string(string: &str) -> String {
-    let mut bytes = hex::decode(string.split_at(2).1).unwrap();
+/// Returns a `const` expression of the BN254 scalar field element, with the
+/// limbs of its Montgomery representation.
+fn get_mont_fr_string(element: &Fr) -> String {
+    format!("Fr::new_unchecked(BigInt::new({:?})),\n", (element.0).0)
+}
+
+fn get_fr_string<F: PrimeField<BigInt = BigInteger256>>(element: &F) -> String {
     let mut tmp_str = String::from("F::from(ark_ff::BigInteger256::new([\n");
-    bytes.reverse();
-    for i in 0..4 {
-        tmp_str += &format!(
-            "\t{},\n",
-            u64::from_le_bytes(bytes[i * 8..(i + 1) * 8].try_into().unwrap())
-        );
+    for limb in element.into_bigint().0 {
+        tmp_str += &format!("\t{},\n", limb);
     }
 
     tmp_str += "])),\n";